@@ -0,0 +1,22 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use Genetic_Alg::ops;
+use Genetic_Alg::problems::cournot_fitness;
+use rand::Rng;
+
+fn phases(c:&mut Criterion)
+{
+    let mut rng = rand::thread_rng();
+    let n = 1000;
+    let data:Vec<u64> = (0..n).map(|_| rng.gen_range(0..1024)).collect();
+    let probabilities = vec![1.0 / n as f64; n];
+    let pairs = ops::pair_up(n, &mut rng);
+
+    c.bench_function("evaluate", |b| b.iter(|| ops::evaluate(black_box(&data), n, cournot_fitness)));
+    c.bench_function("select", |b| b.iter(|| ops::select(black_box(&probabilities), n, &mut rng)));
+    c.bench_function("pair_up", |b| b.iter(|| ops::pair_up(black_box(n), &mut rng)));
+    c.bench_function("crossover", |b| b.iter(|| ops::crossover(black_box(&data), &pairs, 0.322, 10, 2, &mut rng)));
+    c.bench_function("mutate", |b| b.iter(|| ops::mutate(black_box(&data), 0.00522, 10, &mut rng)));
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
@@ -0,0 +1,28 @@
+//! Evolves giant tours for a random capacitated VRP instance with a `PermutationRun`: edge
+//! recombination, a mix of permutation mutations, and the capacity as a penalized constraint.
+//!
+//! Run with `cargo run --example cvrp`.
+use Genetic_Alg::{Cvrp, MutationMix};
+use rand::Rng;
+
+fn main()
+{
+    let mut rng = rand::thread_rng();
+    let points:Vec<(f64, f64)> = (0..31).map(|_| (rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0))).collect();
+    let demands:Vec<f64> = (0..31).map(|i| if i == 0 { 0.0 } else { rng.gen_range(1..=10) as f64 }).collect();
+    let cvrp = Cvrp::from_points(&points, demands, 40.0);
+
+    let mut run = cvrp.build_run(60, 1000.0);
+    run.mutation = MutationMix::uniform();
+    run.Pmut = 0.2;
+    for _ in 0..4 {
+        run.run(50);
+        println!("generation {}: best fitness {:.1}", run.generation(), run.best().unwrap().fitness);
+    }
+
+    let solution = cvrp.split(&run.best().unwrap().order);
+    println!("{} routes, distance {:.1}, violation {}", solution.routes.len(), solution.distance, solution.violation);
+    for route in solution.routes {
+        println!("  {:?}", route);
+    }
+}
@@ -0,0 +1,41 @@
+//! Minimal `run_ga` plugin, build with `cargo build --example onemax_plugin` and pass the
+//! resulting library with `run_ga --plugin target/debug/examples/libonemax_plugin.so`.
+
+/// Same layout as `Genetic_Alg::GenomePair`.
+#[repr(C)]
+pub struct GenomePair
+{
+    pub a:u64,
+    pub b:u64,
+}
+
+#[no_mangle]
+pub extern "C" fn ga_fitness(data:u64, _data_sum:u64, _n:usize) -> f64
+{
+    data.count_ones() as f64
+}
+
+#[no_mangle]
+pub extern "C" fn ga_mutate(data:u64, length:u8, random:u64) -> u64
+{
+    data ^ 1 << (random % length as u64)
+}
+
+/// Uniform crossover, each bit of `random` picks the parent the first child takes it from.
+#[no_mangle]
+pub extern "C" fn ga_crossover(a:u64, b:u64, _length:u8, random:u64) -> GenomePair
+{
+    GenomePair {a:(a & random) | (b & !random), b:(b & random) | (a & !random)}
+}
+
+/// Binary tournament on the weights.
+///
+/// # Safety
+/// `weights` must point to `len` readable values.
+#[no_mangle]
+pub unsafe extern "C" fn ga_select(weights:*const f64, len:usize, random:u64) -> usize
+{
+    let weights = std::slice::from_raw_parts(weights, len);
+    let (i, j) = ((random % len as u64) as usize, ((random >> 32) % len as u64) as usize);
+    if weights[j] > weights[i] { j } else { i }
+}
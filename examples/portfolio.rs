@@ -0,0 +1,58 @@
+//! Mean-variance portfolio optimization with a small real-coded GA: blend crossover, uniform
+//! perturbation and repair back onto the budget after every change.
+//!
+//! Run with `cargo run --example portfolio [RETURNS.csv COVARIANCE.csv]`, without files a
+//! four-asset example is used.
+use Genetic_Alg::Portfolio;
+use rand::Rng;
+use std::env;
+
+fn main()
+{
+    let args:Vec<String> = env::args().skip(1).collect();
+    let portfolio = if args.len() == 2 {
+        Portfolio::from_csv(&args[0], &args[1], 3.0).unwrap_or_else(|e| panic!("could not load portfolio: {}", e))
+    }else{
+        Portfolio::new(vec![0.12, 0.10, 0.07, 0.03],
+            vec![vec![0.090, 0.030, 0.010, 0.000],
+                 vec![0.030, 0.060, 0.010, 0.000],
+                 vec![0.010, 0.010, 0.020, 0.000],
+                 vec![0.000, 0.000, 0.000, 0.001]], 3.0)
+    };
+
+    let mut rng = rand::thread_rng();
+    let assets = portfolio.assets();
+    let mut population:Vec<Vec<f64>> = (0..40).map(|_| {
+        let mut weights:Vec<f64> = (0..assets).map(|_| rng.gen()).collect();
+        Portfolio::repair(&mut weights);
+        weights
+    }).collect();
+
+    for _ in 0..200 {
+        let fitness:Vec<f64> = population.iter().map(|w| portfolio.fitness(w)).collect();
+        let best = (0..population.len()).max_by(|&a, &b| fitness[a].total_cmp(&fitness[b])).unwrap();
+        let mut next = vec![population[best].clone()];
+        while next.len() < population.len() {
+            let a = tournament(&fitness, &mut rng);
+            let b = tournament(&fitness, &mut rng);
+            let mut child:Vec<f64> = population[a].iter().zip(&population[b]).map(|(x, y)| {
+                let alpha:f64 = rng.gen_range(-0.25..1.25);
+                x + alpha * (y - x) + if rng.gen::<f64>() < 0.1 { rng.gen_range(-0.1..0.1) } else { 0.0 }
+            }).collect();
+            Portfolio::repair(&mut child);
+            next.push(child);
+        }
+        population = next;
+    }
+
+    let best = population.iter().max_by(|a, b| portfolio.fitness(a).total_cmp(&portfolio.fitness(b))).unwrap();
+    println!("weights {:?}", best.iter().map(|w| (w * 1000.0).round() / 1000.0).collect::<Vec<f64>>());
+    println!("expected return {:.4}, variance {:.4}", portfolio.expected_return(best), portfolio.variance(best));
+}
+
+fn tournament<R:Rng>(fitness:&[f64], rng:&mut R) -> usize
+{
+    let a = rng.gen_range(0..fitness.len());
+    let b = rng.gen_range(0..fitness.len());
+    if fitness[a] >= fitness[b] { a } else { b }
+}
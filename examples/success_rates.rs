@@ -0,0 +1,13 @@
+//! Success rate, mean evaluations to success and ERT of the real-coded GA on every continuous
+//! benchmark in two dimensions.
+//!
+//! Run with `cargo run --release --example success_rates`.
+use Genetic_Alg::{format_table, Benchmark, SuccessReport};
+
+fn main()
+{
+    let reports:Vec<SuccessReport> = Benchmark::ALL.iter()
+        .map(|&benchmark| SuccessReport::benchmark(benchmark, 2, 50, 200, 20, 1e-2))
+        .collect();
+    print!("{}", format_table(&reports));
+}
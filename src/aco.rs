@@ -0,0 +1,235 @@
+//! Ant colony optimization (Ant System) for ordering problems. Ants build permutations one
+//! element at a time, preferring transitions with more pheromone and, when a heuristic is
+//! given, shorter distances. Pheromone evaporates every generation and each ant deposits
+//! `deposit / cost` on the edges of its closed tour. The cost is minimized.
+//!
+//! For GA+ACO hybrids, `construct` seeds permutation populations and `reinforce` lets
+//! solutions found by the GA feed back into the pheromone matrix.
+use crate::evolution::GenomeStats;
+use crate::harness::{trace_of, Trace};
+use crate::objective::Objective;
+use crate::permutation::PermutationChromosome;
+use crate::routing::DistanceMatrix;
+use rand::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+pub type OrderCostFn = Arc<dyn Fn(&[usize]) -> f64 + Send + Sync>;
+
+/// Pheromone never drops below this, so no transition becomes impossible.
+const MIN_PHEROMONE:f64 = 1e-9;
+
+#[derive(Clone)]
+pub struct AntColony
+{
+    /// `pheromone[i][j]` is the desirability of going from `i` to `j`, kept symmetric.
+    pub pheromone:Vec<Vec<f64>>,
+    pub ants:usize,
+    /// Weight of the pheromone in the transition probabilities.
+    pub alpha:f64,
+    /// Weight of the heuristic in the transition probabilities.
+    pub beta:f64,
+    /// Fraction of pheromone lost per generation.
+    pub evaporation:f64,
+    /// Pheromone an ant spreads over its tour, divided by the tour's cost.
+    pub deposit:f64,
+    heuristic:Option<Vec<Vec<f64>>>,
+    cost:OrderCostFn,
+    period:u32,
+    evaluations:usize,
+    history:Vec<GenomeStats>,
+    best:Option<PermutationChromosome>,
+    rng:StdRng,
+}
+
+impl fmt::Debug for AntColony
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("AntColony").field("len", &self.pheromone.len()).field("ants", &self.ants)
+            .field("alpha", &self.alpha).field("beta", &self.beta).field("evaporation", &self.evaporation)
+            .field("deposit", &self.deposit).field("heuristic", &self.heuristic.is_some())
+            .field("period", &self.period).field("best", &self.best).finish()
+    }
+}
+
+impl AntColony
+{
+    /// Minimizes `cost` over orderings of `0..len` without a heuristic. Starts with unit
+    /// pheromone, alpha 1, beta 2, evaporation 0.5 and deposit 1.
+    pub fn new<F>(len:usize, ants:usize, cost:F) -> Self
+        where F:Fn(&[usize]) -> f64 + Send + Sync + 'static
+    {
+        assert!(ants >= 1, "the colony needs at least one ant");
+        AntColony {pheromone:vec![vec![1.0; len]; len], ants, alpha:1.0, beta:2.0, evaporation:0.5, deposit:1.0,
+            heuristic:None, cost:Arc::new(cost), period:0, evaluations:0, history:Vec::new(), best:None,
+            rng:StdRng::from_entropy()}
+    }
+
+    /// Like `new` with every ant's tour drawn from an RNG seeded with `seed`.
+    pub fn with_seed<F>(len:usize, ants:usize, seed:u64, cost:F) -> Self
+        where F:Fn(&[usize]) -> f64 + Send + Sync + 'static
+    {
+        let mut colony = Self::new(len, ants, cost);
+        colony.set_seed(seed);
+        colony
+    }
+
+    pub fn set_seed(&mut self, seed:u64)
+    {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Minimizes the closed tour length through `distances`, with inverse distances as the
+    /// heuristic.
+    pub fn for_tours(distances:&DistanceMatrix, ants:usize) -> Self
+    {
+        let len = distances.len();
+        let heuristic = (0..len).map(|i| (0..len).map(|j| 1.0 / distances.get(i, j).max(f64::EPSILON)).collect()).collect();
+        let distances = distances.clone();
+        let mut colony = Self::new(len, ants, move |order:&[usize]| distances.tour_length(order));
+        colony.heuristic = Some(heuristic);
+        colony
+    }
+
+    pub fn best(&self) -> Option<&PermutationChromosome>
+    {
+        self.best.as_ref()
+    }
+
+    pub fn history(&self) -> &[GenomeStats]
+    {
+        &self.history
+    }
+
+    pub fn evaluations(&self) -> usize
+    {
+        self.evaluations
+    }
+
+    pub fn trace(&self) -> Trace
+    {
+        trace_of(&self.history, Objective::Minimize)
+    }
+
+    /// One ant's ordering, not evaluated.
+    pub fn construct<R:Rng + ?Sized>(&self, rng:&mut R) -> PermutationChromosome
+    {
+        let len = self.pheromone.len();
+        if len == 0 {
+            return PermutationChromosome::identity(0);
+        }
+        let mut visited = vec![false; len];
+        let mut order = Vec::with_capacity(len);
+        let mut current = rng.gen_range(0..len);
+        loop {
+            order.push(current);
+            visited[current] = true;
+            if order.len() == len {
+                return PermutationChromosome::new(order);
+            }
+            let weights:Vec<(usize, f64)> = (0..len).filter(|&j| !visited[j]).map(|j| {
+                let eta = self.heuristic.as_ref().map_or(1.0, |h| h[current][j]);
+                (j, self.pheromone[current][j].powf(self.alpha) * eta.powf(self.beta))
+            }).collect();
+            let total:f64 = weights.iter().map(|(_, w)| w).sum();
+            let mut target = rng.gen::<f64>() * total;
+            current = weights.last().unwrap().0;
+            for (j, w) in weights {
+                if target < w {
+                    current = j;
+                    break;
+                }
+                target -= w;
+            }
+        }
+    }
+
+    /// Deposits pheromone for evaluated orderings, whose `fitness` is their cost.
+    pub fn reinforce(&mut self, solutions:&[PermutationChromosome])
+    {
+        for solution in solutions {
+            let amount = self.deposit / solution.fitness.max(f64::EPSILON);
+            let order = &solution.order;
+            for k in 0..order.len() {
+                let (i, j) = (order[k], order[(k + 1) % order.len()]);
+                self.pheromone[i][j] += amount;
+                if i != j {
+                    self.pheromone[j][i] += amount;
+                }
+            }
+        }
+    }
+
+    pub fn run(&mut self, iterations:u32)
+    {
+        let mut rng = self.rng.clone();
+        for _ in 0..iterations {
+            self.period += 1;
+            let tours:Vec<PermutationChromosome> = (0..self.ants).map(|_| {
+                let mut tour = self.construct(&mut rng);
+                tour.fitness = (self.cost)(&tour.order);
+                tour
+            }).collect();
+            self.evaluations += tours.len();
+
+            let elite = tours.iter().min_by(|a, b| a.fitness.total_cmp(&b.fitness)).unwrap();
+            if self.best.as_ref().is_none_or(|best| elite.fitness < best.fitness) {
+                self.best = Some(elite.clone());
+            }
+            let mean_fitness = tours.iter().map(|t| t.fitness).sum::<f64>() / tours.len() as f64;
+            self.history.push(GenomeStats {generation:self.period, best_fitness:elite.fitness, mean_fitness, evaluations:self.evaluations,
+                feasible_fraction:1.0});
+
+            for row in self.pheromone.iter_mut() {
+                row.iter_mut().for_each(|tau| *tau = (*tau * (1.0 - self.evaporation)).max(MIN_PHEROMONE));
+            }
+            self.reinforce(&tours);
+        }
+        self.rng = rng;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permutation::is_permutation;
+
+    #[test]
+    fn finds_the_circle_tour()
+    {
+        // points on a circle, the optimal tour visits them in angular order
+        let points:Vec<(f64, f64)> = (0..12).map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / 12.0;
+            (angle.cos(), angle.sin())
+        }).collect();
+        let distances = DistanceMatrix::from_points(&points);
+        let optimum = distances.tour_length(&(0..12).collect::<Vec<usize>>());
+        let mut colony = AntColony::for_tours(&distances, 10);
+        colony.run(50);
+        let best = colony.best().unwrap();
+        assert!(is_permutation(&best.order));
+        assert!(best.fitness < optimum * 1.05, "{} vs {}", best.fitness, optimum);
+        assert_eq!(colony.trace().len(), 50);
+
+        let mut a = AntColony::for_tours(&distances, 10);
+        let mut b = AntColony::for_tours(&distances, 10);
+        a.set_seed(7);
+        b.set_seed(7);
+        a.run(5);
+        b.run(5);
+        assert_eq!((a.history(), a.best(), &a.pheromone), (b.history(), b.best(), &b.pheromone));
+    }
+
+    #[test]
+    fn reinforce_marks_tour_edges()
+    {
+        let mut colony = AntColony::new(4, 1, |_:&[usize]| 1.0);
+        let mut tour = PermutationChromosome::new(vec![0, 2, 1, 3]);
+        tour.fitness = 0.5;
+        colony.reinforce(&[tour]);
+        assert_eq!(colony.pheromone[0][2], 3.0);
+        assert_eq!(colony.pheromone[3][0], 3.0);
+        assert_eq!(colony.pheromone[0][1], 1.0);
+    }
+}
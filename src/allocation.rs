@@ -0,0 +1,115 @@
+//! Resource-allocation fitness: every individual claims `data` units of a resource shared by the
+//! whole population. The Cournot model is one instance of this coupling through the population
+//! sum; here the coupling is a budget, and claims beyond it are rationed or penalized.
+use crate::operators::Fitness;
+use crate::stats::PopulationStats;
+use crate::Chromosome;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rationing
+{
+    /// When total claims exceed the budget every claim is scaled down by `budget / total`.
+    Proportional,
+    /// Claims are granted in full but every individual loses `rate` per unit of total excess.
+    Penalty{rate:f64},
+}
+
+/// Fitness `value(granted) - unit_cost * claimed` with the grant decided by `rationing`.
+#[derive(Debug, Clone, Copy)]
+pub struct Allocation
+{
+    pub budget:f64,
+    pub value:fn(f64) -> f64,
+    pub unit_cost:f64,
+    pub rationing:Rationing,
+}
+
+fn linear(x:f64) -> f64
+{
+    x
+}
+
+impl Allocation
+{
+    pub fn new(budget:f64, value:fn(f64) -> f64) -> Self
+    {
+        Allocation {budget, value, unit_cost:0.0, rationing:Rationing::Proportional}
+    }
+
+    /// Every granted unit is worth one.
+    pub fn linear(budget:f64) -> Self
+    {
+        Self::new(budget, linear)
+    }
+
+    /// Diminishing returns, a granted amount `x` is worth `sqrt(x)`.
+    pub fn concave(budget:f64) -> Self
+    {
+        Self::new(budget, f64::sqrt)
+    }
+
+    pub fn with_unit_cost(mut self, unit_cost:f64) -> Self
+    {
+        self.unit_cost = unit_cost;
+        self
+    }
+
+    pub fn with_rationing(mut self, rationing:Rationing) -> Self
+    {
+        self.rationing = rationing;
+        self
+    }
+
+    /// Fitness of claiming `claim` when the population claims `total` in all.
+    pub fn payoff(&self, claim:u64, total:u64) -> f64
+    {
+        let (claim, total) = (claim as f64, total as f64);
+        let excess = (total - self.budget).max(0.0);
+        let granted = match self.rationing {
+            Rationing::Proportional if excess > 0.0 => claim * self.budget / total,
+            _ => claim,
+        };
+        let penalty = match self.rationing {
+            Rationing::Penalty{rate} => rate * excess,
+            Rationing::Proportional => 0.0,
+        };
+        (self.value)(granted) - self.unit_cost * claim - penalty
+    }
+}
+
+impl Fitness for Allocation
+{
+    fn evaluate(&self, ind:&Chromosome, context:&PopulationStats) -> f64
+    {
+        self.payoff(ind.data, context.data_sum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Run;
+
+    #[test]
+    fn rationing_scales_claims()
+    {
+        let allocation = Allocation::linear(100.0);
+        assert_eq!(allocation.payoff(30, 80), 30.0);
+        assert_eq!(allocation.payoff(50, 200), 25.0);
+        let penalized = allocation.with_rationing(Rationing::Penalty{rate:0.5});
+        assert_eq!(penalized.payoff(50, 200), 0.0);
+        assert_eq!(Allocation::concave(100.0).with_unit_cost(1.0).payoff(16, 16), 4.0 - 16.0);
+    }
+
+    #[test]
+    fn grants_never_exceed_budget()
+    {
+        let allocation = Allocation::linear(2000.0);
+        let mut test_run = Run::new(0.322, 0.05, 10, 20, 2).unwrap();
+        test_run.set_fitness_function(Box::new(allocation));
+        test_run.run(20).unwrap();
+        let total = test_run.population.data_sum();
+        let granted:f64 = test_run.population.data.iter().map(|claim| allocation.payoff(*claim, total)).sum();
+        assert!(granted <= 2000.0 + 1e-6, "granted {}", granted);
+    }
+}
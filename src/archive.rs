@@ -0,0 +1,233 @@
+use crate::error::GaError;
+use crate::genome::GenomeBuildHasher;
+use crate::objective::Objective;
+use crate::Chromosome;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+
+/// Objective extractor used when the archive is built with `EpsilonArchive::new`.
+pub fn fitness_objective(ind:&Chromosome) -> Vec<f64>
+{
+    vec![ind.fitness]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveEntry
+{
+    pub chromosome:Chromosome,
+    pub objectives:Vec<f64>,
+}
+
+/// External archive keeping epsilon-distinct, non-dominated solutions. Every objective is
+/// maximized or minimized as the `Objective` passed to `insert` says.
+///
+/// Every objective vector is mapped to a box of side `epsilons[i]`; at most one entry survives per
+/// box and entries whose box is dominated are dropped, so the archive stays bounded by the
+/// objective range divided by epsilon no matter how long the run is. `max_size` is an extra hard cap.
+#[derive(Debug, Clone)]
+pub struct EpsilonArchive
+{
+    epsilons:Vec<f64>,
+    objectives:fn(&Chromosome) -> Vec<f64>,
+    max_size:Option<usize>,
+    entries:Vec<ArchiveEntry>,
+    genomes:HashSet<u64, GenomeBuildHasher>,
+}
+
+impl EpsilonArchive
+{
+    pub fn new(epsilons:Vec<f64>) -> Self
+    {
+        Self::with_objectives(epsilons, fitness_objective)
+    }
+
+    pub fn with_objectives(epsilons:Vec<f64>, objectives:fn(&Chromosome) -> Vec<f64>) -> Self
+    {
+        assert!(epsilons.iter().all(|eps| *eps > 0.0), "epsilons must be positive");
+        EpsilonArchive {epsilons, objectives, max_size:None, entries:Vec::new(), genomes:HashSet::default()}
+    }
+
+    pub fn with_max_size(mut self, max_size:usize) -> Self
+    {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn entries(&self) -> &[ArchiveEntry]
+    {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+
+    // replaces the contents with entries saved from an archive with the same settings
+    pub(crate) fn restore_entries(&mut self, entries:Vec<ArchiveEntry>)
+    {
+        self.genomes = entries.iter().map(|entry| entry.chromosome.data).collect();
+        self.entries = entries;
+    }
+
+    // squared distance to the best corner of the box, used to break ties inside one box
+    fn corner_distance(&self, objectives:&[f64], box_index:&[i64]) -> f64
+    {
+        objectives.iter().zip(&self.epsilons).zip(box_index)
+            .map(|((f, eps), b)| ((*b + 1) as f64 * eps - f).powi(2))
+            .sum()
+    }
+
+    /// Offers `ind` to the archive, returns true if it was accepted.
+    pub fn insert(&mut self, ind:&Chromosome, objective:Objective) -> bool
+    {
+        let values = (self.objectives)(ind);
+        assert_eq!(values.len(), self.epsilons.len(), "one epsilon is needed per objective");
+        if values.iter().any(|f| !f.is_finite()) {
+            return false;
+        }
+        if self.genomes.contains(&ind.data) {
+            return false;
+        }
+
+        let objectives = maximized(&values, objective);
+        let new_box = box_index(&self.epsilons, &objectives);
+        for i in 0..self.entries.len() {
+            let old = maximized(&self.entries[i].objectives, objective);
+            let old_box = box_index(&self.epsilons, &old);
+            if old_box == new_box {
+                let replace = dominates(&objectives, &old)
+                    || (!dominates(&old, &objectives)
+                        && self.corner_distance(&objectives, &new_box) < self.corner_distance(&old, &old_box));
+                if replace {
+                    self.genomes.remove(&self.entries[i].chromosome.data);
+                    self.genomes.insert(ind.data);
+                    self.entries[i] = ArchiveEntry {chromosome:ind.clone(), objectives:values};
+                }
+                return replace;
+            }
+            if dominates(&old_box, &new_box) {
+                return false;
+            }
+        }
+
+        let before = self.entries.len();
+        let epsilons = &self.epsilons;
+        let genomes = &mut self.genomes;
+        self.entries.retain(|entry| {
+            let keep = !dominates(&new_box, &box_index(epsilons, &maximized(&entry.objectives, objective)));
+            if !keep {
+                genomes.remove(&entry.chromosome.data);
+            }
+            keep
+        });
+        if self.entries.len() == before && self.max_size.is_some_and(|max| before >= max) {
+            return false;
+        }
+        self.genomes.insert(ind.data);
+        self.entries.push(ArchiveEntry {chromosome:ind.clone(), objectives:values});
+        true
+    }
+
+    pub fn update(&mut self, population:&[Chromosome], objective:Objective)
+    {
+        for ind in population {
+            self.insert(ind, objective);
+        }
+    }
+}
+
+// the objective values turned around when minimizing, so larger is always better
+fn maximized(objectives:&[f64], objective:Objective) -> Vec<f64>
+{
+    match objective {
+        Objective::Maximize => objectives.to_vec(),
+        Objective::Minimize => objectives.iter().map(|f| -f).collect(),
+    }
+}
+
+fn box_index(epsilons:&[f64], objectives:&[f64]) -> Vec<i64>
+{
+    objectives.iter().zip(epsilons)
+        .map(|(f, eps)| (f / eps).floor() as i64)
+        .collect()
+}
+
+// weak Pareto dominance for maximization, a must be at least as good everywhere and better somewhere
+fn dominates<T:PartialOrd>(a:&[T], b:&[T]) -> bool
+{
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+pub fn save_archive_to_csv(archive:&EpsilonArchive, file_name:&str) -> Result<(), GaError>
+{
+    let file = File::create(file_name)?;
+    let mut writer = csv::Writer::from_writer(file);
+
+    let mut header = vec!["data".to_string(), "fitness".to_string()];
+    header.extend((0..archive.epsilons.len()).map(|i| format!("obj_{}", i)));
+    writer.write_record(&header)?;
+
+    for entry in archive.entries() {
+        let mut record = vec![entry.chromosome.data.to_string(), entry.chromosome.fitness.to_string()];
+        record.extend(entry.objectives.iter().map(|f| f.to_string()));
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ind(data:u64, fitness:f64) -> Chromosome
+    {
+        Chromosome {data, fitness, N:10, violation:0.0}
+    }
+
+    #[test]
+    fn keeps_one_entry_per_box()
+    {
+        let mut archive = EpsilonArchive::new(vec![10.0]);
+        assert!(archive.insert(&ind(1, 101.0), Objective::Maximize));
+        assert!(archive.insert(&ind(2, 105.0), Objective::Maximize));
+        assert!(!archive.insert(&ind(3, 102.0), Objective::Maximize));
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive.entries()[0].chromosome.data, 2);
+    }
+
+    #[test]
+    fn dominated_boxes_are_removed()
+    {
+        let mut archive = EpsilonArchive::new(vec![1.0]);
+        archive.update(&[ind(1, 3.0), ind(2, 7.0), ind(3, 5.0)], Objective::Maximize);
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive.entries()[0].chromosome.data, 2);
+
+        let mut archive = EpsilonArchive::new(vec![1.0]);
+        archive.update(&[ind(1, 3.0), ind(2, 7.0), ind(3, 5.0)], Objective::Minimize);
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive.entries()[0].chromosome.data, 1);
+        assert_eq!(archive.entries()[0].objectives, vec![3.0]);
+    }
+
+    #[test]
+    fn max_size_caps_growth()
+    {
+        fn two(ind:&Chromosome) -> Vec<f64>
+        {
+            vec![ind.data as f64, -(ind.data as f64)]
+        }
+        let mut archive = EpsilonArchive::with_objectives(vec![1.0, 1.0], two).with_max_size(2);
+        archive.update(&[ind(1, 0.0), ind(5, 0.0), ind(9, 0.0)], Objective::Maximize);
+        assert_eq!(archive.len(), 2);
+    }
+}
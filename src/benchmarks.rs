@@ -0,0 +1,126 @@
+//! Standard continuous test functions for validating and comparing real-valued operators. All
+//! are minimized, take any dimension and have a known global optimum.
+use crate::objective::Objective;
+use crate::real::RealRun;
+use std::f64::consts::{E, PI};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Benchmark
+{
+    /// Highly multimodal with a regular grid of local minima.
+    Rastrigin,
+    /// Nearly flat outer region with a deep hole at the origin.
+    Ackley,
+    /// Deceptive, the second best minimum is far from the global one.
+    Schwefel,
+    /// Many shallow local minima from the product term.
+    Griewank,
+    /// Unimodal in low dimensions but with a narrow curved valley.
+    Rosenbrock,
+}
+
+impl Benchmark
+{
+    pub const ALL:[Benchmark; 5] = [Benchmark::Rastrigin, Benchmark::Ackley, Benchmark::Schwefel,
+        Benchmark::Griewank, Benchmark::Rosenbrock];
+
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            Benchmark::Rastrigin => "rastrigin",
+            Benchmark::Ackley => "ackley",
+            Benchmark::Schwefel => "schwefel",
+            Benchmark::Griewank => "griewank",
+            Benchmark::Rosenbrock => "rosenbrock",
+        }
+    }
+
+    pub fn evaluate(self, x:&[f64]) -> f64
+    {
+        let d = x.len() as f64;
+        match self {
+            Benchmark::Rastrigin => 10.0 * d + x.iter().map(|v| v * v - 10.0 * (2.0 * PI * v).cos()).sum::<f64>(),
+            Benchmark::Ackley => {
+                if x.is_empty() {
+                    return 0.0;
+                }
+                let squares = x.iter().map(|v| v * v).sum::<f64>() / d;
+                let cosines = x.iter().map(|v| (2.0 * PI * v).cos()).sum::<f64>() / d;
+                -20.0 * (-0.2 * squares.sqrt()).exp() - cosines.exp() + 20.0 + E
+            }
+            Benchmark::Schwefel => 418.982_887_272_433_9 * d - x.iter().map(|v| v * v.abs().sqrt().sin()).sum::<f64>(),
+            Benchmark::Griewank => {
+                let sum = x.iter().map(|v| v * v).sum::<f64>() / 4000.0;
+                let product:f64 = x.iter().enumerate().map(|(i, v)| (v / ((i + 1) as f64).sqrt()).cos()).product();
+                sum - product + 1.0
+            }
+            Benchmark::Rosenbrock => x.windows(2).map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2)).sum(),
+        }
+    }
+
+    /// The usual search domain, the same for every coordinate.
+    pub fn bounds(self) -> (f64, f64)
+    {
+        match self {
+            Benchmark::Rastrigin => (-5.12, 5.12),
+            Benchmark::Ackley => (-32.768, 32.768),
+            Benchmark::Schwefel => (-500.0, 500.0),
+            Benchmark::Griewank => (-600.0, 600.0),
+            Benchmark::Rosenbrock => (-5.0, 10.0),
+        }
+    }
+
+    /// Location of the global minimum in `dimension` dimensions, its value is `optimum_value`.
+    pub fn optimum(self, dimension:usize) -> Vec<f64>
+    {
+        let coordinate = match self {
+            Benchmark::Schwefel => 420.968_746_359_982,
+            Benchmark::Rosenbrock => 1.0,
+            _ => 0.0,
+        };
+        vec![coordinate; dimension]
+    }
+
+    pub fn optimum_value(self) -> f64
+    {
+        0.0
+    }
+
+    /// A run minimizing the benchmark in `dimension` dimensions over its usual domain.
+    pub fn build_run(self, dimension:usize, n:usize) -> RealRun
+    {
+        let mut run = RealRun::new(vec![self.bounds(); dimension], n, move |x:&[f64]| self.evaluate(x));
+        run.set_objective(Objective::Minimize);
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optima_are_minimal()
+    {
+        for benchmark in Benchmark::ALL {
+            for dimension in [1, 2, 10] {
+                let optimum = benchmark.optimum(dimension);
+                let value = benchmark.evaluate(&optimum);
+                assert!((value - benchmark.optimum_value()).abs() < 1e-6, "{} at optimum: {}", benchmark.name(), value);
+                let shifted:Vec<f64> = optimum.iter().map(|v| v + 0.3).collect();
+                if dimension > 1 || benchmark != Benchmark::Rosenbrock {
+                    assert!(benchmark.evaluate(&shifted) > value, "{} is not minimal", benchmark.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn run_approaches_optimum()
+    {
+        let mut test_run = Benchmark::Rastrigin.build_run(2, 60);
+        test_run.run(150);
+        assert!(test_run.best().unwrap().fitness < 2.0, "{:?}", test_run.best());
+        assert!(test_run.bounds().iter().all(|&b| b == (-5.12, 5.12)));
+    }
+}
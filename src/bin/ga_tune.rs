@@ -0,0 +1,92 @@
+//! Target runner for automatic configurators such as irace and ParamILS.
+//!
+//! `ga_tune <config_id> <instance_id> <seed> <instance> [--Pcross P] [--Pmut P] [--n N] [--z Z]
+//! [--bits L] [--iterations I] [--tournament_size K] [--elitism K] [--mating_pool F]
+//! [--gray true|false] [--paramils]`
+//!
+//! The instance is a problem name from the registry and the seed seeds the run, so the same
+//! configuration, instance and seed always give the same cost. The last line of stdout is the
+//! cost to minimize, the negated best fitness for maximized problems. With `--paramils` the
+//! line is `Result for ParamILS: SAT, <seconds>, -1, <cost>, <seed>` instead.
+//! `ga_tune --parameters` prints the tunable parameters in irace's `parameters.txt` format.
+//!
+//! Exit codes: 0 success, 1 invalid configuration, 2 malformed command line.
+use Genetic_Alg::*;
+use std::env;
+use std::process;
+use std::time::Instant;
+
+const PARAMETERS:&str = "\
+Pcross          \"--Pcross \"          r (0.0, 1.0)
+Pmut            \"--Pmut \"            r (0.0, 0.2)
+n               \"--n \"               c (10, 20, 30, 50, 100)
+z               \"--z \"               i (1, 8)
+tournament_size \"--tournament_size \" i (1, 8)
+elitism         \"--elitism \"         i (0, 4)
+mating_pool     \"--mating_pool \"     r (0.5, 2.0)
+gray            \"--gray \"            c (false, true)
+";
+
+fn exit_with(code:i32, message:&str) -> !
+{
+    eprintln!("{}", message);
+    process::exit(code);
+}
+
+fn parse<T:std::str::FromStr>(flag:&str, value:Option<String>) -> T
+{
+    let value = value.unwrap_or_else(|| exit_with(2, &format!("{} expects a value", flag)));
+    value.parse().unwrap_or_else(|_| exit_with(2, &format!("{} got an invalid value '{}'", flag, value)))
+}
+
+fn main()
+{
+    let mut args = env::args().skip(1);
+    let Some(config_id) = args.next() else { exit_with(2, "usage: ga_tune <config_id> <instance_id> <seed> <instance> [params]") };
+    if config_id == "--parameters" {
+        print!("{}", PARAMETERS);
+        return;
+    }
+    let (Some(_instance_id), Some(seed), Some(instance)) = (args.next(), args.next(), args.next()) else {
+        exit_with(2, "usage: ga_tune <config_id> <instance_id> <seed> <instance> [params]")
+    };
+    let seed:u64 = parse("seed", Some(seed));
+
+    let registry = ProblemRegistry::with_builtins();
+    let problem = registry.get(&instance).unwrap_or_else(|| exit_with(1, &format!("unknown problem '{}'", instance)));
+    let mut config = RunConfig {problem:instance.clone(), L:problem.default_bits, seed:Some(seed), ..RunConfig::default()};
+    let mut paramils = false;
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--Pcross" => config.Pcross = parse(&flag, args.next()),
+            "--Pmut" => config.Pmut = parse(&flag, args.next()),
+            "--n" => config.n = parse(&flag, args.next()),
+            "--z" => config.z = parse(&flag, args.next()),
+            "--bits" => config.L = parse(&flag, args.next()),
+            "--iterations" => config.iterations = parse(&flag, args.next()),
+            "--tournament_size" => config.tournament_size = Some(parse(&flag, args.next())),
+            "--elitism" => config.elitism = parse(&flag, args.next()),
+            "--mating_pool" => config.mating_pool = parse(&flag, args.next()),
+            "--gray" => config.gray = parse(&flag, args.next()),
+            "--paramils" => paramils = true,
+            _ => exit_with(2, &format!("unknown parameter {}", flag)),
+        }
+    }
+
+    let mut run = config.build_run(&registry).unwrap_or_else(|errors| {
+        let messages:Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        exit_with(1, &format!("invalid configuration {}: {}", config_id, messages.join("; ")))
+    });
+    let started = Instant::now();
+    run.run(config.iterations).unwrap_or_else(|e| exit_with(1, &format!("run {} failed: {}", config_id, e)));
+    let best = run.best().map_or(problem.objective.worst_value(), |best| best.fitness);
+    let cost = match problem.objective {
+        Objective::Maximize => -best,
+        Objective::Minimize => best,
+    };
+    if paramils {
+        println!("Result for ParamILS: SAT, {}, -1, {}, {}", started.elapsed().as_secs_f64(), cost, seed);
+    }else{
+        println!("{}", cost);
+    }
+}
@@ -1,11 +1,343 @@
-use Genetic_Alg::*;  // Replace with your actual crate name
-
-fn main() {
-    let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2);
-    let result = test_run.run(1000);
-    
-    match save_iter_to_csv(&result.1, "run_3.csv") {
-       Ok(_) => println!("Successfully wrote to CSV in current directory"),
-       Err(e) => println!("Error: {}", e)
+//! Command-line front-end.
+//!
+//! `run_ga run [--problem NAME] [--pcross P] [--pmut P] [--genome-len L] [--pop-size N]
+//! [--iterations I] [--seed S] [--output FILE] [--checkpoint FILE] [--check]` runs once,
+//! `run_ga sweep --pcross 0.6,0.8 --pmut 0.01,0.05 ... [--threads T]` runs every combination
+//! of the listed values and `run_ga resume CHECKPOINT --iterations I` continues a checkpointed
+//! run. Flags given on the command line override the `--config` file. `run_ga help <command>`
+//! lists all flags.
+use clap::{Args, Parser, Subcommand};
+use Genetic_Alg::*;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process;
+
+#[derive(Parser)]
+#[command(name = "run_ga", about = "Runs the genetic algorithm on a registered problem")]
+struct Cli
+{
+    #[command(subcommand)]
+    command:Command,
+}
+
+#[derive(Subcommand)]
+enum Command
+{
+    /// Runs once and writes the stats to CSV
+    Run
+    {
+        #[command(flatten)]
+        setup:Setup,
+        #[command(flatten)]
+        parameters:Parameters,
+        /// Writes a checkpoint of the finished run, for `resume`
+        #[arg(long)]
+        checkpoint:Option<PathBuf>,
+        /// Prints the configuration and its cost instead of running
+        #[arg(long)]
+        check:bool,
+    },
+    /// Runs every combination of the listed parameter values, writing a row per run and per combination
+    Sweep
+    {
+        #[command(flatten)]
+        setup:Setup,
+        #[command(flatten)]
+        grid:Grid,
+    },
+    /// Continues a run from a checkpoint written by `run --checkpoint`, updating the checkpoint
+    Resume
+    {
+        checkpoint:PathBuf,
+        #[command(flatten)]
+        setup:Setup,
+        /// Generations to add
+        #[arg(long)]
+        iterations:u32,
+        /// Stats CSV of the continued run
+        #[arg(long)]
+        output:Option<String>,
+    },
+}
+
+/// What is being optimized.
+#[derive(Args)]
+struct Setup
+{
+    /// JSON run configuration, see `RunConfig`
+    #[arg(long, value_name = "FILE")]
+    config:Option<PathBuf>,
+    /// Registered problem, its genome length and suggested parameters become the defaults
+    #[arg(long, value_name = "NAME")]
+    problem:Option<String>,
+    /// Fitness (and any mutation, crossover or selection it exports) from a shared library
+    #[arg(long, value_name = "LIB")]
+    plugin:Option<PathBuf>,
+    /// Fitness from a Rhai script
+    #[arg(long, value_name = "FILE")]
+    script:Option<PathBuf>,
+    /// MAX-SAT on a DIMACS CNF formula, one bit per variable
+    #[arg(long, value_name = "FILE")]
+    cnf:Option<String>,
+}
+
+#[derive(Args)]
+struct Parameters
+{
+    /// Crossover probability
+    #[arg(long)]
+    pcross:Option<f32>,
+    /// Mutation probability
+    #[arg(long)]
+    pmut:Option<f32>,
+    /// Bits per genome
+    #[arg(long, alias = "bits")]
+    genome_len:Option<u8>,
+    /// Population size
+    #[arg(long)]
+    pop_size:Option<usize>,
+    /// Crossover point
+    #[arg(long)]
+    crossover_point:Option<u8>,
+    #[arg(long)]
+    iterations:Option<u32>,
+    /// Seed for a reproducible run
+    #[arg(long)]
+    seed:Option<u64>,
+    /// Stats CSV
+    #[arg(long)]
+    output:Option<String>,
+}
+
+#[derive(Args)]
+struct Grid
+{
+    /// Crossover probabilities, comma separated
+    #[arg(long, value_delimiter = ',')]
+    pcross:Vec<f32>,
+    /// Mutation probabilities, comma separated
+    #[arg(long, value_delimiter = ',')]
+    pmut:Vec<f32>,
+    /// Bits per genome
+    #[arg(long, alias = "bits")]
+    genome_len:Option<u8>,
+    /// Population sizes, comma separated
+    #[arg(long, value_delimiter = ',')]
+    pop_size:Vec<usize>,
+    #[arg(long)]
+    iterations:Option<u32>,
+    /// Master seed the seed of every run is derived from
+    #[arg(long)]
+    seed:Option<u64>,
+    /// Runs per combination
+    #[arg(long, default_value_t = 1)]
+    replicates:u64,
+    /// Runs at the same time
+    #[arg(long, default_value_t = 1)]
+    threads:usize,
+    /// CSV with one row per run
+    #[arg(long, default_value = "sweep.csv")]
+    output:String,
+    /// CSV with the mean and standard deviation of the best fitness of each combination
+    #[arg(long, default_value = "sweep_summary.csv")]
+    summary:String,
+}
+
+fn fail(message:&str) -> !
+{
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+// the plugin replaces the problem's fitness and, for each one it exports, the mutation,
+// crossover and selection
+#[cfg(feature = "plugins")]
+fn load_plugin(run:&mut Run, path:&Path, mutation_rate:f32)
+{
+    let plugin = unsafe { Plugin::load(path) }
+        .unwrap_or_else(|e| fail(&format!("could not load plugin {}: {}", path.display(), e)));
+    if let Some(mutation) = plugin.mutation(mutation_rate) {
+        run.set_mutation(Box::new(mutation));
+    }
+    if let Some(crossover) = plugin.crossover() {
+        run.set_crossover(Box::new(crossover));
+    }
+    if let Some(selection) = plugin.selection() {
+        run.set_selection(Box::new(selection));
+    }
+    run.set_fitness_function(Box::new(plugin));
+}
+
+#[cfg(not(feature = "plugins"))]
+fn load_plugin(_run:&mut Run, _path:&Path, _mutation_rate:f32)
+{
+    fail("run_ga was built without the plugins feature");
+}
+
+#[cfg(feature = "scripting")]
+fn load_script(run:&mut Run, path:&Path)
+{
+    let script = ScriptFitness::from_file(path)
+        .unwrap_or_else(|e| fail(&format!("could not load script {}: {}", path.display(), e)));
+    run.set_fitness_function(Box::new(script));
+}
+
+#[cfg(not(feature = "scripting"))]
+fn load_script(_run:&mut Run, _path:&Path)
+{
+    fail("run_ga was built without the scripting feature");
+}
+
+impl Setup
+{
+    // the configuration file, a picked problem with the suggested parameters for its genome
+    // length, and a CNF formula's variable count as the genome length unless `genome_len` is set
+    fn config(&self, registry:&ProblemRegistry, genome_len:Option<u8>) -> (RunConfig, Option<MaxSat>)
+    {
+        let mut config = match &self.config {
+            Some(path) => RunConfig::from_json_file(path)
+                .unwrap_or_else(|e| fail(&format!("could not read {}: {}", path.display(), e))),
+            None => RunConfig::default(),
+        };
+        if let Some(name) = &self.problem {
+            let length = genome_len.or(registry.get(name).map(|p| p.default_bits)).unwrap_or(config.L);
+            let p = suggest_parameters(length.max(1));
+            config = RunConfig {output:format!("run_{}.csv", name), problem:name.clone(), Pcross:p.Pcross, Pmut:p.Pmut,
+                L:length, n:p.n, z:p.z, ..config};
+        }
+        let formula = self.cnf.as_ref().map(|path| MaxSat::from_dimacs(path)
+            .unwrap_or_else(|e| fail(&format!("could not read {}: {}", path, e))));
+        if let (Some(formula), None) = (&formula, genome_len) {
+            if formula.variables > 64 {
+                fail(&format!("{} variables do not fit a 64-bit genome", formula.variables));
+            }
+            config.L = formula.variables as u8;
+        }
+        (config, formula)
+    }
+
+    fn build(&self, config:&RunConfig, registry:&ProblemRegistry, formula:Option<&MaxSat>) -> Run
+    {
+        if let Err(errors) = config.validate_with(registry) {
+            let messages:Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            fail(&format!("invalid configuration:\n  {}", messages.join("\n  ")));
+        }
+        let mut run = config.build_run(registry).unwrap_or_else(|_| fail("invalid configuration"));
+        if let Some(path) = &self.plugin {
+            load_plugin(&mut run, path, config.Pmut);
+        }
+        if let Some(path) = &self.script {
+            load_script(&mut run, path);
+        }
+        if let Some(formula) = formula {
+            run.set_fitness_function(Box::new(formula.clone()));
+        }
+        run
+    }
+}
+
+fn report(run:&Run, config:&RunConfig, formula:Option<&MaxSat>, output:&str)
+{
+    if let Some(best) = run.best() {
+        println!("best: data {} fitness {}", best.data, best.fitness);
+    }
+    if config.problem == "cournot" && formula.is_none() {
+        let (nash, competitive) = run.equilibria();
+        let industry = run.history().last().map_or(0, |s| s.ind_out);
+        println!("industry output {} (nash {:.1}, competitive {:.1})", industry, nash.output, competitive.output);
+    }
+    match save_stats_to_csv(run.history(), output) {
+        Ok(_) => println!("wrote {}", output),
+        Err(e) => fail(&format!("could not write {}: {}", output, e)),
+    }
+}
+
+fn run(setup:Setup, parameters:Parameters, checkpoint:Option<PathBuf>, check:bool, registry:&ProblemRegistry)
+{
+    let (mut config, formula) = setup.config(registry, parameters.genome_len);
+    config.Pcross = parameters.pcross.unwrap_or(config.Pcross);
+    config.Pmut = parameters.pmut.unwrap_or(config.Pmut);
+    config.L = parameters.genome_len.unwrap_or(config.L);
+    config.n = parameters.pop_size.unwrap_or(config.n);
+    config.z = parameters.crossover_point.unwrap_or(config.z);
+    config.iterations = parameters.iterations.unwrap_or(config.iterations);
+    config.seed = parameters.seed.or(config.seed);
+    config.output = parameters.output.unwrap_or(config.output);
+
+    if check {
+        if let Err(errors) = config.validate_with(registry) {
+            let messages:Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            fail(&format!("invalid configuration:\n  {}", messages.join("\n  ")));
+        }
+        let estimate = config.estimate();
+        println!("{}", config);
+        println!("evaluations {}", estimate.evaluations);
+        println!("memory      ~{} bytes population, ~{} bytes history", estimate.population_bytes, estimate.history_bytes);
+        return;
+    }
+
+    let mut run = setup.build(&config, registry, formula.as_ref());
+    if let Some(formula) = &formula {
+        println!("{} clauses over {} variables", formula.clauses.len(), formula.variables);
+    }
+    run.run(config.iterations).unwrap_or_else(|e| fail(&format!("run failed: {}", e)));
+    report(&run, &config, formula.as_ref(), &config.output);
+    if let Some(path) = checkpoint {
+        run.checkpoint(&path).unwrap_or_else(|e| fail(&format!("could not write {}: {}", path.display(), e)));
+    }
+}
+
+fn sweep(setup:Setup, grid:Grid, registry:&ProblemRegistry)
+{
+    let (mut config, formula) = setup.config(registry, grid.genome_len);
+    config.L = grid.genome_len.unwrap_or(config.L);
+    config.iterations = grid.iterations.unwrap_or(config.iterations);
+    // a parameter left out of the grid keeps its configured value
+    let sweep = Sweep {base:config, Pcross:grid.pcross, Pmut:grid.pmut, n:grid.pop_size, L:Vec::new(),
+        replicates:grid.replicates, threads:grid.threads};
+    let mut seeds = SeedSequence::new(grid.seed.unwrap_or_else(rand::random));
+    let runs = sweep.run(&mut seeds, |config| setup.build(config, registry, formula.as_ref()))
+        .unwrap_or_else(|e| fail(&format!("sweep failed: {}", e)));
+
+    let file = File::create(&grid.output).unwrap_or_else(|e| fail(&format!("could not write {}: {}", grid.output, e)));
+    let mut writer = csv::Writer::from_writer(file);
+    let mut write = |record:&[String]| writer.write_record(record).unwrap_or_else(|e| fail(&format!("could not write {}: {}", grid.output, e)));
+    write(&["Pcross", "Pmut", "n", "replicate", "seed", "best_fitness", "evaluations", "stop_reason"].map(String::from));
+    for run in &runs {
+        let config = &run.config;
+        write(&[config.Pcross.to_string(), config.Pmut.to_string(), config.n.to_string(), run.replicate.to_string(),
+            config.seed.map_or(String::new(), |seed| seed.to_string()), run.best_fitness.map_or(String::new(), |f| f.to_string()),
+            run.evaluations.to_string(), format!("{:?}", run.stop_reason)]);
+    }
+    writer.flush().unwrap_or_else(|e| fail(&format!("could not write {}: {}", grid.output, e)));
+
+    let summaries = summarize(&runs);
+    for summary in &summaries {
+        let config = &summary.config;
+        println!("Pcross {} Pmut {} n {}: best {:.4} ± {:.4} over {} runs", config.Pcross, config.Pmut, config.n,
+            summary.mean_best_fitness, summary.std_best_fitness, summary.runs);
+    }
+    save_sweep_summary_to_csv(&summaries, &grid.summary).unwrap_or_else(|e| fail(&format!("could not write {}: {}", grid.summary, e)));
+    println!("wrote {} runs to {} and {} (master seed {})", runs.len(), grid.output, grid.summary, seeds.master());
+}
+
+fn resume(checkpoint:PathBuf, setup:Setup, iterations:u32, output:Option<String>, registry:&ProblemRegistry)
+{
+    let (config, formula) = setup.config(registry, None);
+    let mut run = setup.build(&config, registry, formula.as_ref());
+    run.resume(&checkpoint).unwrap_or_else(|e| fail(&format!("could not resume from {}: {}", checkpoint.display(), e)));
+    println!("resuming at generation {}", run.generation());
+    run.run(iterations).unwrap_or_else(|e| fail(&format!("run failed: {}", e)));
+    report(&run, &config, formula.as_ref(), &output.unwrap_or(config.output.clone()));
+    run.checkpoint(&checkpoint).unwrap_or_else(|e| fail(&format!("could not write {}: {}", checkpoint.display(), e)));
+}
+
+fn main()
+{
+    let registry = ProblemRegistry::with_builtins();
+    match Cli::parse().command {
+        Command::Run{setup, parameters, checkpoint, check} => run(setup, parameters, checkpoint, check, &registry),
+        Command::Sweep{setup, grid} => sweep(setup, grid, &registry),
+        Command::Resume{checkpoint, setup, iterations, output} => resume(checkpoint, setup, iterations, output, &registry),
     }
 }
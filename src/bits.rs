@@ -0,0 +1,242 @@
+//! Bit-string genomes of any length, stored as little-endian `u64` words: bit `i` is bit
+//! `i % 64` of word `i / 64`. `BitRun` evolves them with the generic `GenomeRun` loop, using the
+//! same crossover and mutation schemes as the single-word `Run`.
+use crate::evolution::{Genome, GenomeRun};
+use crate::ops::{self, MutationScheme};
+use crate::sampling;
+use rand::{Rng, RngCore};
+
+/// `GenomeRun` over bit strings, the operators need no context.
+pub type BitRun = GenomeRun<BitGenome>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitGenome
+{
+    words:Vec<u64>,
+    len:usize,
+    pub fitness:f64,
+}
+
+impl BitGenome
+{
+    /// All zeros.
+    pub fn new(len:usize) -> Self
+    {
+        BitGenome {words:vec![0; len.div_ceil(64)], len, fitness:0.0}
+    }
+
+    pub fn random<R:Rng + ?Sized>(len:usize, rng:&mut R) -> Self
+    {
+        let mut genome = Self::new(len);
+        genome.words.iter_mut().for_each(|word| *word = rng.gen());
+        genome.clear_padding();
+        genome
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    pub fn words(&self) -> &[u64]
+    {
+        &self.words
+    }
+
+    pub fn get(&self, bit:usize) -> bool
+    {
+        assert!(bit < self.len, "bit {} out of range for a {}-bit genome", bit, self.len);
+        self.words[bit / 64] >> (bit % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, bit:usize, value:bool)
+    {
+        assert!(bit < self.len, "bit {} out of range for a {}-bit genome", bit, self.len);
+        let mask = 1 << (bit % 64);
+        if value { self.words[bit / 64] |= mask } else { self.words[bit / 64] &= !mask }
+    }
+
+    pub fn flip(&mut self, bit:usize)
+    {
+        assert!(bit < self.len, "bit {} out of range for a {}-bit genome", bit, self.len);
+        self.words[bit / 64] ^= 1 << (bit % 64);
+    }
+
+    pub fn count_ones(&self) -> usize
+    {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    // bits past `len` in the last word stay zero
+    fn clear_padding(&mut self)
+    {
+        let used = self.len - 64 * self.words.len().saturating_sub(1);
+        if let Some(last) = self.words.last_mut() {
+            *last &= ops::low_mask(used as u8);
+        }
+    }
+}
+
+// mask of bits `low..high` within word `w`
+fn word_mask(w:usize, low:usize, high:usize) -> u64
+{
+    let start = low.clamp(64 * w, 64 * w + 64) - 64 * w;
+    let end = high.clamp(64 * w, 64 * w + 64) - 64 * w;
+    ops::low_mask(end as u8) & !ops::low_mask(start as u8)
+}
+
+/// Exchanges bits `low..high` of `a` and `b`, which must have the same length.
+pub fn swap_range(a:&mut BitGenome, b:&mut BitGenome, low:usize, high:usize)
+{
+    assert_eq!(a.len, b.len, "genomes must have the same length");
+    for w in low / 64..high.div_ceil(64).min(a.words.len()) {
+        (a.words[w], b.words[w]) = ops::swap_bits(a.words[w], b.words[w], word_mask(w, low, high));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitCrossover
+{
+    /// Swaps the low `z` bits, the single-word `Run`'s default crossover.
+    TailSwap{z:usize},
+    /// Swaps the bits below a uniform cut in `1..len`.
+    #[default]
+    SinglePoint,
+    /// Swaps the bits between two distinct cuts.
+    TwoPoint,
+    /// Swaps every bit with probability one half.
+    Uniform,
+}
+
+impl BitCrossover
+{
+    /// Both children of `a` and `b`.
+    pub fn apply<R:Rng + ?Sized>(self, a:&BitGenome, b:&BitGenome, rng:&mut R) -> (BitGenome, BitGenome)
+    {
+        let (mut c, mut d) = (a.clone(), b.clone());
+        let len = a.len;
+        match self {
+            BitCrossover::TailSwap{z} => swap_range(&mut c, &mut d, 0, z.min(len)),
+            BitCrossover::SinglePoint if len >= 2 => swap_range(&mut c, &mut d, 0, rng.gen_range(1..len)),
+            BitCrossover::TwoPoint if len >= 1 => {
+                let cuts = rand::seq::index::sample(rng, len + 1, 2);
+                swap_range(&mut c, &mut d, cuts.index(0).min(cuts.index(1)), cuts.index(0).max(cuts.index(1)));
+            }
+            BitCrossover::Uniform => {
+                for w in 0..c.words.len() {
+                    (c.words[w], d.words[w]) = ops::swap_bits(c.words[w], d.words[w], rng.gen());
+                }
+                c.clear_padding();
+                d.clear_padding();
+            }
+            _ => {}
+        }
+        (c, d)
+    }
+}
+
+impl Genome for BitGenome
+{
+    type Context = ();
+    type Crossover = BitCrossover;
+    type Mutation = MutationScheme;
+
+    fn fitness(&self) -> f64
+    {
+        self.fitness
+    }
+
+    fn set_fitness(&mut self, fitness:f64)
+    {
+        self.fitness = fitness;
+    }
+
+    fn crossover(a:&Self, b:&Self, operator:BitCrossover, _context:&(), rng:&mut dyn RngCore) -> Self
+    {
+        let (mut child, _) = operator.apply(a, b, rng);
+        child.fitness = 0.0;
+        child
+    }
+
+    /// `rate` is read as `Pmut` is by `MutationScheme`.
+    fn mutate(&mut self, scheme:&MutationScheme, rate:f64, _context:&(), rng:&mut dyn RngCore)
+    {
+        if self.len == 0 {
+            return;
+        }
+        match scheme {
+            MutationScheme::PerIndividual => if rng.gen::<f64>() < rate {
+                self.flip(rng.gen_range(0..self.len));
+            }
+            MutationScheme::PerLocus => for bit in sampling::bernoulli_indices(rng, rate, self.len) {
+                self.flip(bit);
+            }
+        }
+    }
+}
+
+impl BitRun
+{
+    /// `n` random genomes of `len` bits, maximizing `fitness` by default, with single-point
+    /// crossover and per-locus mutation at one bit per genome.
+    pub fn new<F>(len:usize, n:usize, fitness:F) -> Self
+        where F:Fn(&BitGenome) -> f64 + Send + Sync + 'static
+    {
+        let mut rng = rand::thread_rng();
+        let population = (0..n).map(|_| BitGenome::random(len, &mut rng)).collect();
+        let mut run = GenomeRun::from_population(population, (), fitness);
+        run.mutation = MutationScheme::PerLocus;
+        run.Pmut = 1.0 / len.max(1) as f32;
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_span_words()
+    {
+        let mut genome = BitGenome::new(130);
+        assert_eq!(genome.words().len(), 3);
+        genome.set(129, true);
+        genome.flip(64);
+        assert!(genome.get(129) && genome.get(64) && !genome.get(63));
+        assert_eq!(genome.count_ones(), 2);
+        let mut rng = rand::thread_rng();
+        assert!(BitGenome::random(70, &mut rng).words()[1] < 1 << 6);
+    }
+
+    #[test]
+    fn crossovers_swap_across_words()
+    {
+        let mut rng = rand::thread_rng();
+        let zeros = BitGenome::new(200);
+        let mut ones = BitGenome::new(200);
+        (0..200).for_each(|bit| ones.set(bit, true));
+
+        let (c, d) = BitCrossover::TailSwap{z:100}.apply(&zeros, &ones, &mut rng);
+        assert_eq!(c.count_ones(), 100);
+        assert!(c.get(99) && !c.get(100) && !d.get(0) && d.get(199));
+        for crossover in [BitCrossover::SinglePoint, BitCrossover::TwoPoint, BitCrossover::Uniform] {
+            let (c, d) = crossover.apply(&zeros, &ones, &mut rng);
+            assert_eq!(c.count_ones() + d.count_ones(), 200, "{:?}", crossover);
+            assert!((0..200).all(|bit| c.get(bit) != d.get(bit)));
+        }
+    }
+
+    #[test]
+    fn run_solves_long_onemax()
+    {
+        let mut test_run = BitRun::new(100, 40, |genome:&BitGenome| genome.count_ones() as f64);
+        test_run.crossover = BitCrossover::Uniform;
+        test_run.run(150);
+        assert!(test_run.best().unwrap().fitness >= 90.0, "{:?}", test_run.best().map(|b| b.fitness));
+    }
+}
@@ -0,0 +1,92 @@
+//! Seeding a run with the best points of its whole search space. For short genomes every
+//! genome can be evaluated, which gives the true optimum to check a run's convergence against;
+//! longer genomes fall back to a large random sample.
+use crate::{Chromosome, Run};
+use rand::Rng;
+
+/// Longest genome that is scanned exhaustively, and the sample size beyond it.
+pub const MAX_EXHAUSTIVE_LEN:u8 = 20;
+
+impl Run
+{
+    /// The `count` best genomes, best first. Every `L`-bit genome is evaluated when `L` is at
+    /// most `MAX_EXHAUSTIVE_LEN`, otherwise `2^MAX_EXHAUSTIVE_LEN` random ones. Fitness is
+    /// taken in the context of the current population, which matters for population-dependent
+    /// fitness like the Cournot profit. Scanned genomes go through the prefilter and evaluation
+    /// budget and count in `evaluations` like the population's.
+    pub fn top_genomes(&mut self, count:usize) -> Vec<Chromosome>
+    {
+        self.calculate_data_sum();
+        let genomes:Vec<u64> = if self.L <= MAX_EXHAUSTIVE_LEN {
+            (0..1u64 << self.L).collect()
+        } else {
+            let mask = if self.L >= 64 { u64::MAX } else { (1u64 << self.L) - 1 };
+            (0..1u64 << MAX_EXHAUSTIVE_LEN).map(|_| self.rng.gen::<u64>() & mask).collect()
+        };
+        let mut scanned:Vec<Chromosome> = genomes.into_iter().map(|data| {
+            let mut ind = Chromosome {data, fitness:0.0, N:self.n, violation:0.0};
+            self.evaluate(&mut ind, self.data_sum, 0.0);
+            ind
+        }).collect();
+        let objective = self.objective;
+        let count = count.min(scanned.len());
+        if count < scanned.len() {
+            scanned.select_nth_unstable_by(count, |a, b| objective.best_first(a.fitness, b.fitness));
+            scanned.truncate(count);
+        }
+        scanned.sort_by(|a, b| objective.best_first(a.fitness, b.fitness));
+        scanned
+    }
+
+    /// Replaces the population with the `n` best genomes of `top_genomes`. When there are fewer
+    /// than `n` genomes of length `L`, all of them are taken and repeated, best first, until the
+    /// population is full.
+    pub fn bootstrap_population(&mut self)
+    {
+        let top = self.top_genomes(self.n);
+        self.population = top.iter().cycle().take(self.n).cloned().collect();
+        self.stale = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objective::Objective;
+
+    // single peak at 0b1011_0110_1100 with a plateau elsewhere
+    fn needle(ind:&Chromosome, _data_sum:u64) -> f64
+    {
+        if ind.data == 0b1011_0110_1100 { 10.0 } else { (ind.data % 7) as f64 / 7.0 }
+    }
+
+    #[test]
+    fn exhaustive_scan_finds_the_optimum()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 12, 10, 4, 1).unwrap();
+        test_run.set_fitness(needle);
+        let top = test_run.top_genomes(3);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].data, 0b1011_0110_1100);
+        assert!(top[1].fitness >= top[2].fitness);
+
+        test_run.set_objective(Objective::Minimize);
+        test_run.bootstrap_population();
+        assert_eq!(test_run.population.len(), 10);
+        assert!(test_run.population.data.iter().all(|d| d % 7 == 0));
+        test_run.step();
+        assert_eq!(test_run.best().unwrap().fitness, 0.0);
+    }
+
+    #[test]
+    fn short_genomes_fill_the_population()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 3, 10, 1, 1).unwrap();
+        test_run.set_fitness(|ind, _| ind.data as f64);
+        test_run.bootstrap_population();
+        assert_eq!(test_run.population.len(), 10);
+        assert_eq!(test_run.population.data, vec![7, 6, 5, 4, 3, 2, 1, 0, 7, 6]);
+        test_run.run(3).unwrap();
+        assert_eq!(test_run.population.len(), 10);
+    }
+}
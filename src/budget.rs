@@ -0,0 +1,116 @@
+//! A fixed number of fitness evaluations shared by several runs, so an experiment spends
+//! exactly its budget whether the islands or replicates drawing on it run in parallel or not.
+//! Every evaluation takes one unit atomically; once none are left the individuals still
+//! waiting get the worst fitness and each run stops at the end of its generation.
+use crate::Run;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Clones share the same remaining count.
+#[derive(Debug, Clone)]
+pub struct EvaluationBudget
+{
+    total:usize,
+    remaining:Arc<AtomicUsize>,
+}
+
+impl EvaluationBudget
+{
+    pub fn new(total:usize) -> Self
+    {
+        EvaluationBudget {total, remaining:Arc::new(AtomicUsize::new(total))}
+    }
+
+    pub fn total(&self) -> usize
+    {
+        self.total
+    }
+
+    pub fn remaining(&self) -> usize
+    {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    pub fn used(&self) -> usize
+    {
+        self.total - self.remaining()
+    }
+
+    pub fn is_exhausted(&self) -> bool
+    {
+        self.remaining() == 0
+    }
+
+    /// Takes one evaluation, false if none are left.
+    pub fn try_take(&self) -> bool
+    {
+        self.remaining.fetch_update(Ordering::AcqRel, Ordering::Acquire, |left| left.checked_sub(1)).is_ok()
+    }
+}
+
+impl Run
+{
+    /// Draws every fitness evaluation from `budget`, stopping with
+    /// `StopReason::BudgetExhausted` once it is spent. Give each run a clone to share it.
+    pub fn set_evaluation_budget(&mut self, budget:EvaluationBudget)
+    {
+        self.budget = Some(budget);
+    }
+
+    pub fn evaluation_budget(&self) -> Option<&EvaluationBudget>
+    {
+        self.budget.as_ref()
+    }
+
+    pub(crate) fn budget_exhausted(&self) -> bool
+    {
+        self.budget.as_ref().is_some_and(EvaluationBudget::is_exhausted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::island::{IslandRun, Topology};
+    use crate::{problems, StopReason};
+
+    fn new_run(seed:u64) -> Run
+    {
+        let mut run = Run::with_seed(0.6, 0.05, 12, 30, 4, seed).unwrap();
+        run.set_fitness(problems::onemax_fitness);
+        run
+    }
+
+    #[test]
+    fn parallel_runs_spend_the_budget_exactly()
+    {
+        let budget = EvaluationBudget::new(1000);
+        let evaluations:usize = std::thread::scope(|scope| {
+            let handles:Vec<_> = (0..4).map(|seed| {
+                let budget = budget.clone();
+                scope.spawn(move || {
+                    let mut run = new_run(seed);
+                    run.set_evaluation_budget(budget);
+                    let result = run.run(1000).unwrap();
+                    assert_eq!(result.stop_reason, StopReason::BudgetExhausted);
+                    result.evaluations
+                })
+            }).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(evaluations, 1000);
+        assert_eq!((budget.used(), budget.remaining()), (1000, 0));
+        assert!(!budget.try_take());
+    }
+
+    #[test]
+    fn islands_stop_when_the_budget_is_spent()
+    {
+        let mut model = IslandRun::new((0..3).map(new_run).collect(), Topology::Ring, 4, 2);
+        model.parallel = true;
+        model.set_evaluation_budget(EvaluationBudget::new(500));
+        model.run(100);
+        assert!(model.generation() < 100);
+        assert!(model.islands().iter().all(|island| island.evaluation_budget().unwrap().is_exhausted()));
+    }
+}
@@ -0,0 +1,177 @@
+//! Named, validated construction of a `Run`. Unset parameters keep the `RunConfig` defaults,
+//! and `build` checks them all together like `RunConfig::validate`.
+use crate::config::{ConfigError, RunConfig};
+use crate::heuristics::{suggest_parameters, SuggestedParameters};
+use crate::objective::Objective;
+use crate::{FitnessFn, MutationScheme, Run};
+
+#[derive(Debug, Clone, Default)]
+pub struct RunBuilder
+{
+    config:RunConfig,
+    seed:Option<u64>,
+    fitness:Option<FitnessFn>,
+    objective:Objective,
+    mutation_scheme:MutationScheme,
+}
+
+impl RunBuilder
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Starts from `suggest_parameters(L)`, the rule-of-thumb `Pcross`, `Pmut`, `n` and `z` for
+    /// `L`-bit genomes, mutating per locus. Setters called afterwards override the suggestions.
+    pub fn auto(L:u8) -> Self
+    {
+        let SuggestedParameters {Pcross, Pmut, L, n, z} = suggest_parameters(L);
+        Self::from_config(RunConfig {Pcross, Pmut, L, n, z, ..RunConfig::default()}).mutation_scheme(MutationScheme::PerLocus)
+    }
+
+    /// Starts from the parameters of `config`, its problem and iterations are ignored.
+    pub fn from_config(config:RunConfig) -> Self
+    {
+        RunBuilder {config, ..Self::default()}
+    }
+
+    /// `Pcross`, the chance a pair of parents is crossed.
+    pub fn crossover_prob(mut self, Pcross:f32) -> Self
+    {
+        self.config.Pcross = Pcross;
+        self
+    }
+
+    /// `Pmut`, read according to the run's `MutationScheme`.
+    pub fn mutation_prob(mut self, Pmut:f32) -> Self
+    {
+        self.config.Pmut = Pmut;
+        self
+    }
+
+    /// How `Pmut` is read, see `Run::set_mutation_scheme`.
+    pub fn mutation_scheme(mut self, scheme:MutationScheme) -> Self
+    {
+        self.mutation_scheme = scheme;
+        self
+    }
+
+    /// `L`, the number of bits of each genome.
+    pub fn genome_len(mut self, L:u8) -> Self
+    {
+        self.config.L = L;
+        self
+    }
+
+    /// `n`, which must be even.
+    pub fn population(mut self, n:usize) -> Self
+    {
+        self.config.n = n;
+        self
+    }
+
+    /// `z`, the number of low bits the default crossover swaps.
+    pub fn crossover_point(mut self, z:u8) -> Self
+    {
+        self.config.z = z;
+        self
+    }
+
+    pub fn tournament(mut self, size:usize) -> Self
+    {
+        self.config.tournament_size = Some(size);
+        self
+    }
+
+    pub fn elitism(mut self, k:usize) -> Self
+    {
+        self.config.elitism = k;
+        self
+    }
+
+    pub fn mating_pool(mut self, factor:f64) -> Self
+    {
+        self.config.mating_pool = factor;
+        self
+    }
+
+    pub fn gray(mut self, gray:bool) -> Self
+    {
+        self.config.gray = gray;
+        self
+    }
+
+    /// Builds a reproducible run, see `Run::with_seed`.
+    pub fn seed(mut self, seed:u64) -> Self
+    {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn fitness(mut self, fitness:FitnessFn) -> Self
+    {
+        self.fitness = Some(fitness);
+        self
+    }
+
+    pub fn objective(mut self, objective:Objective) -> Self
+    {
+        self.objective = objective;
+        self
+    }
+
+    pub fn build(self) -> Result<Run, Vec<ConfigError>>
+    {
+        self.config.validate()?;
+        let RunConfig {Pcross, Pmut, L, n, z, ..} = self.config;
+        let mut run = Run::unchecked(Pcross, Pmut, L, n, z, self.seed);
+        if let Some(fitness) = self.fitness {
+            run.set_fitness(fitness);
+        }
+        run.set_objective(self.objective);
+        run.set_mutation_scheme(self.mutation_scheme);
+        self.config.configure(&mut run);
+        Ok(run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    #[test]
+    fn builds_with_defaults_and_seed()
+    {
+        let build = || RunBuilder::new().crossover_prob(0.6).mutation_prob(0.05).genome_len(8).population(20).crossover_point(4)
+            .fitness(problems::onemax_fitness).seed(11).build().unwrap();
+        let (mut a, mut b) = (build(), build());
+        a.run(10).unwrap();
+        b.run(10).unwrap();
+        assert_eq!(a.population_snapshot(), b.population_snapshot());
+        assert!(RunBuilder::new().build().is_ok());
+    }
+
+    #[test]
+    fn auto_starts_from_the_suggestions()
+    {
+        let suggested = suggest_parameters(40);
+        let run = RunBuilder::auto(40).seed(3).build().unwrap();
+        assert_eq!((run.Pcross, run.Pmut, run.L, run.n, run.z), (suggested.Pcross, suggested.Pmut, 40, suggested.n, suggested.z));
+        assert!((run.mutation_scheme.expected_flips(run.Pmut, run.L) - 1.0).abs() < 1e-6);
+        let run = RunBuilder::auto(40).population(12).build().unwrap();
+        assert_eq!((run.n, run.Pmut), (12, suggested.Pmut));
+    }
+
+    #[test]
+    fn rejects_inconsistent_parameters()
+    {
+        let errors = RunBuilder::new().genome_len(4).crossover_point(6).population(9).mutation_prob(-0.1).build().unwrap_err();
+        assert_eq!(errors, vec![
+            ConfigError::ProbabilityOutOfRange{name:"Pmut", value:-0.1},
+            ConfigError::CrossoverPoint{z:6, L:4},
+            ConfigError::PopulationSize{n:9},
+        ]);
+    }
+}
@@ -0,0 +1,450 @@
+//! Saving the state of a run so it survives a crash or can be continued later. Functions,
+//! trait objects and callbacks can't be serialized, so a checkpoint holds the evolving state
+//! (population, best, hall of fame, archive, history, counters, adaptive penalty, parent log,
+//! market and the RNG) and is restored into a run set up with the same fitness and operators.
+//! From there it continues exactly as the original would have.
+//!
+//! Checkpointing huge populations often rewrites mostly unchanged data, so `CheckpointLog`
+//! writes a full keyframe now and then and in between only what changed since the previous
+//! checkpoint.
+//!
+//! `GenomeCheckpoint` does the same for a `GenomeRun` whose genome type is serializable.
+use crate::archive::ArchiveEntry;
+use crate::constraints::AdaptivePenalty;
+use crate::cournot::Market;
+use crate::error::GaError;
+use crate::events::LifecycleEvent;
+use crate::evolution::{Genome, GenomeRun, GenomeStats};
+use crate::hall_of_fame::HallOfFame;
+use crate::parents::ParentSelection;
+use crate::stats::GenerationStats;
+use crate::{Chromosome, Run};
+use rand_chacha::ChaCha12Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint
+{
+    pub Pcross:f32,
+    pub Pmut:f32,
+    pub L:u8,
+    pub n:usize,
+    pub z:u8,
+    pub generation:u32,
+    pub population:Vec<Chromosome>,
+    pub best:Option<Chromosome>,
+    pub hall_of_fame:Option<HallOfFame>,
+    /// Contents of the run's `EpsilonArchive`, if it has one.
+    #[serde(default)]
+    pub archive:Option<Vec<ArchiveEntry>>,
+    /// Stats kept in memory, see `Run::history`.
+    pub history:Vec<GenerationStats>,
+    pub last_step:Option<GenomeStats>,
+    pub evaluations:usize,
+    pub stagnant_generations:u32,
+    pub rejected:usize,
+    pub penalty:f64,
+    /// Holds the streaks counted towards the next penalty change.
+    #[serde(default)]
+    pub adaptive_penalty:Option<AdaptivePenalty>,
+    #[serde(default)]
+    pub parent_log:Option<Vec<ParentSelection>>,
+    pub market:Market,
+    pub firm_costs:Option<Vec<f64>>,
+    /// Slots a steady-state run still has to evaluate, `None` for the whole population.
+    #[serde(default)]
+    pub stale:Option<Vec<usize>>,
+    rng:ChaCha12Rng,
+}
+
+/// The evolving state of a `GenomeRun`, restored into a run set up with the same fitness,
+/// context, operators and options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenomeCheckpoint<G>
+{
+    pub generation:u32,
+    pub population:Vec<G>,
+    pub best:Option<G>,
+    pub history:Vec<GenomeStats>,
+    pub evaluations:usize,
+    rng:ChaCha12Rng,
+}
+
+/// A checkpoint stored as its differences from the checkpoint before it. Everything but the
+/// population and history is small and kept whole in `state`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointDelta
+{
+    /// Generation of the checkpoint this one applies to.
+    pub base_generation:u32,
+    pub population_len:usize,
+    /// Slots whose individual differs from the base, with their new individual.
+    pub changed:Vec<(usize, Chromosome)>,
+    /// Stats past the first `history_from` of the base.
+    pub history_from:usize,
+    pub history_tail:Vec<GenerationStats>,
+    /// The checkpoint without its population and history.
+    pub state:Checkpoint,
+}
+
+impl Checkpoint
+{
+    /// What changed from `base` to this checkpoint.
+    pub fn diff(&self, base:&Checkpoint) -> CheckpointDelta
+    {
+        let changed = self.population.iter().enumerate()
+            .filter(|&(slot, ind)| base.population.get(slot) != Some(ind))
+            .map(|(slot, ind)| (slot, ind.clone()))
+            .collect();
+        let history_from = self.history.iter().zip(&base.history).take_while(|(a, b)| a == b).count();
+        CheckpointDelta {
+            base_generation:base.generation,
+            population_len:self.population.len(),
+            changed,
+            history_from,
+            history_tail:self.history[history_from..].to_vec(),
+            state:Checkpoint {population:Vec::new(), history:Vec::new(), ..self.clone()},
+        }
+    }
+}
+
+impl CheckpointDelta
+{
+    /// Rebuilds the checkpoint from the one it was taken against.
+    pub fn apply(self, base:&Checkpoint) -> Checkpoint
+    {
+        let mut population = base.population.clone();
+        population.truncate(self.population_len);
+        for (slot, ind) in self.changed {
+            if slot < population.len() {
+                population[slot] = ind;
+            }else{
+                population.push(ind);
+            }
+        }
+        let mut history = base.history[..self.history_from.min(base.history.len())].to_vec();
+        history.extend(self.history_tail);
+        Checkpoint {population, history, ..self.state}
+    }
+}
+
+/// Writes checkpoints of a run into a directory, a full keyframe every `keyframe_every`
+/// checkpoints and deltas from the previous checkpoint in between. Files are named after the
+/// generation, `generation-00000042.full.json` or `.delta.json`.
+#[derive(Debug, Clone)]
+pub struct CheckpointLog
+{
+    dir:PathBuf,
+    keyframe_every:u32,
+    previous:Option<Checkpoint>,
+    since_keyframe:u32,
+}
+
+fn file_generation(path:&Path, kind:&str) -> Option<u32>
+{
+    let name = path.file_name()?.to_str()?;
+    name.strip_prefix("generation-")?.strip_suffix(kind)?.parse().ok()
+}
+
+fn write_atomically(path:&Path, contents:String) -> Result<(), GaError>
+{
+    let partial = path.with_extension("partial");
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+impl CheckpointLog
+{
+    /// Creates `dir` if needed. The first checkpoint written is always a keyframe.
+    pub fn new<P:AsRef<Path>>(dir:P, keyframe_every:u32) -> Result<Self, GaError>
+    {
+        fs::create_dir_all(&dir)?;
+        Ok(CheckpointLog {dir:dir.as_ref().to_path_buf(), keyframe_every:keyframe_every.max(1), previous:None, since_keyframe:0})
+    }
+
+    /// Checkpoints `run`, returning the file written.
+    pub fn save(&mut self, run:&Run) -> Result<PathBuf, GaError>
+    {
+        let checkpoint = run.to_checkpoint();
+        let path = match &self.previous {
+            Some(previous) if self.since_keyframe < self.keyframe_every => {
+                let path = self.dir.join(format!("generation-{:08}.delta.json", checkpoint.generation));
+                write_atomically(&path, serde_json::to_string(&checkpoint.diff(previous))?)?;
+                self.since_keyframe += 1;
+                path
+            }
+            _ => {
+                let path = self.dir.join(format!("generation-{:08}.full.json", checkpoint.generation));
+                write_atomically(&path, serde_json::to_string(&checkpoint)?)?;
+                self.since_keyframe = 1;
+                path
+            }
+        };
+        self.previous = Some(checkpoint);
+        run.emit(LifecycleEvent::CheckpointWritten{generation:run.period, path:path.clone()});
+        Ok(path)
+    }
+
+    /// The latest checkpoint in `dir`: its last keyframe with the deltas written after it
+    /// applied in order. `None` if there is no keyframe.
+    pub fn load_latest<P:AsRef<Path>>(dir:P) -> Result<Option<Checkpoint>, GaError>
+    {
+        let mut files:Vec<PathBuf> = fs::read_dir(dir)?.map(|entry| entry.map(|e| e.path())).collect::<Result<_, _>>()?;
+        files.sort();
+        let Some(keyframe) = files.iter().rposition(|path| file_generation(path, ".full.json").is_some()) else {
+            return Ok(None);
+        };
+        let mut checkpoint:Checkpoint = serde_json::from_str(&fs::read_to_string(&files[keyframe])?)?;
+        for path in &files[keyframe + 1..] {
+            if file_generation(path, ".delta.json").is_none() {
+                continue;
+            }
+            let delta:CheckpointDelta = serde_json::from_str(&fs::read_to_string(path)?)?;
+            if delta.base_generation != checkpoint.generation {
+                break;
+            }
+            checkpoint = delta.apply(&checkpoint);
+        }
+        Ok(Some(checkpoint))
+    }
+}
+
+impl Run
+{
+    pub fn to_checkpoint(&self) -> Checkpoint
+    {
+        Checkpoint {
+            Pcross:self.Pcross, Pmut:self.Pmut, L:self.L, n:self.n, z:self.z,
+            generation:self.period,
+            population:self.population.to_chromosomes(),
+            best:self.best.clone(),
+            hall_of_fame:self.hall_of_fame.clone(),
+            archive:self.archive.as_ref().map(|archive| archive.entries().to_vec()),
+            history:self.history.clone(),
+            last_step:self.last_step,
+            evaluations:self.evaluations,
+            stagnant_generations:self.stagnant_generations,
+            rejected:self.rejected,
+            penalty:self.penalty,
+            adaptive_penalty:self.adaptive_penalty.clone(),
+            parent_log:self.parent_log.clone(),
+            market:self.market,
+            firm_costs:self.firm_costs.clone(),
+            stale:self.stale.clone(),
+            rng:self.rng.clone(),
+        }
+    }
+
+    /// Replaces the state of this run with `checkpoint`, keeping its configuration, and emits
+    /// `LifecycleEvent::Restarted`.
+    pub fn restore(&mut self, checkpoint:Checkpoint)
+    {
+        let Checkpoint {Pcross, Pmut, L, n, z, generation, population, best, hall_of_fame, archive, history, last_step, evaluations,
+            stagnant_generations, rejected, penalty, adaptive_penalty, parent_log, market, firm_costs, stale, rng} = checkpoint;
+        (self.Pcross, self.Pmut, self.L, self.n, self.z) = (Pcross, Pmut, L, n, z);
+        self.period = generation;
+        self.population = population.into_iter().collect();
+        self.stale = stale;
+        self.best = best;
+        self.hall_of_fame = hall_of_fame;
+        if let (Some(archive), Some(entries)) = (&mut self.archive, archive) {
+            archive.restore_entries(entries);
+        }
+        self.history = history;
+        self.last_step = last_step;
+        self.evaluations = evaluations;
+        self.stagnant_generations = stagnant_generations;
+        self.rejected = rejected;
+        self.penalty = penalty;
+        if adaptive_penalty.is_some() {
+            self.adaptive_penalty = adaptive_penalty;
+        }
+        if parent_log.is_some() {
+            self.parent_log = parent_log;
+        }
+        self.market = market;
+        self.firm_costs = firm_costs;
+        self.rng = rng;
+        self.emit(LifecycleEvent::Restarted{config_hash:self.config_hash(), generation:self.period});
+    }
+
+    /// Writes the checkpoint as JSON. The file is written next to `path` and renamed over it,
+    /// so a crash while saving leaves the previous checkpoint intact.
+    pub fn checkpoint<P:AsRef<Path>>(&self, path:P) -> Result<(), GaError>
+    {
+        write_atomically(path.as_ref(), serde_json::to_string(&self.to_checkpoint())?)?;
+        self.emit(LifecycleEvent::CheckpointWritten{generation:self.period, path:path.as_ref().to_path_buf()});
+        Ok(())
+    }
+
+    /// Restores a checkpoint written by `checkpoint` into this run, which must be set up with
+    /// the fitness, operators and options of the run that wrote it.
+    pub fn resume<P:AsRef<Path>>(&mut self, path:P) -> Result<(), GaError>
+    {
+        let checkpoint:Checkpoint = serde_json::from_str(&fs::read_to_string(path)?)?;
+        self.restore(checkpoint);
+        Ok(())
+    }
+}
+
+impl<G:Genome> GenomeRun<G>
+{
+    pub fn to_checkpoint(&self) -> GenomeCheckpoint<G>
+    {
+        GenomeCheckpoint {generation:self.period, population:self.population.clone(), best:self.best.clone(),
+            history:self.history.clone(), evaluations:self.evaluations, rng:self.rng.clone()}
+    }
+
+    /// Replaces the state of this run with `checkpoint`, keeping its configuration.
+    pub fn restore(&mut self, checkpoint:GenomeCheckpoint<G>)
+    {
+        let GenomeCheckpoint {generation, population, best, history, evaluations, rng} = checkpoint;
+        self.period = generation;
+        self.population = population;
+        self.best = best;
+        self.history = history;
+        self.evaluations = evaluations;
+        self.rng = rng;
+    }
+}
+
+impl<G:Genome + Serialize + DeserializeOwned> GenomeRun<G>
+{
+    /// Writes the checkpoint as JSON, atomically like `Run::checkpoint`.
+    pub fn checkpoint<P:AsRef<Path>>(&self, path:P) -> Result<(), GaError>
+    {
+        write_atomically(path.as_ref(), serde_json::to_string(&self.to_checkpoint())?)
+    }
+
+    pub fn resume<P:AsRef<Path>>(&mut self, path:P) -> Result<(), GaError>
+    {
+        let checkpoint:GenomeCheckpoint<G> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        self.restore(checkpoint);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::EpsilonArchive;
+    use crate::problems;
+    use crate::real::{RealChromosome, RealRun};
+    use crate::steady::ReplacementPolicy;
+
+    fn new_run(seed:u64) -> Run
+    {
+        let mut run = Run::with_seed(0.6, 0.05, 12, 20, 4, seed).unwrap();
+        run.set_fitness(problems::onemax_fitness);
+        run
+    }
+
+    #[test]
+    fn resumed_genome_runs_continue()
+    {
+        let new_run = |seed| {
+            let mut run = RealRun::with_seed(vec![(-5.0, 5.0); 3], 10, seed, |x:&[f64]| x.iter().sum());
+            run.add_constraint(|ind:&RealChromosome| ind.genes[0]);
+            run
+        };
+        let mut original = new_run(8);
+        original.run(4);
+        let path = std::env::temp_dir().join(format!("genome_checkpoint_{}.json", std::process::id()));
+        original.checkpoint(&path).unwrap();
+        original.run(4);
+
+        // a different seed, everything random comes from the checkpoint
+        let mut resumed = new_run(99);
+        resumed.resume(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        resumed.run(4);
+        assert_eq!(resumed.to_checkpoint(), original.to_checkpoint());
+    }
+
+    #[test]
+    fn resumed_runs_continue_bit_for_bit()
+    {
+        let path = std::env::temp_dir().join(format!("checkpoint_{}.json", std::process::id()));
+        let mut original = new_run(4);
+        original.run(15).unwrap();
+        original.checkpoint(&path).unwrap();
+        original.run(15).unwrap();
+
+        let mut resumed = new_run(99);
+        resumed.resume(&path).unwrap();
+        assert_eq!(resumed.generation(), 15);
+        resumed.run(15).unwrap();
+        assert_eq!(resumed.population_snapshot(), original.population_snapshot());
+        assert_eq!(resumed.best(), original.best());
+        assert_eq!(resumed.evaluations(), original.evaluations());
+        assert_eq!(resumed.history().len(), 30);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(resumed.resume(&path), Err(GaError::Io(_))));
+
+        // adaptive penalty streaks, the archive and the parent log carry over too
+        let constrained = |seed| {
+            let mut run = new_run(seed);
+            run.add_constraint(|ind| ind.data.count_ones() as f64 - 8.0);
+            run.set_adaptive_penalty(AdaptivePenalty::new(3, 2.0, 2.0));
+            run.set_archive(EpsilonArchive::new(vec![0.5]));
+            run.record_parents(true);
+            run
+        };
+        let mut original = constrained(5);
+        original.run(16).unwrap();
+        original.checkpoint(&path).unwrap();
+        original.run(16).unwrap();
+
+        let mut resumed = constrained(99);
+        resumed.resume(&path).unwrap();
+        resumed.run(16).unwrap();
+        let trace = |run:&Run| run.history().iter().map(|s| (s.penalty, s.mean_fitness, s.feasible_fraction)).collect::<Vec<_>>();
+        assert_eq!(trace(&resumed), trace(&original));
+        assert_eq!(resumed.population_snapshot(), original.population_snapshot());
+        assert_eq!(resumed.archive().unwrap().entries(), original.archive().unwrap().entries());
+        assert_eq!(resumed.parent_log(), original.parent_log());
+        assert!(original.history().windows(2).any(|w| w[0].penalty != w[1].penalty));
+
+        // a steady-state run resumes evaluating only the children still pending
+        let steady = |seed| {
+            let mut run = new_run(seed);
+            run.set_steady_state(ReplacementPolicy::Worst);
+            run
+        };
+        let mut original = steady(7);
+        original.run(10).unwrap();
+        original.checkpoint(&path).unwrap();
+        original.run(10).unwrap();
+        let mut resumed = steady(99);
+        resumed.resume(&path).unwrap();
+        resumed.run(10).unwrap();
+        assert_eq!(resumed.population_snapshot(), original.population_snapshot());
+        assert_eq!(resumed.evaluations(), original.evaluations());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn deltas_rebuild_the_latest_checkpoint()
+    {
+        let dir = std::env::temp_dir().join(format!("checkpoint_log_{}", std::process::id()));
+        let mut run = new_run(6);
+        let mut log = CheckpointLog::new(&dir, 4).unwrap();
+        let mut written = Vec::new();
+        for _ in 0..10 {
+            run.step();
+            written.push(log.save(&run).unwrap());
+        }
+        let keyframes = written.iter().filter(|path| file_generation(path, ".full.json").is_some()).count();
+        assert_eq!(keyframes, 3);
+        assert_eq!(CheckpointLog::load_latest(&dir).unwrap(), Some(run.to_checkpoint()));
+
+        let unchanged = run.to_checkpoint().diff(&run.to_checkpoint());
+        assert!(unchanged.changed.is_empty() && unchanged.history_tail.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
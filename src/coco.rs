@@ -0,0 +1,90 @@
+//! Export of benchmark runs in the layout of the COCO/BBOB experiment logger, so results can be
+//! post-processed with `cocopp`. For every function `bbobexp_f<id>.info` indexes the runs, and
+//! `data_f<id>/bbobexp_f<id>_DIM<d>.dat` holds one block per run with a line per generation.
+//! Traces only carry the best fitness so far, which fills both the measured and best columns.
+use crate::harness::Trace;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CocoExporter
+{
+    pub directory:PathBuf,
+    pub algorithm:String,
+    pub comment:String,
+    /// Target precision recorded in the info file, COCO uses `1e-8`.
+    pub precision:f64,
+}
+
+impl CocoExporter
+{
+    pub fn new(directory:&Path, algorithm:&str) -> Self
+    {
+        CocoExporter {directory:directory.to_path_buf(), algorithm:algorithm.to_string(),
+            comment:String::new(), precision:1e-8}
+    }
+
+    pub fn with_comment(mut self, comment:&str) -> Self
+    {
+        self.comment = comment.to_string();
+        self
+    }
+
+    /// Appends the runs of function `function_id` in `dimension` dimensions, whose optimum is
+    /// `fopt`. Each run is an instance number and its trace. Returns the info file.
+    pub fn write(&self, function_id:usize, dimension:usize, fopt:f64, runs:&[(u32, Trace)]) -> Result<PathBuf, Box<dyn Error>>
+    {
+        let data_name = format!("data_f{}/bbobexp_f{}_DIM{}.dat", function_id, function_id, dimension);
+        fs::create_dir_all(self.directory.join(format!("data_f{}", function_id)))?;
+
+        let mut data = OpenOptions::new().create(true).append(true).open(self.directory.join(&data_name))?;
+        let mut summaries = Vec::with_capacity(runs.len());
+        for (instance, trace) in runs {
+            writeln!(data, "% function evaluation | noise-free fitness - Fopt ({:.12e}) | best noise-free fitness - Fopt | measured fitness | best measured fitness", fopt)?;
+            for &(evaluations, best) in trace {
+                writeln!(data, "{} {:+.12e} {:+.12e} {:+.12e} {:+.12e}", evaluations, best - fopt, best - fopt, best, best)?;
+            }
+            let (evaluations, best) = trace.last().copied().unwrap_or((0, f64::INFINITY));
+            summaries.push(format!("{}:{}|{:.1e}", instance, evaluations, best - fopt));
+        }
+
+        let info_path = self.directory.join(format!("bbobexp_f{}.info", function_id));
+        let mut info = OpenOptions::new().create(true).append(true).open(&info_path)?;
+        writeln!(info, "funcId = {}, DIM = {}, Precision = {:.3e}, algId = '{}'", function_id, dimension, self.precision, self.algorithm)?;
+        writeln!(info, "% {}", self.comment)?;
+        writeln!(info, "{}, {}", data_name, summaries.join(", "))?;
+        Ok(info_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::Benchmark;
+
+    #[test]
+    fn writes_info_and_data()
+    {
+        let directory = std::env::temp_dir().join(format!("ga_coco_{}", std::process::id()));
+        let exporter = CocoExporter::new(&directory, "GA").with_comment("real-coded GA");
+        let runs:Vec<(u32, Trace)> = (1..=2).map(|instance| {
+            let mut run = Benchmark::Rastrigin.build_run(2, 10);
+            run.run(5);
+            (instance, run.trace())
+        }).collect();
+        let info_path = exporter.write(3, 2, 0.0, &runs).unwrap();
+
+        let info = fs::read_to_string(&info_path).unwrap();
+        let lines:Vec<&str> = info.lines().collect();
+        assert_eq!(lines[0], "funcId = 3, DIM = 2, Precision = 1.000e-8, algId = 'GA'");
+        assert_eq!(lines[1], "% real-coded GA");
+        assert!(lines[2].starts_with("data_f3/bbobexp_f3_DIM2.dat, 1:50|"));
+        let data = fs::read_to_string(directory.join("data_f3/bbobexp_f3_DIM2.dat")).unwrap();
+        assert_eq!(data.lines().filter(|l| l.starts_with('%')).count(), 2);
+        assert_eq!(data.lines().count(), 12);
+        assert!(data.lines().nth(1).unwrap().starts_with("10 +"));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
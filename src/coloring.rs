@@ -0,0 +1,225 @@
+//! Graph coloring: a genome is an integer vector holding one of `colors` colors per vertex, and
+//! fitness is the number of conflicting edges (both ends the same color), to be minimized.
+//! Graphs are read from DIMACS `.col` files.
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::error::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph
+{
+    pub adjacency:Vec<Vec<usize>>,
+}
+
+impl Graph
+{
+    pub fn new(vertices:usize) -> Self
+    {
+        Graph {adjacency:vec![Vec::new(); vertices]}
+    }
+
+    /// Adds the undirected edge `a - b`, ignoring loops and duplicates.
+    pub fn add_edge(&mut self, a:usize, b:usize)
+    {
+        if a != b && !self.adjacency[a].contains(&b) {
+            self.adjacency[a].push(b);
+            self.adjacency[b].push(a);
+        }
+    }
+
+    pub fn vertices(&self) -> usize
+    {
+        self.adjacency.len()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_
+    {
+        self.adjacency.iter().enumerate()
+            .flat_map(|(a, neighbours)| neighbours.iter().filter(move |&&b| a < b).map(move |&b| (a, b)))
+    }
+
+    /// Parses the DIMACS graph format: `c` comment lines, one `p edge <vertices> <edges>` line
+    /// and `e <a> <b>` lines with 1-based vertices.
+    pub fn parse_dimacs(text:&str) -> Result<Self, Box<dyn Error>>
+    {
+        let mut graph:Option<Graph> = None;
+        for (number, line) in text.lines().enumerate() {
+            let fields:Vec<&str> = line.split_whitespace().collect();
+            match fields.first() {
+                None | Some(&"c") => {}
+                Some(&"p") if fields.len() >= 3 => graph = Some(Graph::new(fields[2].parse()?)),
+                Some(&"e") if fields.len() >= 3 => {
+                    let graph = graph.as_mut().ok_or(format!("line {}: edge before the problem line", number + 1))?;
+                    let (a, b):(usize, usize) = (fields[1].parse()?, fields[2].parse()?);
+                    if a == 0 || b == 0 || a > graph.vertices() || b > graph.vertices() {
+                        return Err(format!("line {}: vertex out of range", number + 1).into());
+                    }
+                    graph.add_edge(a - 1, b - 1);
+                }
+                _ => return Err(format!("line {}: unexpected '{}'", number + 1, line).into()),
+            }
+        }
+        graph.ok_or_else(|| "missing problem line".into())
+    }
+
+    pub fn from_dimacs(file_name:&str) -> Result<Self, Box<dyn Error>>
+    {
+        Self::parse_dimacs(&std::fs::read_to_string(file_name)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphColoring
+{
+    pub graph:Graph,
+    pub colors:usize,
+}
+
+impl GraphColoring
+{
+    pub fn new(graph:Graph, colors:usize) -> Self
+    {
+        assert!(colors > 0, "need at least one color");
+        GraphColoring {graph, colors}
+    }
+
+    pub fn random<R:Rng + ?Sized>(&self, rng:&mut R) -> Vec<usize>
+    {
+        (0..self.graph.vertices()).map(|_| rng.gen_range(0..self.colors)).collect()
+    }
+
+    /// Number of edges whose ends share a color, the fitness to minimize.
+    pub fn conflicts(&self, coloring:&[usize]) -> usize
+    {
+        self.graph.edges().filter(|&(a, b)| coloring[a] == coloring[b]).count()
+    }
+
+    // conflicting edges with at least one end in `vertices`, counted once
+    fn conflicts_around(&self, coloring:&[usize], vertices:&[usize], member:&[bool]) -> usize
+    {
+        vertices.iter()
+            .flat_map(|&a| self.graph.adjacency[a].iter().map(move |&b| (a, b)))
+            .filter(|&(a, b)| coloring[a] == coloring[b] && (!member[b] || a < b))
+            .count()
+    }
+
+    /// Vertices reachable from `start` along edges alternating between colors `a` and `b`,
+    /// where `start` has color `a`.
+    pub fn kempe_chain(&self, coloring:&[usize], start:usize, b:usize) -> Vec<usize>
+    {
+        let a = coloring[start];
+        let mut member = vec![false; self.graph.vertices()];
+        let mut chain = vec![start];
+        let mut queue = VecDeque::from([start]);
+        member[start] = true;
+        while let Some(v) = queue.pop_front() {
+            let other = if coloring[v] == a { b } else { a };
+            for &w in &self.graph.adjacency[v] {
+                if !member[w] && coloring[w] == other {
+                    member[w] = true;
+                    chain.push(w);
+                    queue.push_back(w);
+                }
+            }
+        }
+        chain
+    }
+
+    /// Kempe-chain local search: repeatedly picks a conflicting vertex and applies the best
+    /// improving chain interchange to another color. Stops when no conflicting vertex improves
+    /// or after `max_moves` interchanges were evaluated. Returns the number of conflicts removed.
+    pub fn kempe_repair<R:Rng + ?Sized>(&self, coloring:&mut [usize], max_moves:usize, rng:&mut R) -> usize
+    {
+        let before = self.conflicts(coloring);
+        let mut member = vec![false; self.graph.vertices()];
+        let mut evaluated = 0;
+        'search: loop {
+            let mut conflicting:Vec<usize> = (0..self.graph.vertices())
+                .filter(|&v| self.graph.adjacency[v].iter().any(|&w| coloring[w] == coloring[v]))
+                .collect();
+            conflicting.shuffle(rng);
+            for v in conflicting {
+                let a = coloring[v];
+                let mut best:Option<(isize, Vec<usize>, usize)> = None;
+                for b in (0..self.colors).filter(|&b| b != a) {
+                    if evaluated >= max_moves {
+                        break 'search;
+                    }
+                    evaluated += 1;
+                    let chain = self.kempe_chain(coloring, v, b);
+                    chain.iter().for_each(|&w| member[w] = true);
+                    let old = self.conflicts_around(coloring, &chain, &member) as isize;
+                    swap_colors(coloring, &chain, a, b);
+                    let delta = self.conflicts_around(coloring, &chain, &member) as isize - old;
+                    swap_colors(coloring, &chain, a, b);
+                    chain.iter().for_each(|&w| member[w] = false);
+                    if delta < 0 && best.as_ref().is_none_or(|(d, _, _)| delta < *d) {
+                        best = Some((delta, chain, b));
+                    }
+                }
+                if let Some((_, chain, b)) = best {
+                    swap_colors(coloring, &chain, a, b);
+                    continue 'search;
+                }
+            }
+            break;
+        }
+        before - self.conflicts(coloring)
+    }
+}
+
+fn swap_colors(coloring:&mut [usize], chain:&[usize], a:usize, b:usize)
+{
+    for &v in chain {
+        coloring[v] = if coloring[v] == a { b } else { a };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PETERSEN:&str = "c Petersen graph\np edge 10 15\n\
+        e 1 2\ne 2 3\ne 3 4\ne 4 5\ne 5 1\n\
+        e 1 6\ne 2 7\ne 3 8\ne 4 9\ne 5 10\n\
+        e 6 8\ne 8 10\ne 10 7\ne 7 9\ne 9 6\n";
+
+    #[test]
+    fn parses_dimacs()
+    {
+        let graph = Graph::parse_dimacs(PETERSEN).unwrap();
+        assert_eq!(graph.vertices(), 10);
+        assert_eq!(graph.edges().count(), 15);
+        assert!(graph.adjacency.iter().all(|n| n.len() == 3));
+        assert!(Graph::parse_dimacs("e 1 2\n").is_err());
+        assert!(Graph::parse_dimacs("p edge 2 1\ne 1 3\n").is_err());
+    }
+
+    #[test]
+    fn kempe_chains_alternate_colors()
+    {
+        let coloring = GraphColoring::new(Graph::parse_dimacs(PETERSEN).unwrap(), 3);
+        let colors = vec![0, 1, 0, 1, 2, 1, 2, 2, 0, 0];
+        let chain = coloring.kempe_chain(&colors, 0, 1);
+        assert!(chain.iter().all(|&v| colors[v] == 0 || colors[v] == 1));
+        assert!(chain.contains(&1) && chain.contains(&5));
+    }
+
+    #[test]
+    fn repair_never_adds_conflicts()
+    {
+        let mut rng = rand::thread_rng();
+        let coloring = GraphColoring::new(Graph::parse_dimacs(PETERSEN).unwrap(), 3);
+        for _ in 0..20 {
+            let mut colors = coloring.random(&mut rng);
+            let before = coloring.conflicts(&colors);
+            let removed = coloring.kempe_repair(&mut colors, 1000, &mut rng);
+            assert_eq!(coloring.conflicts(&colors), before - removed);
+            assert!(colors.iter().all(|&c| c < 3));
+        }
+        let mut colors = vec![0; 10];
+        coloring.kempe_repair(&mut colors, 2, &mut rand::thread_rng());
+        assert!(coloring.conflicts(&colors) < 15);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::cournot::Decoding;
+use crate::operators::Tournament;
+use crate::{GenerationStats, ProblemRegistry, Run};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::mem::size_of;
+use std::path::Path;
+
+/// Everything needed to reproduce a run, loadable from a JSON file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunConfig
+{
+    pub problem:String,
+    pub Pcross:f32,
+    pub Pmut:f32,
+    pub L:u8,
+    pub n:usize,
+    pub z:u8,
+    pub iterations:u32,
+    pub output:String,
+    /// Tournament selection of this size instead of roulette.
+    pub tournament_size:Option<usize>,
+    /// Best individuals copied unchanged into the next generation.
+    pub elitism:usize,
+    /// Parents selected per generation as a multiple of `n`.
+    pub mating_pool:f64,
+    /// Genomes are Gray codes of the integer the fitness sees, see `Decoding::gray`.
+    pub gray:bool,
+    /// Seed of a reproducible run, see `Run::with_seed`; `None` seeds from entropy.
+    pub seed:Option<u64>,
+}
+
+impl Default for RunConfig
+{
+    fn default() -> Self
+    {
+        RunConfig {problem:"cournot".to_string(), Pcross:0.322, Pmut:0.00522, L:10, n:30, z:2,
+            iterations:1000, output:"run_3.csv".to_string(), tournament_size:None, elitism:0,
+            mating_pool:1.0, gray:false, seed:None}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError
+{
+    ProbabilityOutOfRange{name:&'static str, value:f32},
+    GenomeLength{L:u8},
+    CrossoverPoint{z:u8, L:u8},
+    PopulationSize{n:usize},
+    TournamentSize,
+    Elitism{k:usize, n:usize},
+    MatingPool{factor:f64},
+    NoIterations,
+    UnknownProblem{name:String},
+}
+
+impl fmt::Display for ConfigError
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            ConfigError::ProbabilityOutOfRange{name, value} => write!(f, "{} must be in [0, 1], got {}", name, value),
+            ConfigError::GenomeLength{L} => write!(f, "genome length L must be in 1..=64, got {}", L),
+            ConfigError::CrossoverPoint{z, L} => write!(f, "crossover point z must be in 1..=L ({}), got {}", L, z),
+            ConfigError::PopulationSize{n} => write!(f, "population size n must be even and at least 2, got {}", n),
+            ConfigError::TournamentSize => write!(f, "tournament size must be at least 1"),
+            ConfigError::Elitism{k, n} => write!(f, "elitism must not exceed the population size {}, got {}", n, k),
+            ConfigError::MatingPool{factor} => write!(f, "mating pool factor must be positive, got {}", factor),
+            ConfigError::NoIterations => write!(f, "iterations must be at least 1"),
+            ConfigError::UnknownProblem{name} => write!(f, "unknown problem '{}'", name),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetEstimate
+{
+    pub evaluations:u64,
+    pub population_bytes:usize,
+    pub history_bytes:usize,
+}
+
+impl RunConfig
+{
+    pub fn from_json_file(path:&Path) -> Result<Self, Box<dyn Error>>
+    {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Checks every parameter and returns all problems found, not just the first.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>>
+    {
+        let mut errors = Vec::new();
+        for (name, value) in [("Pcross", self.Pcross), ("Pmut", self.Pmut)] {
+            if !(0.0..=1.0).contains(&value) {
+                errors.push(ConfigError::ProbabilityOutOfRange{name, value});
+            }
+        }
+        if !(1..=64).contains(&self.L) {
+            errors.push(ConfigError::GenomeLength{L:self.L});
+        }else if self.z == 0 || self.z > self.L {
+            errors.push(ConfigError::CrossoverPoint{z:self.z, L:self.L});
+        }
+        // pairing needs a partner for everyone
+        if self.n < 2 || !self.n.is_multiple_of(2) {
+            errors.push(ConfigError::PopulationSize{n:self.n});
+        }
+        if self.tournament_size == Some(0) {
+            errors.push(ConfigError::TournamentSize);
+        }
+        if self.elitism > self.n {
+            errors.push(ConfigError::Elitism{k:self.elitism, n:self.n});
+        }
+        if !(self.mating_pool > 0.0 && self.mating_pool.is_finite()) {
+            errors.push(ConfigError::MatingPool{factor:self.mating_pool});
+        }
+        if self.iterations == 0 {
+            errors.push(ConfigError::NoIterations);
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// `validate` plus a check that the problem exists in `registry`.
+    pub fn validate_with(&self, registry:&ProblemRegistry) -> Result<(), Vec<ConfigError>>
+    {
+        let mut errors = self.validate().err().unwrap_or_default();
+        if registry.get(&self.problem).is_none() {
+            errors.push(ConfigError::UnknownProblem{name:self.problem.clone()});
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Rough cost of the run: fitness evaluations and the bytes held by the population
+    /// (twice, while the next generation is built) and by the per-generation history.
+    pub fn estimate(&self) -> BudgetEstimate
+    {
+        let generations = self.iterations as usize;
+        BudgetEstimate {
+            evaluations:self.n as u64 * self.iterations as u64,
+            population_bytes:2 * self.n * (size_of::<u64>() + 2 * size_of::<f64>()),
+            history_bytes:generations * (size_of::<GenerationStats>() + size_of::<(u64, f64)>()),
+        }
+    }
+
+    pub fn build_run(&self, registry:&ProblemRegistry) -> Result<Run, Vec<ConfigError>>
+    {
+        self.validate_with(registry)?;
+        let problem = registry.get(&self.problem).expect("validated above");
+        let mut run = Run::unchecked(self.Pcross, self.Pmut, self.L, self.n, self.z, self.seed);
+        problem.set_up(&mut run, self.L);
+        self.configure(&mut run);
+        Ok(run)
+    }
+
+    // applies the settings beyond the `Run::new` parameters
+    pub(crate) fn configure(&self, run:&mut Run)
+    {
+        if let Some(size) = self.tournament_size {
+            run.set_selection(Box::new(Tournament{size}));
+        }
+        run.set_elitism(self.elitism);
+        run.set_mating_pool(self.mating_pool);
+        if self.gray {
+            run.set_decoding(Decoding::gray());
+        }
+    }
+}
+
+impl fmt::Display for RunConfig
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "problem     {}", self.problem)?;
+        writeln!(f, "Pcross      {}", self.Pcross)?;
+        writeln!(f, "Pmut        {}", self.Pmut)?;
+        writeln!(f, "L           {}", self.L)?;
+        writeln!(f, "n           {}", self.n)?;
+        writeln!(f, "z           {}", self.z)?;
+        writeln!(f, "iterations  {}", self.iterations)?;
+        match self.tournament_size {
+            Some(size) => writeln!(f, "selection   tournament of {}", size)?,
+            None => writeln!(f, "selection   roulette")?,
+        }
+        writeln!(f, "elitism     {}", self.elitism)?;
+        writeln!(f, "mating pool {} x n", self.mating_pool)?;
+        writeln!(f, "encoding    {}", if self.gray { "gray" } else { "binary" })?;
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed        {}", seed)?;
+        }
+        write!(f, "output      {}", self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid()
+    {
+        let config = RunConfig::default();
+        assert_eq!(config.validate_with(&ProblemRegistry::with_builtins()), Ok(()));
+        assert_eq!(config.estimate().evaluations, 30_000);
+    }
+
+    #[test]
+    fn reports_every_error()
+    {
+        let config = RunConfig {Pcross:1.5, z:12, n:31, iterations:0, problem:"nope".to_string(), tournament_size:Some(0),
+            mating_pool:0.0, ..RunConfig::default()};
+        let errors = config.validate_with(&ProblemRegistry::with_builtins()).unwrap_err();
+        assert_eq!(errors, vec![
+            ConfigError::ProbabilityOutOfRange{name:"Pcross", value:1.5},
+            ConfigError::CrossoverPoint{z:12, L:10},
+            ConfigError::PopulationSize{n:31},
+            ConfigError::TournamentSize,
+            ConfigError::MatingPool{factor:0.0},
+            ConfigError::NoIterations,
+            ConfigError::UnknownProblem{name:"nope".to_string()},
+        ]);
+    }
+
+    #[test]
+    fn partial_json_uses_defaults()
+    {
+        let config:RunConfig = serde_json::from_str(r#"{"problem": "onemax", "L": 32}"#).unwrap();
+        assert_eq!(config.L, 32);
+        assert_eq!(config.n, 30);
+        assert_eq!(config.tournament_size, None);
+        let config:RunConfig = serde_json::from_str(r#"{"problem": "onemax", "tournament_size": 3, "gray": true}"#).unwrap();
+        let run = config.build_run(&ProblemRegistry::with_builtins()).unwrap();
+        assert!(format!("{:?}", run).contains("Tournament"));
+        assert!(format!("{:?}", run).contains("gray: true"));
+
+        let config:RunConfig = serde_json::from_str(r#"{"problem": "onemax", "seed": 5}"#).unwrap();
+        let registry = ProblemRegistry::with_builtins();
+        let (mut a, mut b) = (config.build_run(&registry).unwrap(), config.build_run(&registry).unwrap());
+        assert_eq!(a.run(5).unwrap().population, b.run(5).unwrap().population);
+    }
+}
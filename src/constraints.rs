@@ -0,0 +1,185 @@
+use crate::objective::Objective;
+use crate::Chromosome;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A constraint returns how much `ind` violates it, anything <= 0 counts as satisfied.
+pub type Constraint = fn(&Chromosome) -> f64;
+
+pub fn total_violation(constraints:&[Constraint], ind:&Chromosome) -> f64
+{
+    // NaN is kept so the run's NonFinitePolicy gets to see it
+    constraints.iter()
+        .map(|constraint| constraint(ind))
+        .map(|violation| if violation.is_nan() { violation } else { violation.max(0.0) })
+        .sum()
+}
+
+pub fn is_feasible(ind:&Chromosome) -> bool
+{
+    ind.violation <= 0.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstraintHandling
+{
+    /// Fitness is reduced by `penalty * violation`, selection stays fitness-proportionate.
+    Penalty,
+    /// Stochastic ranking (Runarsson & Yao): fitness is left unpenalized, the population is
+    /// sorted by a stochastic bubble sort that compares fitness with probability `pf` (or always
+    /// when both are feasible) and violation otherwise, then selected by linear rank.
+    StochasticRanking{pf:f64},
+}
+
+/// Returns population indices ordered from best to worst.
+pub fn stochastic_ranking<R:Rng>(population:&[Chromosome], pf:f64, objective:Objective, rng:&mut R) -> Vec<usize>
+{
+    let fitness:Vec<f64> = population.iter().map(|ind| ind.fitness).collect();
+    let violation:Vec<f64> = population.iter().map(|ind| ind.violation).collect();
+    stochastic_ranking_columns(&fitness, &violation, pf, objective, rng)
+}
+
+pub(crate) fn stochastic_ranking_columns<R:Rng>(fitness:&[f64], violation:&[f64], pf:f64, objective:Objective, rng:&mut R) -> Vec<usize>
+{
+    let mut order:Vec<usize> = (0..fitness.len()).collect();
+    for _ in 0..fitness.len() {
+        let mut swapped = false;
+        for j in 0..order.len().saturating_sub(1) {
+            let (a, b) = (order[j], order[j + 1]);
+            let swap = if (violation[a] <= 0.0 && violation[b] <= 0.0) || rng.gen::<f64>() < pf {
+                objective.is_better(fitness[b], fitness[a])
+            }else{
+                violation[a] > violation[b]
+            };
+            if swap {
+                order.swap(j, j + 1);
+                swapped = true;
+            }
+        }
+        if !swapped {
+            break;
+        }
+    }
+    order
+}
+
+/// Penalty scaling after Bean & Hadj-Alouane: if the best individual was infeasible for the last
+/// `k` generations the coefficient is multiplied by `increase`, if it was feasible for the last `k`
+/// generations it is divided by `decrease`, otherwise it is left alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptivePenalty
+{
+    pub k:u32,
+    pub increase:f64,
+    pub decrease:f64,
+    pub min_penalty:f64,
+    pub max_penalty:f64,
+    infeasible_streak:u32,
+    feasible_streak:u32,
+}
+
+impl AdaptivePenalty
+{
+    pub fn new(k:u32, increase:f64, decrease:f64) -> Self
+    {
+        assert!(k > 0 && increase > 1.0 && decrease > 1.0, "need k > 0 and scaling factors > 1");
+        AdaptivePenalty {k, increase, decrease, min_penalty:f64::MIN_POSITIVE, max_penalty:f64::MAX,
+            infeasible_streak:0, feasible_streak:0}
+    }
+
+    pub fn with_bounds(mut self, min_penalty:f64, max_penalty:f64) -> Self
+    {
+        self.min_penalty = min_penalty;
+        self.max_penalty = max_penalty;
+        self
+    }
+
+    /// Returns the penalty coefficient to use in the next generation.
+    pub fn update(&mut self, penalty:f64, best_feasible:bool) -> f64
+    {
+        if best_feasible {
+            self.feasible_streak += 1;
+            self.infeasible_streak = 0;
+        }else{
+            self.infeasible_streak += 1;
+            self.feasible_streak = 0;
+        }
+
+        let new_penalty = if self.infeasible_streak >= self.k {
+            self.infeasible_streak = 0;
+            penalty * self.increase
+        }else if self.feasible_streak >= self.k {
+            self.feasible_streak = 0;
+            penalty / self.decrease
+        }else{
+            penalty
+        };
+        new_penalty.clamp(self.min_penalty, self.max_penalty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at_most_100(ind:&Chromosome) -> f64
+    {
+        ind.data as f64 - 100.0
+    }
+
+    fn even(ind:&Chromosome) -> f64
+    {
+        (ind.data % 2) as f64
+    }
+
+    #[test]
+    fn violations_add_up()
+    {
+        let ind = Chromosome {data:103, fitness:0.0, N:1, violation:0.0};
+        assert_eq!(total_violation(&[at_most_100, even], &ind), 4.0);
+
+        let ind = Chromosome {data:42, fitness:0.0, N:1, violation:0.0};
+        assert_eq!(total_violation(&[at_most_100, even], &ind), 0.0);
+    }
+
+    #[test]
+    fn stochastic_ranking_with_zero_pf_sorts_by_violation()
+    {
+        let population = vec![
+            Chromosome {data:1, fitness:9.0, N:4, violation:2.0},
+            Chromosome {data:2, fitness:1.0, N:4, violation:0.0},
+            Chromosome {data:3, fitness:5.0, N:4, violation:1.0},
+            Chromosome {data:4, fitness:3.0, N:4, violation:0.0},
+        ];
+        let order = stochastic_ranking(&population, 0.0, Objective::Maximize, &mut rand::thread_rng());
+        assert_eq!(order, vec![3, 1, 2, 0]);
+
+        let order = stochastic_ranking(&population, 1.0, Objective::Maximize, &mut rand::thread_rng());
+        assert_eq!(order, vec![0, 2, 3, 1]);
+
+        let order = stochastic_ranking(&population, 1.0, Objective::Minimize, &mut rand::thread_rng());
+        assert_eq!(order, vec![1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn adaptive_penalty_needs_k_generations()
+    {
+        let mut adaptive = AdaptivePenalty::new(3, 2.0, 4.0);
+        let mut penalty = 1.0;
+        for _ in 0..2 {
+            penalty = adaptive.update(penalty, false);
+        }
+        assert_eq!(penalty, 1.0);
+        penalty = adaptive.update(penalty, false);
+        assert_eq!(penalty, 2.0);
+
+        penalty = adaptive.update(penalty, true);
+        penalty = adaptive.update(penalty, false);
+        assert_eq!(penalty, 2.0);
+
+        for _ in 0..3 {
+            penalty = adaptive.update(penalty, true);
+        }
+        assert_eq!(penalty, 0.5);
+    }
+}
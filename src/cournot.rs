@@ -0,0 +1,398 @@
+//! The Cournot market the crate was written for. Slot `i` of the population is firm `i`: it keeps
+//! its marginal cost across generations and adopts whatever quantity selection puts in its slot.
+use crate::error::GaError;
+use crate::operators::Fitness;
+use crate::stats::PopulationStats;
+use crate::{Chromosome, Run};
+use crate::population::Population;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::File;
+
+/// Maps a genome to the firm's quantity `offset + scale * x`, where `x` is the genome read as a
+/// plain binary number or, with `gray`, as a reflected Gray code. Genome length and quantity
+/// range can then be chosen independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decoding
+{
+    pub scale:u64,
+    pub offset:u64,
+    pub gray:bool,
+}
+
+impl Default for Decoding
+{
+    fn default() -> Self
+    {
+        Decoding {scale:1, offset:0, gray:false}
+    }
+}
+
+impl Decoding
+{
+    /// Gray-coded genomes read as the integer they encode, without scaling. Neighbouring
+    /// integers then differ in one bit, so mutation has no Hamming cliffs to jump.
+    pub fn gray() -> Self
+    {
+        Decoding {gray:true, ..Decoding::default()}
+    }
+
+    pub fn decode(&self, genome:u64) -> u64
+    {
+        let mut x = genome;
+        if self.gray {
+            let mut shift = 1;
+            while shift < 64 {
+                x ^= x >> shift;
+                shift <<= 1;
+            }
+        }
+        self.offset.saturating_add(self.scale.saturating_mul(x))
+    }
+
+    /// Genome of an `L`-bit firm whose quantity is closest to `quantity`.
+    pub fn encode(&self, quantity:u64, L:u8) -> u64
+    {
+        let max = if L >= 64 { u64::MAX } else { (1u64 << L) - 1 };
+        let x = (quantity.saturating_sub(self.offset) as f64 / self.scale.max(1) as f64).round();
+        let x = (x as u64).min(max);
+        if self.gray { x ^ (x >> 1) } else { x }
+    }
+}
+
+/// Linear demand where firm `i` gets the price `intercept - own_slope*q_i - cross_slope*(Q - q_i)`
+/// for its output `q_i` when the industry produces `Q`. The default is the crate's original model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Market
+{
+    pub intercept:f64,
+    pub own_slope:f64,
+    pub cross_slope:f64,
+}
+
+impl Default for Market
+{
+    fn default() -> Self
+    {
+        Market {intercept:20000.0, own_slope:1.0, cross_slope:0.52}
+    }
+}
+
+impl Market
+{
+    /// Price for a firm producing `quantity` out of an industry output of `data_sum`, never negative.
+    pub fn price(&self, quantity:u64, data_sum:u64) -> f64
+    {
+        let others = data_sum.saturating_sub(quantity) as f64;
+        (self.intercept - self.own_slope*quantity as f64 - self.cross_slope*others).max(0.0)
+    }
+
+    /// Revenue of the firm, zero if `data_sum` can't include `quantity`.
+    pub fn profit(&self, quantity:u64, data_sum:u64) -> f64
+    {
+        if data_sum < quantity {
+            return 0.0;
+        }
+        self.price(quantity, data_sum) * quantity as f64
+    }
+
+    /// Output-weighted mean price of the industry, zero when nothing is produced.
+    pub fn market_price(&self, data:&[u64]) -> f64
+    {
+        let data_sum = crate::sum_data(data);
+        if data_sum == 0 {
+            return 0.0;
+        }
+        let revenue:f64 = data.iter().map(|q| self.profit(*q, data_sum)).sum();
+        revenue / data_sum as f64
+    }
+}
+
+/// Surplus of one generation. Consumer surplus comes from the quadratic utility behind the
+/// linear demand, producer surplus is the sum of the firms' profits net of their costs.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Welfare
+{
+    pub consumer_surplus:f64,
+    pub producer_surplus:f64,
+    pub total:f64,
+}
+
+impl Market
+{
+    /// Welfare of the industry producing `data`, `costs` are the firms' marginal costs if any.
+    pub fn welfare(&self, data:&[u64], costs:Option<&[f64]>) -> Welfare
+    {
+        let data_sum = crate::sum_data(data);
+        let total_output = data_sum as f64;
+        let squares:f64 = data.iter().map(|q| (*q as f64).powi(2)).sum();
+        let consumer_surplus = self.own_slope/2.0*squares + self.cross_slope/2.0*(total_output.powi(2) - squares);
+        let producer_surplus = data.iter().enumerate()
+            .map(|(i, q)| {
+                let cost = costs.map_or(0.0, |costs| costs[i]);
+                (self.profit(*q, data_sum) - cost * *q as f64).max(0.0)
+            })
+            .sum();
+        Welfare {consumer_surplus, producer_surplus, total:consumer_surplus + producer_surplus}
+    }
+}
+
+/// Analytical equilibrium of the market, see `Market::nash` and `Market::competitive`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equilibrium
+{
+    pub quantities:Vec<f64>,
+    pub output:f64,
+    /// Output-weighted mean price.
+    pub price:f64,
+}
+
+impl Market
+{
+    /// Cournot-Nash quantities for firms with marginal costs `costs`: every firm best-responds,
+    /// `(2*own_slope - cross_slope)*q_i + cross_slope*Q = intercept - c_i`. Interior solution,
+    /// a firm too costly to produce gets a negative quantity.
+    pub fn nash(&self, costs:&[f64]) -> Equilibrium
+    {
+        self.solve(2.0*self.own_slope - self.cross_slope, costs)
+    }
+
+    /// Competitive quantities where every firm's price equals its marginal cost.
+    pub fn competitive(&self, costs:&[f64]) -> Equilibrium
+    {
+        self.solve(self.own_slope - self.cross_slope, costs)
+    }
+
+    // solves k*q_i + cross_slope*Q = intercept - c_i for all firms
+    fn solve(&self, k:f64, costs:&[f64]) -> Equilibrium
+    {
+        let n = costs.len() as f64;
+        let margin:f64 = costs.iter().map(|c| self.intercept - c).sum();
+        let output = margin / (k + n*self.cross_slope);
+        let quantities:Vec<f64> = costs.iter().map(|c| (self.intercept - c - self.cross_slope*output) / k).collect();
+        let revenue:f64 = quantities.iter()
+            .map(|q| (self.intercept - self.own_slope*q - self.cross_slope*(output - q)) * q)
+            .sum();
+        let price = if output == 0.0 { 0.0 } else { revenue / output };
+        Equilibrium {quantities, output, price}
+    }
+}
+
+impl Fitness for Market
+{
+    fn evaluate(&self, ind:&Chromosome, context:&PopulationStats) -> f64
+    {
+        self.profit(ind.data, context.data_sum)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FirmStats
+{
+    pub firm:usize,
+    pub cost:f64,
+    pub quantity:u64,
+    /// Fitness minus `cost * quantity`, negative for a firm selling below its cost.
+    pub profit:f64,
+    /// Share of the industry output, zero when nothing is produced.
+    pub market_share:f64,
+}
+
+impl Run
+{
+    /// Uses `market` both as the fitness and for the `price` column of the stats.
+    pub fn set_market(&mut self, market:Market)
+    {
+        self.market = market;
+        self.set_fitness_function(Box::new(market));
+        self.custom_fitness = false;
+    }
+
+    pub fn market(&self) -> Market
+    {
+        self.market
+    }
+
+    /// Marginal cost of every firm, one per population slot.
+    pub fn set_firm_costs(&mut self, costs:Vec<f64>)
+    {
+        assert_eq!(costs.len(), self.n, "one cost is needed per firm");
+        self.firm_costs = Some(costs);
+    }
+
+    /// Draws every firm's marginal cost uniformly from `min..=max`.
+    pub fn draw_firm_costs<R:Rng + ?Sized>(&mut self, min:f64, max:f64, rng:&mut R)
+    {
+        let costs = (0..self.n).map(|_| rng.gen_range(min..=max)).collect();
+        self.set_firm_costs(costs);
+    }
+
+    pub fn firm_costs(&self) -> Option<&[f64]>
+    {
+        self.firm_costs.as_deref()
+    }
+
+    /// Quantities are decoded from the genomes with `decoding` before the fitness, constraints,
+    /// market price, welfare and industry output see them.
+    pub fn set_decoding(&mut self, decoding:Decoding)
+    {
+        self.decoding = Some(decoding);
+    }
+
+    pub(crate) fn quantity(&self, genome:u64) -> u64
+    {
+        self.decoding.map_or(genome, |decoding| decoding.decode(genome))
+    }
+
+    /// The population with every genome replaced by its quantity.
+    pub(crate) fn phenotypes(&self) -> Cow<'_, Population>
+    {
+        match self.decoding {
+            None => Cow::Borrowed(&self.population),
+            Some(decoding) => {
+                let mut population = self.population.clone();
+                population.data.iter_mut().for_each(|data| *data = decoding.decode(*data));
+                Cow::Owned(population)
+            }
+        }
+    }
+
+    pub(crate) fn firm_cost(&self, firm:usize) -> f64
+    {
+        self.firm_costs.as_ref().map_or(0.0, |costs| costs[firm])
+    }
+
+    /// Nash and competitive references for this run's firms, costs default to zero.
+    pub fn equilibria(&self) -> (Equilibrium, Equilibrium)
+    {
+        let costs = self.firm_costs.clone().unwrap_or_else(|| vec![0.0; self.n]);
+        (self.market.nash(&costs), self.market.competitive(&costs))
+    }
+
+    /// Profit and market share of every firm in the current population.
+    pub fn firm_stats(&self) -> Vec<FirmStats>
+    {
+        let phenotypes = self.phenotypes();
+        let data_sum = phenotypes.data_sum();
+        phenotypes.iter().enumerate().map(|(firm, ind)| {
+            let cost = self.firm_cost(firm);
+            let profit = self.fitness.evaluate(&ind, &self.population_stats(data_sum)) - cost * ind.data as f64;
+            let market_share = if data_sum == 0 { 0.0 } else { ind.data as f64 / data_sum as f64 };
+            FirmStats {firm, cost, quantity:ind.data, profit, market_share}
+        }).collect()
+    }
+}
+
+pub fn save_firm_stats_to_csv(firms:&[FirmStats], file_name:&str) -> Result<(), GaError>
+{
+    let file = File::create(file_name)?;
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(["firm", "cost", "quantity", "profit", "market_share"])?;
+    for firm in firms {
+        writer.write_record(&[firm.firm.to_string(), firm.cost.to_string(), firm.quantity.to_string(),
+            firm.profit.to_string(), firm.market_share.to_string()])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_market_matches_fitness()
+    {
+        let ind = Chromosome {data:700, fitness:0.0, N:10, violation:0.0};
+        assert_eq!(Market::default().evaluate(&ind, &PopulationStats::new(2, 3000)), crate::problems::cournot_fitness(&ind, 3000));
+        assert_eq!(Market::default().market_price(&[0, 0]), 0.0);
+        let price = Market::default().market_price(&[100, 100]);
+        assert_eq!(price, 20000.0 - 100.0 - 52.0);
+    }
+
+    #[test]
+    fn welfare_of_a_duopoly()
+    {
+        let market = Market {intercept:100.0, own_slope:1.0, cross_slope:0.5};
+        let welfare = market.welfare(&[10, 20], Some(&[1.0, 2.0]));
+        // utility 100*30 - (100 + 400)/2 - 0.5*200 = 2650, spending 10*80 + 20*75 = 2300
+        assert_eq!(welfare.consumer_surplus, 350.0);
+        assert_eq!(welfare.producer_surplus, 2300.0 - 10.0 - 40.0);
+        assert_eq!(welfare.total, welfare.consumer_surplus + welfare.producer_surplus);
+    }
+
+    #[test]
+    fn decoding_scales_and_gray_codes()
+    {
+        let decoding = Decoding {scale:10, offset:100, gray:false};
+        assert_eq!(decoding.decode(5), 150);
+        assert_eq!(decoding.encode(154, 8), 5);
+        assert_eq!(decoding.encode(100_000, 8), 255);
+
+        let gray = Decoding::gray();
+        for x in 0..256u64 {
+            assert_eq!(gray.decode(gray.encode(x, 8)), x);
+            assert_eq!((gray.encode(x, 8) ^ gray.encode(x + 1, 9)).count_ones(), 1);
+        }
+    }
+
+    #[test]
+    fn decoded_quantities_drive_the_market()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 6, 30, 2).unwrap();
+        test_run.set_decoding(Decoding {scale:20, offset:0, gray:true});
+        test_run.run(5).unwrap();
+        assert!(test_run.history().iter().all(|s| s.ind_out.is_multiple_of(20)));
+        assert!(test_run.firm_stats().iter().all(|f| f.quantity.is_multiple_of(20)));
+    }
+
+    #[test]
+    fn symmetric_equilibria()
+    {
+        let market = Market::default();
+        let nash = market.nash(&[0.0; 30]);
+        let q = 20000.0 / (2.0 + 0.52*29.0);
+        assert!(nash.quantities.iter().all(|x| (x - q).abs() < 1e-6));
+        assert!((nash.output - 30.0*q).abs() < 1e-6);
+        assert!((nash.price - q).abs() < 1e-6);
+
+        let competitive = market.competitive(&[100.0; 30]);
+        assert!((competitive.price - 100.0).abs() < 1e-6);
+        assert!(competitive.output > nash.output);
+    }
+
+    #[test]
+    fn price_is_tracked_per_generation()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2).unwrap();
+        test_run.set_market(Market {intercept:30000.0, ..Market::default()});
+        test_run.run(5).unwrap();
+        assert!(test_run.history().iter().all(|s| s.price > 0.0 && s.price < 30000.0));
+    }
+
+    #[test]
+    fn shares_sum_to_one()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2).unwrap();
+        test_run.draw_firm_costs(0.0, 100.0, &mut rand::thread_rng());
+        test_run.run(20).unwrap();
+        let firms = test_run.firm_stats();
+        assert_eq!(firms.len(), 30);
+        let total:f64 = firms.iter().map(|f| f.market_share).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(firms.iter().all(|f| f.profit >= 0.0 && (0.0..=100.0).contains(&f.cost)));
+    }
+
+    #[test]
+    fn costs_lower_fitness()
+    {
+        let mut cheap = Run::new(0.322, 0.00522, 10, 4, 2).unwrap();
+        let mut dear = cheap.clone();
+        cheap.set_firm_costs(vec![0.0; 4]);
+        dear.set_firm_costs(vec![1000.0; 4]);
+        let cheap_profit:f64 = cheap.firm_stats().iter().map(|f| f.profit).sum();
+        let dear_profit:f64 = dear.firm_stats().iter().map(|f| f.profit).sum();
+        assert!(dear_profit < cheap_profit);
+    }
+}
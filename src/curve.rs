@@ -0,0 +1,96 @@
+//! Least-squares curve fitting: the parameters of a user model `y = model(x, params)` are
+//! evolved as a real genome, minimizing the root-mean-square error over the data.
+use crate::objective::Objective;
+use crate::real::RealRun;
+use std::fmt;
+use std::sync::Arc;
+
+pub type Model = Arc<dyn Fn(f64, &[f64]) -> f64 + Send + Sync>;
+
+#[derive(Clone)]
+pub struct CurveFit
+{
+    pub points:Vec<(f64, f64)>,
+    model:Model,
+}
+
+impl fmt::Debug for CurveFit
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("CurveFit").field("points", &self.points).finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitResult
+{
+    pub params:Vec<f64>,
+    pub rmse:f64,
+    /// RMSE of the best parameters in every generation.
+    pub rmse_history:Vec<f64>,
+}
+
+impl CurveFit
+{
+    pub fn new<M>(points:Vec<(f64, f64)>, model:M) -> Self
+        where M:Fn(f64, &[f64]) -> f64 + Send + Sync + 'static
+    {
+        assert!(!points.is_empty(), "need at least one data point");
+        CurveFit {points, model:Arc::new(model)}
+    }
+
+    /// Sum of squared residuals, NaN model outputs count as infinitely bad.
+    pub fn sse(&self, params:&[f64]) -> f64
+    {
+        let sse:f64 = self.points.iter().map(|&(x, y)| ((self.model)(x, params) - y).powi(2)).sum();
+        if sse.is_nan() { f64::INFINITY } else { sse }
+    }
+
+    pub fn rmse(&self, params:&[f64]) -> f64
+    {
+        (self.sse(params) / self.points.len() as f64).sqrt()
+    }
+
+    /// A run minimizing the RMSE with one gene per parameter within `bounds`, the best fitness
+    /// in its history is the RMSE per generation.
+    pub fn build_run(&self, bounds:Vec<(f64, f64)>, n:usize) -> RealRun
+    {
+        let fit = self.clone();
+        let mut run = RealRun::new(bounds, n, move |params:&[f64]| fit.rmse(params));
+        run.set_objective(Objective::Minimize);
+        run
+    }
+
+    pub fn fit(&self, bounds:Vec<(f64, f64)>, n:usize, generations:u32) -> FitResult
+    {
+        let mut run = self.build_run(bounds, n);
+        run.run(generations);
+        let best = run.best().expect("a run of at least one generation");
+        FitResult {params:best.genes.clone(), rmse:best.fitness, rmse_history:run.history().iter().map(|s| s.best_fitness).collect()}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_a_line()
+    {
+        let points:Vec<(f64, f64)> = (0..20).map(|i| (i as f64, 3.0 * i as f64 - 2.0)).collect();
+        let fit = CurveFit::new(points, |x, p| p[0] * x + p[1]);
+        assert_eq!(fit.rmse(&[3.0, -2.0]), 0.0);
+        let result = fit.fit(vec![(-10.0, 10.0); 2], 40, 150);
+        assert!(result.rmse < 0.5, "{:?}", result);
+        assert_eq!(result.rmse_history.len(), 150);
+        assert_eq!(*result.rmse_history.last().unwrap(), result.rmse);
+    }
+
+    #[test]
+    fn nan_outputs_are_worst()
+    {
+        let fit = CurveFit::new(vec![(1.0, 1.0)], |x, p| (x * p[0]).ln());
+        assert_eq!(fit.rmse(&[-1.0]), f64::INFINITY);
+    }
+}
@@ -0,0 +1,142 @@
+//! Entry and exit of firms in the market model. After a generation is evaluated, firms whose
+//! profit fell below `exit_profit` leave, and if the survivors earn more than `entry_profit` on
+//! average new firms with random quantities join, so the population size changes over the run.
+//! Fitness is read as profit, so the rules only apply to maximizing runs.
+use crate::{Chromosome, Objective, Run};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntryExit
+{
+    pub exit_profit:f64,
+    pub entry_profit:f64,
+    pub max_entrants:usize,
+    pub min_firms:usize,
+    pub max_firms:usize,
+    /// Marginal cost of an entrant, used when the run has firm costs.
+    pub entrant_cost:f64,
+}
+
+impl Default for EntryExit
+{
+    fn default() -> Self
+    {
+        EntryExit {exit_profit:0.0, entry_profit:f64::INFINITY, max_entrants:2, min_firms:2, max_firms:usize::MAX, entrant_cost:0.0}
+    }
+}
+
+impl Run
+{
+    /// The population must stay even for pairing, so the entrant count is adjusted by one
+    /// (or the best exiting firm stays) when the rules would leave an odd number of firms.
+    /// Panics unless the run maximizes, the thresholds being profits.
+    pub fn set_entry_exit(&mut self, rules:EntryExit)
+    {
+        assert!(rules.min_firms >= 2 && rules.min_firms <= rules.max_firms, "need 2 <= min_firms <= max_firms");
+        assert!(self.objective == Objective::Maximize, "entry and exit need a maximizing run");
+        self.entry_exit = Some(rules);
+    }
+
+    // returns the number of entrants and exits
+    pub(crate) fn apply_entry_exit(&mut self) -> (usize, usize)
+    {
+        let Some(rules) = self.entry_exit else { return (0, 0) };
+        let fitness = &self.population.fitness;
+        let mut ranked:Vec<usize> = (0..fitness.len()).collect();
+        ranked.sort_by(|&a, &b| self.objective.best_first(fitness[a], fitness[b]));
+        let staying = ranked.iter().filter(|&&i| fitness[i] >= rules.exit_profit).count().max(rules.min_firms);
+        let (survivors, exiting) = ranked.split_at(staying.min(ranked.len()));
+        let mut survivors = survivors.to_vec();
+
+        let mean = survivors.iter().map(|&i| fitness[i]).sum::<f64>() / survivors.len() as f64;
+        let room = rules.max_firms.saturating_sub(survivors.len());
+        let mut entrants = if mean > rules.entry_profit { rules.max_entrants.min(room) } else { 0 };
+        if !(survivors.len() + entrants).is_multiple_of(2) {
+            if entrants < room && entrants > 0 {
+                entrants += 1;
+            }else if entrants > 0 {
+                entrants -= 1;
+            }else if let Some(&best_exiting) = exiting.first() {
+                survivors.push(best_exiting);
+            }else{
+                survivors.pop();
+            }
+        }
+        let exits = self.population.len() - survivors.len();
+        if exits == 0 && entrants == 0 {
+            return (0, 0);
+        }
+
+        survivors.sort();
+        let mut population = self.population.gather(&survivors);
+        let mut costs = self.firm_costs.as_ref().map(|costs| survivors.iter().map(|&i| costs[i]).collect::<Vec<f64>>());
+        for _ in 0..entrants {
+            let mut entrant = Chromosome::random_with(self.L, &mut self.rng);
+            entrant.N = population.N;
+            population.push(&entrant);
+            if let Some(costs) = &mut costs {
+                costs.push(rules.entrant_cost);
+            }
+        }
+        self.population = population;
+        self.firm_costs = costs;
+        self.n = self.population.len();
+
+        // entrants are evaluated in the market they join, everybody else keeps this generation's fitness
+        self.calculate_data_sum();
+        for index in self.n - entrants..self.n {
+            let mut ind = self.population.get(index);
+            self.evaluate(&mut ind, self.data_sum, self.firm_cost(index));
+            self.population.fitness[index] = ind.fitness;
+            self.population.violation[index] = ind.violation;
+        }
+        self.total_fitness = self.selection_weights().iter().sum();
+        (entrants, exits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unprofitable_firms_exit()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2).unwrap();
+        test_run.set_firm_costs(vec![0.0; 30]);
+        test_run.set_entry_exit(EntryExit {exit_profit:f64::INFINITY, min_firms:10, ..EntryExit::default()});
+        test_run.run(3).unwrap();
+        assert_eq!(test_run.population.len(), 10);
+        assert_eq!(test_run.firm_costs().unwrap().len(), 10);
+        assert_eq!(test_run.history()[0].exits, 20);
+        assert_eq!(test_run.history()[0].firms, 10);
+    }
+
+    #[test]
+    fn profitable_markets_attract_entrants()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 10, 2).unwrap();
+        test_run.set_entry_exit(EntryExit {entry_profit:0.0, max_entrants:3, max_firms:20, ..EntryExit::default()});
+        test_run.run(10).unwrap();
+        let firms:Vec<usize> = test_run.history().iter().map(|s| s.firms).collect();
+        assert!(firms.iter().all(|f| f.is_multiple_of(2) && *f <= 20));
+        assert_eq!(test_run.population.len(), 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "maximizing run")]
+    fn minimizing_runs_are_rejected()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 10, 2).unwrap();
+        test_run.set_objective(Objective::Minimize);
+        test_run.set_entry_exit(EntryExit::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "maximizing run")]
+    fn switching_to_minimize_is_rejected()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 10, 2).unwrap();
+        test_run.set_entry_exit(EntryExit::default());
+        test_run.set_objective(Objective::Minimize);
+    }
+}
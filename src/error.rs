@@ -0,0 +1,72 @@
+use crate::config::ConfigError;
+use crate::guard::NonFiniteFitness;
+use thiserror::Error;
+
+/// Everything that can go wrong building or running a `Run`.
+#[derive(Debug, Error)]
+pub enum GaError
+{
+    /// Every problem found in the parameters, see `RunConfig::validate`.
+    #[error("invalid parameters: {}", .0.iter().map(|e| e.to_string()).collect::<Vec<String>>().join("; "))]
+    Config(Vec<ConfigError>),
+    #[error(transparent)]
+    NonFiniteFitness(#[from] NonFiniteFitness),
+    /// The fitness function panicked under `PanicPolicy::Abort`.
+    #[error("generation {generation}: fitness of individual {index} panicked: {message}")]
+    FitnessPanic{generation:u32, index:usize, message:String},
+    /// Selection had nothing to work with, only raised if the run was asked to, see
+    /// `Run::set_zero_fitness_error`.
+    #[error("generation {generation}: total fitness is zero")]
+    ZeroFitness{generation:u32},
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A value in a saved stats file that doesn't fit its column.
+    #[error("line {line}: invalid {column} '{value}'")]
+    Parse{line:u64, column:String, value:String},
+    #[cfg(feature = "parquet")]
+    #[error("parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[cfg(feature = "plotting")]
+    #[error("plotting error: {0}")]
+    Plot(String),
+}
+
+impl From<Vec<ConfigError>> for GaError
+{
+    fn from(errors:Vec<ConfigError>) -> Self
+    {
+        GaError::Config(errors)
+    }
+}
+
+impl From<csv::Error> for GaError
+{
+    fn from(e:csv::Error) -> Self
+    {
+        GaError::Io(e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn errors_explain_themselves()
+    {
+        let error = GaError::from(vec![ConfigError::GenomeLength{L:0}, ConfigError::NoIterations]);
+        assert_eq!(error.to_string(), "invalid parameters: genome length L must be in 1..=64, got 0; iterations must be at least 1");
+        let error = GaError::from(std::io::Error::other("disk full"));
+        assert!(error.source().is_some());
+        assert_eq!(error.to_string(), "i/o error: disk full");
+
+        let missing = std::env::temp_dir().join("no_such_dir").join("stats.csv");
+        assert!(matches!(crate::save_stats_to_csv(&[], missing.to_str().unwrap()), Err(GaError::Io(_))));
+    }
+}
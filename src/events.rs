@@ -0,0 +1,218 @@
+use crate::{PhaseTimings, Run, StopReason};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary
+{
+    pub generations:u32,
+    /// Fitness evaluations made by this call of `run`, the run's total being in `RunResult`.
+    pub evaluations:u64,
+    pub best_data:Option<u64>,
+    pub best_fitness:Option<f64>,
+    pub elapsed:Duration,
+    /// Time per phase summed over the generations of this run.
+    pub timings:PhaseTimings,
+    pub stop_reason:StopReason,
+}
+
+/// Milestones of a run, for orchestration code that needs to react to them. They go to the
+/// event callback and to every observer's `on_event`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LifecycleEvent
+{
+    RunStarted{config_hash:u64, generation:u32},
+    /// `Run::checkpoint` or `CheckpointLog::save` wrote `path`.
+    CheckpointWritten{generation:u32, path:PathBuf},
+    /// Island `from` of an `IslandRun` sent `migrants` individuals to island `to`, emitted by
+    /// the receiving island.
+    Migration{generation:u32, from:usize, to:usize, migrants:usize},
+    /// The run was restored from a checkpoint and carries on from `generation`.
+    Restarted{config_hash:u64, generation:u32},
+    RunFinished{config_hash:u64, summary:RunSummary},
+}
+
+// FNV-1a, stable across platforms and compiler versions unlike DefaultHasher
+pub(crate) fn fnv1a(bytes:&[u8]) -> u64
+{
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+impl Run
+{
+    pub fn set_event_callback(&mut self, callback:fn(&LifecycleEvent))
+    {
+        self.event_callback = Some(callback);
+    }
+
+    pub(crate) fn emit(&self, event:LifecycleEvent)
+    {
+        if let Some(callback) = self.event_callback {
+            callback(&event);
+        }
+        self.observers.iter().for_each(|o| o.on_event(&event));
+    }
+
+    /// Hash of the GA parameters, identical on every machine and unchanged while the run
+    /// goes on. Fitness and constraint functions are not part of it since their addresses
+    /// differ between builds; custom operators are, by their `Debug` output.
+    pub fn config_hash(&self) -> u64
+    {
+        let description = format!("{:?}|{:?}|{}|{}|{}|{:?}|{:?}|{:?}|{}|{:?}|{:?}|{:?}|{}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}",
+            self.Pcross, self.Pmut, self.L, self.n, self.z, self.objective, self.constraint_handling,
+            self.initial_penalty, self.constraints.len(), self.non_finite_policy, self.panic_policy,
+            self.evaluation_timeout, self.elitism, self.mating_pool, self.mutation_scheme, self.steady_state,
+            self.epsilon, self.decoding, self.seed, self.selection, self.crossover, self.mutation);
+        fnv1a(description.as_bytes())
+    }
+
+    pub(crate) fn summary(&self, elapsed:Duration, evaluations:u64, timings:PhaseTimings, stop_reason:StopReason) -> RunSummary
+    {
+        RunSummary {
+            generations:self.period,
+            evaluations,
+            best_data:self.best.as_ref().map(|b| b.data),
+            best_fitness:self.best.as_ref().map(|b| b.fitness),
+            elapsed,
+            timings,
+            stop_reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::EvaluationBudget;
+    use crate::constraints::AdaptivePenalty;
+    use crate::island::{IslandRun, Topology};
+    use crate::observer::Observer;
+    use crate::operators::Tournament;
+    use crate::{problems, Chromosome};
+    use std::sync::{Arc, Mutex};
+
+    static EVENTS:Mutex<Vec<LifecycleEvent>> = Mutex::new(Vec::new());
+
+    fn record(event:&LifecycleEvent)
+    {
+        EVENTS.lock().unwrap().push(event.clone());
+    }
+
+    #[test]
+    fn start_and_finish_are_emitted()
+    {
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        let hash = test_run.config_hash();
+        test_run.set_event_callback(record);
+        test_run.run(5).unwrap();
+
+        let events = EVENTS.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], LifecycleEvent::RunStarted{config_hash:hash, generation:0});
+        match &events[1] {
+            LifecycleEvent::RunFinished{config_hash, summary} => {
+                assert_eq!(*config_hash, hash);
+                assert_eq!(summary.generations, 5);
+                assert_eq!(summary.evaluations, 150);
+                assert!(summary.best_fitness.is_some());
+                assert!(summary.timings.total() <= summary.elapsed);
+                assert_eq!(summary.stop_reason, StopReason::Iterations);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Collector(Arc<Mutex<Vec<LifecycleEvent>>>);
+
+    impl Observer for Collector
+    {
+        fn on_event(&self, event:&LifecycleEvent)
+        {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn observers_see_checkpoints_restarts_and_migrations()
+    {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut test_run = Run::with_seed(0.322, 0.01, 10, 30, 2, 4).unwrap();
+        test_run.add_observer(Box::new(Collector(events.clone())));
+        test_run.run(3).unwrap();
+        let path = std::env::temp_dir().join(format!("events_{}.json", std::process::id()));
+        test_run.checkpoint(&path).unwrap();
+        test_run.resume(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let hash = test_run.config_hash();
+        {
+            let events = events.lock().unwrap();
+            assert_eq!(events.len(), 4);
+            assert!(matches!(events[1], LifecycleEvent::RunFinished{..}));
+            assert_eq!(events[2], LifecycleEvent::CheckpointWritten{generation:3, path});
+            assert_eq!(events[3], LifecycleEvent::Restarted{config_hash:hash, generation:3});
+        }
+
+        let received:Vec<_> = (0..2).map(|_| Arc::new(Mutex::new(Vec::new()))).collect();
+        let islands = received.iter().zip([5, 6]).map(|(received, seed)| {
+            let mut island = Run::with_seed(0.322, 0.01, 10, 30, 2, seed).unwrap();
+            island.add_observer(Box::new(Collector(received.clone())));
+            island
+        }).collect();
+        let mut model = IslandRun::new(islands, Topology::Ring, 2, 3);
+        model.run(2);
+        assert_eq!(received[0].lock().unwrap().as_slice(), [LifecycleEvent::Migration{generation:2, from:1, to:0, migrants:3}]);
+        assert_eq!(received[1].lock().unwrap().as_slice(), [LifecycleEvent::Migration{generation:2, from:0, to:1, migrants:3}]);
+    }
+
+    #[test]
+    fn summary_counts_actual_evaluations()
+    {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut test_run = Run::with_seed(0.322, 0.01, 10, 30, 2, 1).unwrap();
+        test_run.add_observer(Box::new(Collector(events.clone())));
+        test_run.run(2).unwrap();
+        test_run.set_evaluation_budget(EvaluationBudget::new(40));
+        let result = test_run.run(10).unwrap();
+        assert_eq!(result.stop_reason, StopReason::BudgetExhausted);
+        let events = events.lock().unwrap();
+        let summaries:Vec<&RunSummary> = events.iter().filter_map(|event| match event {
+            LifecycleEvent::RunFinished{summary, ..} => Some(summary),
+            _ => None,
+        }).collect();
+        assert_eq!(summaries.iter().map(|s| s.evaluations).collect::<Vec<_>>(), [60, 40]);
+        assert_eq!(result.evaluations, 100);
+    }
+
+    #[test]
+    fn hash_follows_parameters()
+    {
+        let a = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        let b = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        let c = Run::new(0.322, 0.02, 10, 30, 2).unwrap();
+        assert_eq!(a.config_hash(), b.config_hash());
+        assert_ne!(a.config_hash(), c.config_hash());
+
+        let mut d = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        d.set_elitism(2);
+        assert_ne!(a.config_hash(), d.config_hash());
+        assert_ne!(a.config_hash(), Run::with_seed(0.322, 0.01, 10, 30, 2, 1).unwrap().config_hash());
+        let mut e = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        e.set_selection(Box::new(Tournament {size:3}));
+        assert_ne!(a.config_hash(), e.config_hash());
+    }
+
+    #[test]
+    fn hash_ignores_the_adaptive_penalty()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 12, 20, 4, 2).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.add_constraint(|ind:&Chromosome| ind.data.count_ones() as f64 - 4.0);
+        test_run.set_penalty(0.5);
+        test_run.set_adaptive_penalty(AdaptivePenalty::new(1, 2.0, 2.0));
+        let hash = test_run.config_hash();
+        test_run.run(10).unwrap();
+        assert_ne!(test_run.penalty(), 0.5);
+        assert_eq!(test_run.config_hash(), hash);
+    }
+}
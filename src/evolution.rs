@@ -0,0 +1,306 @@
+//! A generational GA over any genome type. `GenomeRun` evolves genomes that bring their own
+//! crossover and mutation operators, such as `RealChromosome` or `PermutationChromosome`, and
+//! has the parts of `Run` that do not depend on the genome: objectives, constraints with a
+//! static penalty, elitism, pluggable `Selection`, observers, evaluation budgets, seeding and
+//! checkpoints. Without a custom selection each parent is the better of two uniformly drawn
+//! individuals.
+//!
+//! It is a second engine rather than a generic `Run`, which stays the bit-string engine of the
+//! market model. So these `Run` features have no `GenomeRun` counterpart:
+//!
+//! - steady-state reproduction, mating groups, mating pools and guided crossover;
+//! - islands and migration;
+//! - the market: shocks, firm costs, learning, entry and exit;
+//! - adaptive penalties, stochastic ranking and prefilters;
+//! - termination criteria other than the budget and observers, and the epsilon for improvement;
+//! - fitness panic, timeout and non-finite policies;
+//! - hall of fame, archive, leaderboard, parent log and health warnings;
+//! - `GenerationStats` with its exports (CSV, JSON lines, Parquet, SQLite), history retention
+//!   and the low-memory mode; a `GenomeRun` keeps `GenomeStats` in memory;
+//! - checkpoint deltas and lifecycle events;
+//! - scripted and plugin fitness and operators.
+//!
+//! A feature added to one engine is not added to the other unless listed here afterwards.
+use crate::budget::EvaluationBudget;
+use crate::error::GaError;
+use crate::objective::Objective;
+use crate::observer::GenomeObserver;
+use crate::operators::Selection;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug};
+use std::fs::File;
+use std::sync::Arc;
+
+/// A genome `GenomeRun` can evolve. `Context` holds problem-wide data the operators need, such
+/// as gene bounds, and the operator types choose between the genome's variation operators.
+pub trait Genome: Clone + Debug + Send + Sync
+{
+    type Context: Clone + Debug + Send + Sync;
+    type Crossover: Copy + Debug + Default + Send + Sync;
+    type Mutation: Clone + Debug + Default + Send + Sync;
+
+    fn fitness(&self) -> f64;
+    fn set_fitness(&mut self, fitness:f64);
+    /// One child of `a` and `b`.
+    fn crossover(a:&Self, b:&Self, operator:Self::Crossover, context:&Self::Context, rng:&mut dyn RngCore) -> Self;
+    /// `rate` is the run's `Pmut`, each genome type documents how it reads it.
+    fn mutate(&mut self, operator:&Self::Mutation, rate:f64, context:&Self::Context, rng:&mut dyn RngCore);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GenomeStats
+{
+    pub generation:u32,
+    pub best_fitness:f64,
+    pub mean_fitness:f64,
+    /// Fitness evaluations so far, this generation included.
+    pub evaluations:usize,
+    /// Share of the generation satisfying every constraint, 1 for unconstrained engines.
+    #[serde(default = "all_feasible")]
+    pub feasible_fraction:f64,
+}
+
+fn all_feasible() -> f64
+{
+    1.0
+}
+
+/// Writes `history` with a `generation,best_fitness,mean_fitness,evaluations,feasible_fraction`
+/// header.
+pub fn save_genome_stats_to_csv(history:&[GenomeStats], file_name:&str) -> Result<(), GaError>
+{
+    let file = File::create(file_name)?;
+    let mut writer = csv::Writer::from_writer(file);
+
+    writer.write_record(["generation", "best_fitness", "mean_fitness", "evaluations", "feasible_fraction"])?;
+
+    for stats in history {
+        writer.write_record(&[stats.generation.to_string(), stats.best_fitness.to_string(),
+            stats.mean_fitness.to_string(), stats.evaluations.to_string(), stats.feasible_fraction.to_string()])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// A constraint on a genome, its violation as a non-negative amount, zero when satisfied.
+pub type GenomeConstraint<G> = Arc<dyn Fn(&G) -> f64 + Send + Sync>;
+
+#[derive(Clone)]
+pub struct GenomeRun<G:Genome>
+{
+    pub Pcross:f32,
+    pub Pmut:f32,
+    pub crossover:G::Crossover,
+    pub mutation:G::Mutation,
+    pub context:G::Context,
+    pub population:Vec<G>,
+    fitness:Arc<dyn Fn(&G) -> f64 + Send + Sync>,
+    objective:Objective,
+    constraints:Vec<GenomeConstraint<G>>,
+    penalty:f64,
+    elitism:usize,
+    selection:Option<Arc<dyn Selection>>,
+    observers:Vec<Arc<dyn GenomeObserver<G>>>,
+    budget:Option<EvaluationBudget>,
+    pub(crate) period:u32,
+    pub(crate) evaluations:usize,
+    pub(crate) history:Vec<GenomeStats>,
+    pub(crate) best:Option<G>,
+    pub(crate) rng:ChaCha12Rng,
+}
+
+impl<G:Genome> fmt::Debug for GenomeRun<G>
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("GenomeRun").field("Pcross", &self.Pcross).field("Pmut", &self.Pmut)
+            .field("crossover", &self.crossover).field("mutation", &self.mutation).field("context", &self.context)
+            .field("population", &self.population.len()).field("objective", &self.objective)
+            .field("constraints", &self.constraints.len()).field("penalty", &self.penalty).field("elitism", &self.elitism)
+            .field("selection", &self.selection).field("period", &self.period).field("best", &self.best).finish()
+    }
+}
+
+impl<G:Genome> GenomeRun<G>
+{
+    /// Maximizes `fitness` over `population` with the default operators, crossover probability
+    /// 0.9 and `Pmut` 0.1, keeping one elite. The RNG is seeded from the operating system's
+    /// entropy, see `set_seed`.
+    pub fn from_population<F>(population:Vec<G>, context:G::Context, fitness:F) -> Self
+        where F:Fn(&G) -> f64 + Send + Sync + 'static
+    {
+        assert!(population.len() >= 2, "population needs at least two individuals");
+        GenomeRun {Pcross:0.9, Pmut:0.1, crossover:G::Crossover::default(), mutation:G::Mutation::default(), context,
+            population, fitness:Arc::new(fitness), objective:Objective::Maximize, constraints:Vec::new(), penalty:1.0,
+            elitism:1, selection:None, observers:Vec::new(), budget:None, period:0, evaluations:0,
+            history:Vec::new(), best:None, rng:ChaCha12Rng::from_entropy()}
+    }
+
+    /// Seeds every selection, crossover and mutation draw from here on, so two runs with the
+    /// same population, settings and seed produce the same history.
+    pub fn set_seed(&mut self, seed:u64)
+    {
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+
+    pub fn set_objective(&mut self, objective:Objective)
+    {
+        self.objective = objective;
+    }
+
+    pub fn objective(&self) -> Objective
+    {
+        self.objective
+    }
+
+    /// Violated constraints make the fitness worse by `penalty` times the total violation.
+    pub fn add_constraint<F>(&mut self, constraint:F)
+        where F:Fn(&G) -> f64 + Send + Sync + 'static
+    {
+        self.constraints.push(Arc::new(constraint));
+    }
+
+    pub fn set_penalty(&mut self, penalty:f64)
+    {
+        self.penalty = penalty;
+    }
+
+    /// The `k` best individuals are copied unchanged into the next generation.
+    pub fn set_elitism(&mut self, k:usize)
+    {
+        self.elitism = k;
+    }
+
+    /// Picks parents from the weights of `Objective::selection_weights` instead of binary
+    /// tournaments.
+    pub fn set_selection(&mut self, selection:Box<dyn Selection>)
+    {
+        self.selection = Some(Arc::from(selection));
+    }
+
+    /// Observers are called in the order they were added.
+    pub fn add_observer(&mut self, observer:Box<dyn GenomeObserver<G>>)
+    {
+        self.observers.push(Arc::from(observer));
+    }
+
+    /// Draws every fitness evaluation from `budget`. Once it is spent the individuals still
+    /// waiting get the worst possible fitness and `run` returns after that generation.
+    pub fn set_evaluation_budget(&mut self, budget:EvaluationBudget)
+    {
+        self.budget = Some(budget);
+    }
+
+    pub fn evaluation_budget(&self) -> Option<&EvaluationBudget>
+    {
+        self.budget.as_ref()
+    }
+
+    pub fn best(&self) -> Option<&G>
+    {
+        self.best.as_ref()
+    }
+
+    pub fn history(&self) -> &[GenomeStats]
+    {
+        &self.history
+    }
+
+    pub fn generation(&self) -> u32
+    {
+        self.period
+    }
+
+    pub fn evaluations(&self) -> usize
+    {
+        self.evaluations
+    }
+
+    /// Runs `iterations` more generations, fewer if the evaluation budget runs out or an
+    /// observer asks to stop.
+    pub fn run(&mut self, iterations:u32)
+    {
+        for _ in 0..iterations {
+            if self.budget.as_ref().is_some_and(EvaluationBudget::is_exhausted) || self.step() {
+                break;
+            }
+        }
+    }
+
+    // one generation, true if an observer asked to stop
+    fn step(&mut self) -> bool
+    {
+        self.period += 1;
+        self.observers.iter().for_each(|o| o.on_generation_start(self.period));
+        let objective = self.objective;
+        let mut feasible = 0;
+        for ind in self.population.iter_mut() {
+            if !self.budget.as_ref().is_none_or(EvaluationBudget::try_take) {
+                ind.set_fitness(objective.worst_value());
+                continue;
+            }
+            self.evaluations += 1;
+            let violation:f64 = self.constraints.iter().map(|constraint| constraint(ind).max(0.0)).sum();
+            let fitness = (self.fitness)(ind);
+            ind.set_fitness(match objective {
+                _ if violation <= 0.0 => fitness,
+                Objective::Maximize => fitness - self.penalty*violation,
+                Objective::Minimize => fitness + self.penalty*violation,
+            });
+            if violation <= 0.0 {
+                feasible += 1;
+            }
+        }
+
+        let mut order:Vec<usize> = (0..self.population.len()).collect();
+        order.sort_by(|&a, &b| objective.best_first(self.population[a].fitness(), self.population[b].fitness()));
+        let elite = &self.population[order[0]];
+        if self.best.as_ref().is_none_or(|best| objective.is_better(elite.fitness(), best.fitness())) {
+            self.best = Some(elite.clone());
+        }
+        let n = self.population.len();
+        let mean_fitness = self.population.iter().map(|ind| ind.fitness()).sum::<f64>() / n as f64;
+        let stats = GenomeStats {generation:self.period, best_fitness:elite.fitness(), mean_fitness,
+            evaluations:self.evaluations, feasible_fraction:feasible as f64 / n as f64};
+        self.history.push(stats);
+        self.observers.iter().for_each(|o| o.on_evaluated(self.period, &self.population));
+
+        let mut next:Vec<G> = order.iter().take(self.elitism.min(n)).map(|&slot| self.population[slot].clone()).collect();
+        let parents = self.select_parents(2*(n - next.len()));
+        for pair in parents.chunks(2) {
+            let (a, b) = (&self.population[pair[0]], &self.population[pair[1]]);
+            let mut child = if self.rng.gen::<f32>() < self.Pcross {
+                G::crossover(a, b, self.crossover, &self.context, &mut self.rng)
+            }else{
+                a.clone()
+            };
+            child.mutate(&self.mutation, self.Pmut as f64, &self.context, &mut self.rng);
+            next.push(child);
+        }
+        self.population = next;
+        // every observer sees the stats even when an earlier one already asked to stop
+        self.observers.iter().fold(false, |stop, o| o.on_generation_end(&stats) | stop)
+    }
+
+    fn select_parents(&mut self, count:usize) -> Vec<usize>
+    {
+        match &self.selection {
+            Some(selection) => {
+                let fitness:Vec<f64> = self.population.iter().map(|ind| ind.fitness()).collect();
+                selection.select(&self.objective.selection_weights(&fitness), count, &mut self.rng)
+            }
+            None => (0..count).map(|_| self.tournament()).collect(),
+        }
+    }
+
+    // index of the better of two uniformly drawn individuals
+    fn tournament(&mut self) -> usize
+    {
+        let a = self.rng.gen_range(0..self.population.len());
+        let b = self.rng.gen_range(0..self.population.len());
+        if self.objective.is_better(self.population[b].fitness(), self.population[a].fitness()) { b } else { a }
+    }
+}
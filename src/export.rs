@@ -0,0 +1,208 @@
+//! Complete run records as JSON for downstream tooling. `save_stats_to_csv` flattens the stats
+//! into columns; here every field of `GenerationStats` is kept, next to the parameters and seed
+//! of the run that produced it and why it stopped. `save_run_to_json` writes one document per
+//! run, `JsonlWriter` streams one self-contained line per generation for `jq` or
+//! `pandas.read_json(lines=True)` and ends with a line holding the stop reason.
+use crate::cournot::Decoding;
+use crate::error::GaError;
+use crate::objective::Objective;
+use crate::result::{RunResult, StopReason};
+use crate::stats::GenerationStats;
+use crate::Run;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+
+/// The parameters of a run that its stats depend on. Fitness and operators are functions and
+/// trait objects, so they are only covered by `config_hash` as far as it goes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunManifest
+{
+    pub Pcross:f32,
+    pub Pmut:f32,
+    pub L:u8,
+    pub n:usize,
+    pub z:u8,
+    pub objective:Objective,
+    pub elitism:usize,
+    /// How genomes map to the phenotypes the fitness sees, `None` if they are the same.
+    pub decoding:Option<Decoding>,
+    /// `None` for runs seeded from entropy, which can't be repeated.
+    pub seed:Option<u64>,
+    pub config_hash:u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord
+{
+    pub config:RunManifest,
+    /// Why the run returned, `None` in records written before it was kept.
+    #[serde(default)]
+    pub stop_reason:Option<StopReason>,
+    pub stats:Vec<GenerationStats>,
+}
+
+// one line of a JSONL file
+#[derive(Serialize)]
+struct JsonlLine<'a>
+{
+    #[serde(flatten)]
+    config:&'a RunManifest,
+    #[serde(flatten)]
+    stats:&'a GenerationStats,
+}
+
+// the last line of a JSONL file
+#[derive(Serialize)]
+struct JsonlEnd<'a>
+{
+    #[serde(flatten)]
+    config:&'a RunManifest,
+    stop_reason:&'a StopReason,
+    evaluations:usize,
+}
+
+impl Run
+{
+    pub fn manifest(&self) -> RunManifest
+    {
+        RunManifest {
+            Pcross:self.Pcross, Pmut:self.Pmut, L:self.L, n:self.n, z:self.z,
+            objective:self.objective,
+            elitism:self.elitism,
+            decoding:self.decoding,
+            seed:self.seed,
+            config_hash:self.config_hash(),
+        }
+    }
+}
+
+/// Writes the manifest of `run`, the stop reason of `result` and the run's in-memory `history`
+/// as one JSON document.
+pub fn save_run_to_json(run:&Run, result:&RunResult, file_name:&str) -> Result<(), GaError>
+{
+    let record = RunRecord {config:run.manifest(), stop_reason:Some(result.stop_reason.clone()), stats:run.history().to_vec()};
+    let mut writer = BufWriter::new(File::create(file_name)?);
+    serde_json::to_writer_pretty(&mut writer, &record)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Appends one JSON object per generation, the manifest fields followed by the stats fields.
+pub struct JsonlWriter
+{
+    config:RunManifest,
+    writer:BufWriter<File>,
+}
+
+impl JsonlWriter
+{
+    /// Opens `file_name` for appending, so several runs can share one file.
+    pub fn append(file_name:&str, config:RunManifest) -> Result<Self, GaError>
+    {
+        let file = OpenOptions::new().create(true).append(true).open(file_name)?;
+        Ok(JsonlWriter {config, writer:BufWriter::new(file)})
+    }
+
+    pub fn write(&mut self, stats:&GenerationStats) -> Result<(), GaError>
+    {
+        serde_json::to_writer(&mut self.writer, &JsonlLine {config:&self.config, stats})?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), GaError>
+    {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Appends the line that closes the run, the manifest fields with the stop reason and
+    /// evaluation count of `result`, and flushes.
+    pub fn finish(&mut self, result:&RunResult) -> Result<(), GaError>
+    {
+        let end = JsonlEnd {config:&self.config, stop_reason:&result.stop_reason, evaluations:result.evaluations};
+        serde_json::to_writer(&mut self.writer, &end)?;
+        self.writer.write_all(b"\n")?;
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+    use std::fs;
+
+    fn seeded_run() -> (Run, RunResult)
+    {
+        let mut run = Run::with_seed(0.6, 0.05, 10, 20, 4, 11).unwrap();
+        run.set_fitness(problems::onemax_fitness);
+        let result = run.run(6).unwrap();
+        (run, result)
+    }
+
+    #[test]
+    fn json_keeps_the_whole_run()
+    {
+        let (run, result) = seeded_run();
+        let path = std::env::temp_dir().join(format!("run_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_run_to_json(&run, &result, path).unwrap();
+        let record:RunRecord = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(record.config, run.manifest());
+        assert_eq!(record.config.seed, Some(11));
+        assert_eq!(record.stop_reason, Some(StopReason::Iterations));
+        assert_eq!(record.stats.len(), 6);
+        assert_eq!(record.stats[5].best, run.history()[5].best);
+        assert_eq!(record.stats[5].timings, run.history()[5].timings);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn jsonl_lines_stand_alone()
+    {
+        let (run, result) = seeded_run();
+        let path = std::env::temp_dir().join(format!("run_{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut writer = JsonlWriter::append(path, run.manifest()).unwrap();
+        run.history().iter().for_each(|stats| writer.write(stats).unwrap());
+        writer.finish(&result).unwrap();
+
+        let lines:Vec<serde_json::Value> = fs::read_to_string(path).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[6]["stop_reason"], "Iterations");
+        assert_eq!(lines[6]["evaluations"], result.evaluations);
+        assert_eq!(lines[6]["seed"], 11);
+        assert_eq!(lines[2]["generation"], 3);
+        assert_eq!(lines[2]["seed"], 11);
+        assert_eq!(lines[2]["Pcross"], 0.6);
+        assert_eq!(lines[2]["config_hash"], run.config_hash());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn decoded_phenotypes_are_exported()
+    {
+        let decoding = Decoding {scale:3, offset:1, gray:false};
+        let mut run = Run::with_seed(0.6, 0.05, 10, 20, 4, 11).unwrap();
+        run.set_decoding(decoding);
+        let result = run.run(3).unwrap();
+        let best = run.history()[2].best.as_ref().unwrap().data;
+        assert_eq!(run.history()[2].best_phenotype, Some(decoding.decode(best)));
+
+        let path = std::env::temp_dir().join(format!("phenotypes_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_run_to_json(&run, &result, path).unwrap();
+        let record:RunRecord = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(record.config.decoding, Some(decoding));
+        assert_eq!(record.stats[2].best_phenotype, Some(decoding.decode(best)));
+
+        crate::stats::save_stats_to_csv(run.history(), path).unwrap();
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        let column = reader.headers().unwrap().iter().position(|name| name == "best_phenotype").unwrap();
+        let last = reader.records().last().unwrap().unwrap();
+        assert_eq!(last[column], decoding.decode(best).to_string());
+        fs::remove_file(path).unwrap();
+        assert_eq!(seeded_run().0.history()[0].best_phenotype, None);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::Chromosome;
+use rand::Rng;
+
+/// Bit-string chromosome whose length `L` is a compile-time constant. Lengths outside 1..=64
+/// and crossover points past the end of the genome are rejected when the code is compiled
+/// instead of being checked on every generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedChromosome<const L:usize>
+{
+    data:u64,
+    pub fitness:f64,
+    pub violation:f64,
+}
+
+struct CrossoverPoint<const L:usize, const Z:usize>;
+
+impl<const L:usize, const Z:usize> CrossoverPoint<L, Z>
+{
+    const VALID:() = assert!(Z <= L, "crossover point past the end of the genome");
+}
+
+impl<const L:usize> FixedChromosome<L>
+{
+    const VALID:() = assert!(L >= 1 && L <= 64, "genome length must be between 1 and 64 bits");
+    /// Mask of the `L` genome bits.
+    pub const MASK:u64 = if L >= 64 { u64::MAX } else { (1u64 << L) - 1 };
+
+    /// Genome from the low `L` bits of `data`, higher bits are dropped.
+    pub fn new(data:u64) -> Self
+    {
+        let () = Self::VALID;
+        FixedChromosome {data:data & Self::MASK, fitness:0.0, violation:0.0}
+    }
+
+    pub fn random<R:Rng + ?Sized>(rng:&mut R) -> Self
+    {
+        Self::new(rng.gen())
+    }
+
+    pub fn data(&self) -> u64
+    {
+        self.data
+    }
+
+    /// Genome as an array of bits, least significant first.
+    pub fn bits(&self) -> [bool; L]
+    {
+        std::array::from_fn(|i| self.data >> i & 1 == 1)
+    }
+
+    pub fn from_bits(bits:[bool; L]) -> Self
+    {
+        Self::new(bits.iter().enumerate().fold(0, |data, (i, bit)| data | (*bit as u64) << i))
+    }
+
+    pub fn flip(&mut self, bit:usize)
+    {
+        assert!(bit < L, "bit {} out of range for a {}-bit genome", bit, L);
+        self.data ^= 1 << bit;
+    }
+
+    /// Flips one uniformly chosen bit.
+    pub fn mutate<R:Rng + ?Sized>(&mut self, rng:&mut R)
+    {
+        self.flip(rng.gen_range(0..L));
+    }
+
+    /// Children that swap the low `Z` bits of the parents.
+    pub fn crossover<const Z:usize>(&self, other:&Self) -> (Self, Self)
+    {
+        let () = CrossoverPoint::<L, Z>::VALID;
+        let low = if Z >= 64 { u64::MAX } else { (1u64 << Z) - 1 };
+        (Self::new((self.data & !low) | (other.data & low)), Self::new((other.data & !low) | (self.data & low)))
+    }
+}
+
+impl<const L:usize> From<FixedChromosome<L>> for Chromosome
+{
+    fn from(ind:FixedChromosome<L>) -> Self
+    {
+        Chromosome {data:ind.data, fitness:ind.fitness, N:L, violation:ind.violation}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_to_length()
+    {
+        let ind = FixedChromosome::<4>::new(0xff);
+        assert_eq!(ind.data(), 0b1111);
+        assert_eq!(FixedChromosome::<64>::MASK, u64::MAX);
+        let random = FixedChromosome::<10>::random(&mut rand::thread_rng());
+        assert!(random.data() < 1 << 10);
+    }
+
+    #[test]
+    fn bits_round_trip()
+    {
+        let ind = FixedChromosome::<5>::new(0b10110);
+        assert_eq!(ind.bits(), [false, true, true, false, true]);
+        assert_eq!(FixedChromosome::from_bits(ind.bits()), ind);
+    }
+
+    #[test]
+    fn crossover_swaps_low_bits()
+    {
+        let a = FixedChromosome::<8>::new(0b1111_0000);
+        let b = FixedChromosome::<8>::new(0b0000_1111);
+        let (c, d) = a.crossover::<2>(&b);
+        assert_eq!(c.data(), 0b1111_0011);
+        assert_eq!(d.data(), 0b0000_1100);
+        let chromosome:Chromosome = c.into();
+        assert_eq!(chromosome.N, 8);
+    }
+}
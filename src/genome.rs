@@ -0,0 +1,152 @@
+use crate::Chromosome;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::sync::Arc;
+
+/// splitmix64 finalizer, a cheap full-avalanche mix of one word.
+pub fn mix64(mut x:u64) -> u64
+{
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+impl Chromosome
+{
+    pub fn genome_hash(&self) -> u64
+    {
+        mix64(self.data)
+    }
+}
+
+/// Word-at-a-time hasher for genotypes, much cheaper than SipHash and fine for
+/// non-adversarial keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenomeHasher
+{
+    hash:u64,
+}
+
+impl Hasher for GenomeHasher
+{
+    fn write(&mut self, bytes:&[u8])
+    {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, x:u64)
+    {
+        self.hash = mix64(self.hash ^ x);
+    }
+
+    fn finish(&self) -> u64
+    {
+        self.hash
+    }
+}
+
+pub type GenomeBuildHasher = BuildHasherDefault<GenomeHasher>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GenomeId(pub u32);
+
+/// Stores each distinct genome once and hands out small ids, so equal genomes compare
+/// in O(1) by id whatever their size. The lookup table and the id-ordered list share one
+/// allocation per genome.
+#[derive(Debug, Clone)]
+pub struct GenomeInterner<G:Hash + Eq + Clone>
+{
+    ids:HashMap<Arc<G>, GenomeId, GenomeBuildHasher>,
+    genomes:Vec<Arc<G>>,
+}
+
+impl<G:Hash + Eq + Clone> Default for GenomeInterner<G>
+{
+    fn default() -> Self
+    {
+        GenomeInterner {ids:HashMap::default(), genomes:Vec::new()}
+    }
+}
+
+impl<G:Hash + Eq + Clone> GenomeInterner<G>
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, genome:&G) -> GenomeId
+    {
+        if let Some(id) = self.ids.get(genome) {
+            return *id;
+        }
+        let id = GenomeId(self.genomes.len() as u32);
+        let genome = Arc::new(genome.clone());
+        self.genomes.push(Arc::clone(&genome));
+        self.ids.insert(genome, id);
+        id
+    }
+
+    pub fn get(&self, id:GenomeId) -> Option<&G>
+    {
+        self.genomes.get(id.0 as usize).map(|genome| genome.as_ref())
+    }
+
+    pub fn id_of(&self, genome:&G) -> Option<GenomeId>
+    {
+        self.ids.get(genome).copied()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.genomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.genomes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn interning_dedupes()
+    {
+        let mut interner:GenomeInterner<Vec<u64>> = GenomeInterner::new();
+        let a = interner.intern(&vec![1, 2, 3]);
+        let b = interner.intern(&vec![4]);
+        let c = interner.intern(&vec![1, 2, 3]);
+        assert_eq!(a, c);
+        assert_ne!(a, b);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get(b), Some(&vec![4]));
+        assert_eq!(interner.id_of(&vec![9]), None);
+        assert!(interner.genomes.iter().all(|genome| Arc::strong_count(genome) == 2));
+    }
+
+    #[test]
+    fn distinct_genotypes_in_run()
+    {
+        let mut test_run = crate::Run::new(0.322, 0.01, 10, 4, 2).unwrap();
+        for (genome, data) in test_run.population.data.iter_mut().zip([5, 7, 5, 5]) {
+            *genome = data;
+        }
+        assert_eq!(test_run.distinct_genotypes(), 2);
+    }
+
+    #[test]
+    fn hash_spreads_neighbours()
+    {
+        let hashes:HashSet<u64> = (0..1000u64).map(mix64).collect();
+        assert_eq!(hashes.len(), 1000);
+        assert!((mix64(1) ^ mix64(2)).count_ones() > 16);
+    }
+}
@@ -0,0 +1,122 @@
+//! Two mating groups with their own mutation rates, e.g. a high-mutation explorer group and a
+//! low-mutation exploiter group. The first half of the population slots is the first group and
+//! the second half the second. Parents are selected within each group, but every pair has one
+//! parent from each, and each pair's children go back one to each group.
+use crate::{ops, sampling, stats, Run};
+use rand::seq::SliceRandom;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatingGroups
+{
+    pub first_Pmut:f32,
+    pub second_Pmut:f32,
+}
+
+impl Run
+{
+    /// Splits generational reproduction into two groups, see `MatingGroups`. The groups replace
+    /// the run's `Pmut` unless a mutation operator is set, and the mating pool is always the
+    /// population size. Steady-state runs ignore the groups.
+    pub fn set_mating_groups(&mut self, groups:MatingGroups)
+    {
+        self.mating_groups = Some(groups);
+    }
+
+    pub fn mating_groups(&self) -> Option<MatingGroups>
+    {
+        self.mating_groups
+    }
+
+    // first and second group slots
+    fn group_slots(&self) -> (Vec<usize>, Vec<usize>)
+    {
+        let half = self.population.len() / 2;
+        ((0..half).collect(), (half..self.population.len()).collect())
+    }
+
+    // returns the effective population size of the selection
+    pub(crate) fn recomb_groups(&mut self) -> f64
+    {
+        let (first, second) = self.group_slots();
+        let mut selected = self.select_parents_among(&first, first.len());
+        selected.extend(self.select_parents_among(&second, second.len()));
+        self.log_parents(&selected);
+        let mut offspring = vec![0; self.population.len()];
+        selected.iter().for_each(|&slot| offspring[slot] += 1);
+        self.population = self.population.gather(&selected);
+        stats::effective_size(&offspring)
+    }
+
+    pub(crate) fn cross_groups(&mut self)
+    {
+        let (first, mut second) = self.group_slots();
+        let rng = &mut self.rng;
+        second.shuffle(rng);
+        let pairs:Vec<(usize, usize)> = first.iter().copied().zip(second).collect();
+        let children = match &self.crossover {
+            Some(crossover) => {
+                let mut children:Vec<u64> = pairs.iter().flat_map(|&(a, b)| [self.population.data[a], self.population.data[b]]).collect();
+                for k in sampling::bernoulli_indices(rng, self.Pcross as f64, pairs.len()) {
+                    (children[2*k], children[2*k + 1]) = crossover.crossover(children[2*k], children[2*k + 1], self.L, rng);
+                }
+                children
+            }
+            None => ops::crossover(&self.population.data, &pairs, self.Pcross, self.L, self.z, rng),
+        };
+        let order:Vec<usize> = pairs.iter().flat_map(|&(a, b)| [a, b]).collect();
+        let mut next = self.population.gather(&order);
+        next.data = children;
+        // back into group order, first-group children in the first half
+        let mut slots:Vec<usize> = (0..pairs.len()).map(|k| 2*k).collect();
+        slots.extend((0..pairs.len()).map(|k| 2*k + 1));
+        self.population = next.gather(&slots);
+    }
+
+    pub(crate) fn mutate_groups(&mut self, groups:MatingGroups)
+    {
+        let rng = &mut self.rng;
+        let half = self.population.len() / 2;
+        let (first, second) = self.population.data.split_at_mut(half);
+        for (data, Pmut) in [(first, groups.first_Pmut), (second, groups.second_Pmut)] {
+            match &self.mutation {
+                Some(mutation) => mutation.mutate(data, self.L, rng),
+                None => {
+                    let mutated = self.mutation_scheme.apply(data, Pmut, self.L, rng);
+                    data.copy_from_slice(&mutated);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    #[test]
+    fn groups_mate_across()
+    {
+        let mut test_run = Run::new(1.0, 0.0, 8, 8, 8).unwrap();
+        test_run.population.data = vec![0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        test_run.cross_groups();
+        // z = L swaps whole genomes, so only cross-group pairs move the groups' contents
+        assert_eq!(test_run.population.data, vec![0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn groups_keep_their_mutation_rates()
+    {
+        let mut test_run = Run::new(0.0, 0.0, 16, 40, 4).unwrap();
+        test_run.population.data = vec![0; 40];
+        test_run.mutate_groups(MatingGroups {first_Pmut:0.0, second_Pmut:1.0});
+        assert!(test_run.population.data[..20].iter().all(|&d| d == 0));
+        assert!(test_run.population.data[20..].iter().all(|&d| d.count_ones() == 1));
+
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.set_mating_groups(MatingGroups {first_Pmut:0.5, second_Pmut:0.01});
+        test_run.run(20).unwrap();
+        assert_eq!(test_run.population.len(), 40);
+        assert_eq!(test_run.mating_groups().unwrap().second_Pmut, 0.01);
+    }
+}
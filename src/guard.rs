@@ -0,0 +1,98 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+
+/// What to do with a NaN or infinite fitness or constraint violation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFinitePolicy
+{
+    /// Infinities are clamped into the valid range, NaN becomes the worst value.
+    Clamp,
+    /// Any non-finite value becomes the worst value.
+    Worst,
+    /// Stop the run with a `NonFiniteFitness` error.
+    Error,
+}
+
+impl NonFinitePolicy
+{
+    /// Replacement for `value` given the worst and best allowed values, finite values pass through.
+    pub fn repair(self, value:f64, worst:f64, best:f64) -> f64
+    {
+        if value.is_finite() {
+            return value;
+        }
+        match self {
+            NonFinitePolicy::Clamp if !value.is_nan() => value.clamp(worst.min(best), worst.max(best)),
+            _ => worst,
+        }
+    }
+}
+
+/// What to do when the fitness function panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicPolicy
+{
+    /// Let the panic unwind out of the run like any other.
+    #[default]
+    Propagate,
+    /// Give the individual the worst fitness and carry on, see `GenerationStats::panics`.
+    Worst,
+    /// Stop the run with `GaError::FitnessPanic`. The run stays usable, so it can be inspected,
+    /// saved or continued.
+    Abort,
+}
+
+/// Why an evaluation gave no fitness, the individual then gets the worst one.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum EvaluationFailure
+{
+    /// The fitness function panicked with this message.
+    Panic(String),
+    /// The evaluation overran `Run::set_evaluation_timeout`.
+    Timeout,
+}
+
+pub(crate) fn panic_message(payload:Box<dyn Any + Send>) -> String
+{
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or("unknown panic".to_string(), |m| m.to_string()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonFiniteFitness
+{
+    pub generation:u32,
+    pub index:usize,
+    pub fitness:f64,
+    pub violation:f64,
+}
+
+impl fmt::Display for NonFiniteFitness
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "generation {}: individual {} evaluated to fitness {} with violation {}",
+            self.generation, self.index, self.fitness, self.violation)
+    }
+}
+
+impl Error for NonFiniteFitness {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair_values()
+    {
+        assert_eq!(NonFinitePolicy::Clamp.repair(f64::INFINITY, 0.0, f64::MAX), f64::MAX);
+        assert_eq!(NonFinitePolicy::Clamp.repair(f64::NEG_INFINITY, 0.0, f64::MAX), 0.0);
+        assert_eq!(NonFinitePolicy::Clamp.repair(f64::NAN, 0.0, f64::MAX), 0.0);
+        assert_eq!(NonFinitePolicy::Worst.repair(f64::INFINITY, 0.0, f64::MAX), 0.0);
+        assert_eq!(NonFinitePolicy::Worst.repair(f64::NAN, f64::MAX, 0.0), f64::MAX);
+        assert_eq!(NonFinitePolicy::Worst.repair(3.5, 0.0, f64::MAX), 3.5);
+    }
+}
@@ -0,0 +1,83 @@
+//! Crossover guided by allele statistics, blending an estimation-of-distribution step into the
+//! GA loop as in PBIL-guided recombination. Where the parents disagree, the first child takes a
+//! one with a probability pulled from one half towards the frequency of ones at that locus in
+//! the mating population; the second child gets the other parent's bit. Agreeing bits are kept.
+use crate::ops;
+use crate::Run;
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuidedCrossover
+{
+    /// 0 is uniform crossover, 1 draws disagreeing bits from the allele frequencies alone.
+    pub strength:f64,
+}
+
+impl GuidedCrossover
+{
+    pub fn crossover<R:Rng + ?Sized>(&self, a:u64, b:u64, frequencies:&[f64], rng:&mut R) -> (u64, u64)
+    {
+        let strength = self.strength.clamp(0.0, 1.0);
+        let mut child = a;
+        let differing = a ^ b;
+        for (bit, frequency) in frequencies.iter().enumerate() {
+            if differing >> bit & 1 == 1 {
+                let one = rng.gen::<f64>() < (1.0 - strength) * 0.5 + strength * frequency;
+                child = (child & !(1 << bit)) | (one as u64) << bit;
+            }
+        }
+        (child, child ^ differing)
+    }
+}
+
+/// Fraction of genomes with a one at each of the `L` loci.
+pub fn allele_frequencies(data:&[u64], L:u8) -> Vec<f64>
+{
+    let mask = ops::low_mask(L);
+    (0..L).map(|bit| data.iter().filter(|&&d| (d & mask) >> bit & 1 == 1).count() as f64 / data.len().max(1) as f64).collect()
+}
+
+impl Run
+{
+    /// Generational runs cross with `guided` instead of the crossover operator or tail swap,
+    /// using the allele frequencies of the selected parents. `Pcross` still decides which pairs
+    /// cross.
+    pub fn set_guided_crossover(&mut self, guided:GuidedCrossover)
+    {
+        self.guided_crossover = Some(guided);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_allele_frequencies()
+    {
+        let mut rng = rand::thread_rng();
+        assert_eq!(allele_frequencies(&[0b01, 0b11, 0b101], 2), vec![1.0, 1.0 / 3.0]);
+
+        let guided = GuidedCrossover {strength:1.0};
+        let frequencies = [1.0, 0.0, 0.5];
+        for _ in 0..20 {
+            let (c, d) = guided.crossover(0b010, 0b101, &frequencies, &mut rng);
+            assert_eq!(c & 0b011, 0b001);
+            assert_eq!(c ^ d, 0b111);
+        }
+        let uniform = GuidedCrossover {strength:0.0};
+        let ones = (0..1000).filter(|_| uniform.crossover(0, 1, &[1.0], &mut rng).0 == 1).count();
+        assert!((400..600).contains(&ones), "{}", ones);
+    }
+
+    #[test]
+    fn guided_run_solves_onemax()
+    {
+        let mut test_run = Run::new(0.9, 0.05, 16, 30, 8).unwrap();
+        test_run.set_fitness(crate::problems::onemax_fitness);
+        test_run.set_guided_crossover(GuidedCrossover {strength:0.5});
+        test_run.set_elitism(1);
+        test_run.run(60).unwrap();
+        assert!(test_run.best().unwrap().fitness >= 14.0, "{:?}", test_run.best());
+    }
+}
@@ -0,0 +1,104 @@
+//! The best individuals ever evaluated in a run. Selection is stochastic, so the best genome
+//! of one generation can be lost in the next; the hall of fame keeps the top `capacity`
+//! distinct genotypes over the whole run.
+use crate::objective::Objective;
+use crate::Chromosome;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HallOfFame
+{
+    capacity:usize,
+    /// Best first, feasible before infeasible.
+    members:Vec<Chromosome>,
+}
+
+// feasible before infeasible, then by fitness
+fn rank(a:&Chromosome, b:&Chromosome, objective:Objective) -> Ordering
+{
+    (b.violation <= 0.0).cmp(&(a.violation <= 0.0)).then(objective.best_first(a.fitness, b.fitness))
+}
+
+impl HallOfFame
+{
+    pub fn new(capacity:usize) -> Self
+    {
+        HallOfFame {capacity, members:Vec::with_capacity(capacity + 1)}
+    }
+
+    pub fn capacity(&self) -> usize
+    {
+        self.capacity
+    }
+
+    pub fn members(&self) -> &[Chromosome]
+    {
+        &self.members
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.members.is_empty()
+    }
+
+    /// Adds `ind` if it ranks among the best. A genotype already present keeps its better
+    /// evaluation. Returns whether the hall changed.
+    pub fn insert(&mut self, ind:&Chromosome, objective:Objective) -> bool
+    {
+        if let Some(i) = self.members.iter().position(|member| member.data == ind.data) {
+            if rank(ind, &self.members[i], objective) != Ordering::Less {
+                return false;
+            }
+            self.members.remove(i);
+        }else if self.members.len() == self.capacity
+            && self.members.last().is_none_or(|worst| rank(ind, worst, objective) != Ordering::Less) {
+            return false;
+        }
+        let at = self.members.partition_point(|member| rank(member, ind, objective) != Ordering::Greater);
+        self.members.insert(at, ind.clone());
+        self.members.truncate(self.capacity);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{problems, Run};
+
+    fn ind(data:u64, fitness:f64) -> Chromosome
+    {
+        Chromosome {data, fitness, N:4, violation:0.0}
+    }
+
+    #[test]
+    fn keeps_the_best_distinct_genotypes()
+    {
+        let mut hall = HallOfFame::new(3);
+        for (data, fitness) in [(1, 5.0), (2, 7.0), (1, 6.0), (3, 1.0), (4, 9.0), (1, 2.0)] {
+            hall.insert(&ind(data, fitness), Objective::Maximize);
+        }
+        let members:Vec<(u64, f64)> = hall.members().iter().map(|m| (m.data, m.fitness)).collect();
+        assert_eq!(members, vec![(4, 9.0), (2, 7.0), (1, 6.0)]);
+        assert!(!hall.insert(&ind(5, 6.0), Objective::Maximize));
+        assert!(HallOfFame::new(0).members().is_empty());
+    }
+
+    #[test]
+    fn runs_report_their_hall_of_fame()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 10, 20, 4, 8).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.set_hall_of_fame(5);
+        let result = test_run.run(30).unwrap();
+        assert_eq!(result.hall_of_fame.len(), 5);
+        assert_eq!(result.hall_of_fame[0].fitness, test_run.best().unwrap().fitness);
+        assert!(result.hall_of_fame.windows(2).all(|w| w[0].fitness >= w[1].fitness && w[0].data != w[1].data));
+    }
+}
@@ -0,0 +1,184 @@
+//! Success-rate benchmarking: an algorithm is run repeatedly on a problem with a known optimum,
+//! and each run counts as a success once its best fitness gets within `tolerance` of it.
+//! Reports the success rate, the mean evaluations of successful runs and the expected running
+//! time (ERT): all evaluations spent, failed runs included, per success.
+use crate::benchmarks::Benchmark;
+use crate::objective::Objective;
+use crate::evolution::{Genome, GenomeRun, GenomeStats};
+use crate::pbil::Pbil;
+use std::fmt;
+
+/// Best fitness seen so far after a number of evaluations, one point per generation.
+pub type Trace = Vec<(usize, f64)>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Target
+{
+    pub optimum:f64,
+    pub tolerance:f64,
+    pub objective:Objective,
+}
+
+impl Target
+{
+    pub fn reached(&self, fitness:f64) -> bool
+    {
+        match self.objective {
+            Objective::Minimize => fitness <= self.optimum + self.tolerance,
+            Objective::Maximize => fitness >= self.optimum - self.tolerance,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuccessReport
+{
+    pub name:String,
+    pub runs:usize,
+    pub successes:usize,
+    /// Mean evaluations until the target was reached, over successful runs.
+    pub mean_evaluations:Option<f64>,
+    /// Evaluations of all runs (up to success, or all of a failed run) per success,
+    /// infinite without successes.
+    pub ert:f64,
+}
+
+impl SuccessReport
+{
+    pub fn success_rate(&self) -> f64
+    {
+        if self.runs == 0 { 0.0 } else { self.successes as f64 / self.runs as f64 }
+    }
+
+    /// Scores the traces of independent runs against `target`.
+    pub fn from_traces(name:&str, traces:&[Trace], target:Target) -> Self
+    {
+        let mut successes = 0;
+        let mut success_evaluations = 0;
+        let mut total_evaluations = 0;
+        for trace in traces {
+            match trace.iter().find(|(_, best)| target.reached(*best)) {
+                Some(&(evaluations, _)) => {
+                    successes += 1;
+                    success_evaluations += evaluations;
+                    total_evaluations += evaluations;
+                }
+                None => total_evaluations += trace.last().map_or(0, |&(evaluations, _)| evaluations),
+            }
+        }
+        SuccessReport {
+            name:name.to_string(),
+            runs:traces.len(),
+            successes,
+            mean_evaluations:(successes > 0).then(|| success_evaluations as f64 / successes as f64),
+            ert:if successes > 0 { total_evaluations as f64 / successes as f64 } else { f64::INFINITY },
+        }
+    }
+
+    /// Runs `trial` `runs` times, each call being one independent run returning its trace.
+    pub fn measure<F:FnMut() -> Trace>(name:&str, runs:usize, target:Target, mut trial:F) -> Self
+    {
+        let traces:Vec<Trace> = (0..runs).map(|_| trial()).collect();
+        Self::from_traces(name, &traces, target)
+    }
+
+    /// `runs` runs of `generations` generations of a population of `n` on `benchmark`.
+    pub fn benchmark(benchmark:Benchmark, dimension:usize, n:usize, generations:u32, runs:usize, tolerance:f64) -> Self
+    {
+        let target = Target {optimum:benchmark.optimum_value(), tolerance, objective:Objective::Minimize};
+        let name = format!("{}-{}d", benchmark.name(), dimension);
+        Self::measure(&name, runs, target, || {
+            let mut run = benchmark.build_run(dimension, n);
+            run.run(generations);
+            run.trace()
+        })
+    }
+}
+
+/// Best fitness so far against evaluations, one point per generation of `history`.
+pub fn trace_of(history:&[GenomeStats], objective:Objective) -> Trace
+{
+    let mut best:Option<f64> = None;
+    history.iter().map(|stats| {
+        let fitness = match best {
+            Some(b) if !objective.is_better(stats.best_fitness, b) => b,
+            _ => stats.best_fitness,
+        };
+        best = Some(fitness);
+        (stats.evaluations, fitness)
+    }).collect()
+}
+
+impl<G:Genome> GenomeRun<G>
+{
+    pub fn trace(&self) -> Trace
+    {
+        trace_of(self.history(), self.objective())
+    }
+}
+
+impl Pbil
+{
+    pub fn trace(&self) -> Trace
+    {
+        trace_of(self.history(), self.objective())
+    }
+}
+
+/// Formats reports as an aligned plain-text table.
+pub fn format_table(reports:&[SuccessReport]) -> String
+{
+    let width = reports.iter().map(|r| r.name.len()).chain([7]).max().unwrap();
+    let mut table = format!("{:<width$}  {:>6}  {:>8}  {:>12}  {:>12}\n", "problem", "runs", "success", "mean evals", "ERT");
+    for report in reports {
+        table.push_str(&format!("{:<width$}  {}\n", report.name, report));
+    }
+    table
+}
+
+impl fmt::Display for SuccessReport
+{
+    // the numeric columns of a table row
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        let mean = self.mean_evaluations.map_or("-".to_string(), |m| format!("{:.0}", m));
+        let ert = if self.ert.is_finite() { format!("{:.0}", self.ert) } else { "inf".to_string() };
+        write!(f, "{:>6}  {:>7.0}%  {:>12}  {:>12}", self.runs, 100.0 * self.success_rate(), mean, ert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ert_counts_failed_runs()
+    {
+        let target = Target {optimum:0.0, tolerance:0.1, objective:Objective::Minimize};
+        let traces = vec![
+            vec![(10, 5.0), (20, 0.05)],
+            vec![(10, 5.0), (20, 1.0), (30, 1.0)],
+            vec![(10, 0.0)],
+        ];
+        let report = SuccessReport::from_traces("toy", &traces, target);
+        assert_eq!(report.successes, 2);
+        assert_eq!(report.mean_evaluations, Some(15.0));
+        assert_eq!(report.ert, 30.0);
+        assert!((report.success_rate() - 2.0 / 3.0).abs() < 1e-12);
+
+        let failed = SuccessReport::from_traces("none", &traces[1..2], target);
+        assert_eq!(failed.ert, f64::INFINITY);
+        let table = format_table(&[report, failed]);
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.contains("inf"));
+    }
+
+    #[test]
+    fn benchmark_report()
+    {
+        let report = SuccessReport::benchmark(Benchmark::Rastrigin, 1, 30, 60, 5, 1e-2);
+        assert_eq!(report.runs, 5);
+        assert!(report.successes >= 1, "{:?}", report);
+        assert!(report.mean_evaluations.unwrap() <= 1800.0);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::Run;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthWarning
+{
+    /// Every individual scored zero, so selection carries no information this generation.
+    ZeroTotalFitness{generation:u32},
+    /// Fewer than 0.1 bit flips are expected per generation across the whole population.
+    LowMutation{expected_flips:f64},
+    /// All individuals share one genotype; at generation 1 or 2 this usually means a bad setup.
+    Converged{generation:u32},
+    /// The leaderboard file could not be read or written at the end of the run.
+    LeaderboardNotUpdated{reason:String},
+}
+
+impl fmt::Display for HealthWarning
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            HealthWarning::ZeroTotalFitness{generation} =>
+                write!(f, "generation {}: total fitness is zero, selection is uninformed", generation),
+            HealthWarning::LowMutation{expected_flips} =>
+                write!(f, "only {:.4} bit flips expected per generation", expected_flips),
+            HealthWarning::Converged{generation} =>
+                write!(f, "generation {}: population converged to a single genotype", generation),
+            HealthWarning::LeaderboardNotUpdated{reason} =>
+                write!(f, "leaderboard not updated: {}", reason),
+        }
+    }
+}
+
+impl Run
+{
+    pub fn set_warning_callback(&mut self, callback:fn(&HealthWarning))
+    {
+        self.warning_callback = Some(callback);
+    }
+
+    pub fn warnings(&self) -> &[HealthWarning]
+    {
+        &self.warnings
+    }
+
+    pub(crate) fn warn(&mut self, warning:HealthWarning)
+    {
+        if let Some(callback) = self.warning_callback {
+            callback(&warning);
+        }
+        self.warnings.push(warning);
+    }
+
+    pub(crate) fn check_configuration(&mut self)
+    {
+        let expected_flips = self.mutation_scheme.expected_flips(self.Pmut, self.L) * self.n as f64;
+        if expected_flips < 0.1 {
+            self.warn(HealthWarning::LowMutation{expected_flips});
+        }
+    }
+
+    // zero fitness and convergence are only reported the first time they happen, an empty
+    // population has neither
+    pub(crate) fn check_generation(&mut self)
+    {
+        let Some(&first) = self.population.data.first() else { return };
+        let generation = self.period;
+        if self.selection_degenerate()
+            && !self.warnings.iter().any(|w| matches!(w, HealthWarning::ZeroTotalFitness{..})) {
+            self.warn(HealthWarning::ZeroTotalFitness{generation});
+        }
+        if self.population.data.iter().all(|data| *data == first)
+            && !self.warnings.iter().any(|w| matches!(w, HealthWarning::Converged{..})) {
+            self.warn(HealthWarning::Converged{generation});
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::MutationScheme;
+
+    #[test]
+    fn low_mutation_is_reported_once()
+    {
+        let mut test_run = Run::new(0.322, 0.0001, 10, 30, 2).unwrap();
+        test_run.run(2).unwrap();
+        test_run.run(2).unwrap();
+        let low:Vec<&HealthWarning> = test_run.warnings().iter()
+            .filter(|w| matches!(w, HealthWarning::LowMutation{..}))
+            .collect();
+        assert_eq!(low.len(), 1);
+
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.run(1).unwrap();
+        assert!(test_run.warnings().is_empty());
+
+        // 0.002 per bit is 0.6 flips per generation over 10-bit genomes
+        let mut test_run = Run::new(0.322, 0.002, 10, 30, 2).unwrap();
+        test_run.set_mutation_scheme(MutationScheme::PerLocus);
+        test_run.run(1).unwrap();
+        assert!(test_run.warnings().is_empty());
+        let mut test_run = Run::new(0.322, 0.0002, 10, 30, 2).unwrap();
+        test_run.set_mutation_scheme(MutationScheme::PerLocus);
+        test_run.run(1).unwrap();
+        assert!(matches!(test_run.warnings()[0], HealthWarning::LowMutation{expected_flips} if (expected_flips - 0.06).abs() < 1e-6));
+    }
+
+    #[test]
+    fn zero_fitness_is_reported_once()
+    {
+        fn never(_ind:&crate::Chromosome) -> f64
+        {
+            1.0
+        }
+
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.set_fitness(|_, _| 0.0);
+        test_run.add_constraint(never);
+        test_run.set_penalty(1e12);
+        test_run.run(5).unwrap();
+        let zero:Vec<&HealthWarning> = test_run.warnings().iter()
+            .filter(|w| matches!(w, HealthWarning::ZeroTotalFitness{..}))
+            .collect();
+        assert_eq!(zero, vec![&HealthWarning::ZeroTotalFitness{generation:1}]);
+    }
+
+    #[test]
+    fn empty_populations_are_not_reported()
+    {
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.population = std::iter::empty::<crate::Chromosome>().collect();
+        test_run.check_generation();
+        assert!(test_run.warnings().is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{MutationScheme, Run};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuggestedParameters
+{
+    pub Pcross:f32,
+    pub Pmut:f32,
+    pub L:u8,
+    pub n:usize,
+    pub z:u8,
+}
+
+/// Rule-of-thumb settings for a genome of `L` bits: a population proportional to L·ln L
+/// (at least 10, rounded up to an even size so everyone gets a crossover partner),
+/// the textbook mutation rate 1/L per bit, Pcross 0.6 and the crossover point in the middle.
+pub fn suggest_parameters(L:u8) -> SuggestedParameters
+{
+    assert!(L > 0, "genome length must be positive");
+    let l = L as f64;
+    let mut n = ((l * l.ln()).ceil() as usize).max(10);
+    n += n % 2;
+    SuggestedParameters {Pcross:0.6, Pmut:1.0 / l as f32, L, n, z:(L / 2).max(1)}
+}
+
+impl Run
+{
+    /// A run with `suggest_parameters(L)`, mutating per locus so `Pmut` is the rate per bit.
+    pub fn auto(L:u8) -> Self
+    {
+        let p = suggest_parameters(L);
+        let mut run = Run::unchecked(p.Pcross, p.Pmut, p.L, p.n, p.z, None);
+        run.set_mutation_scheme(MutationScheme::PerLocus);
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions_scale_with_length()
+    {
+        let short = suggest_parameters(10);
+        let long = suggest_parameters(64);
+        assert_eq!(short.n, 24);
+        assert!(long.n > short.n && long.n.is_multiple_of(2));
+        assert!((long.Pmut - 1.0 / 64.0).abs() < 1e-9);
+        assert_eq!(suggest_parameters(1).n, 10);
+    }
+
+    #[test]
+    fn auto_run()
+    {
+        let mut test_run = Run::auto(10);
+        assert!((test_run.mutation_scheme.expected_flips(test_run.Pmut, test_run.L) - 1.0).abs() < 1e-6);
+        let result = test_run.run(5).unwrap();
+        assert_eq!(result.population.len(), 24);
+        assert_eq!(result.stats.len(), 5);
+    }
+}
@@ -0,0 +1,188 @@
+//! Reading saved runs back, so analysis and plotting work on runs from earlier sessions as well
+//! as in-memory ones. Each loader accepts what the matching writer produced.
+use crate::cournot::Welfare;
+use crate::error::GaError;
+use crate::export::RunRecord;
+use crate::stats::{GenerationStats, PhaseTimings};
+use crate::Chromosome;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+use std::time::Duration;
+
+// one CSV row, its columns looked up by header name
+struct Row<'a>
+{
+    header:&'a csv::StringRecord,
+    record:&'a csv::StringRecord,
+    line:u64,
+}
+
+impl Row<'_>
+{
+    // `None` for a missing column or an empty cell
+    fn get<T:FromStr>(&self, column:&str) -> Result<Option<T>, GaError>
+    {
+        let Some(value) = self.header.iter().position(|name| name == column).and_then(|i| self.record.get(i)) else {
+            return Ok(None);
+        };
+        if value.is_empty() {
+            return Ok(None);
+        }
+        value.parse().map(Some).map_err(|_| GaError::Parse{line:self.line, column:column.to_string(), value:value.to_string()})
+    }
+
+    fn or_default<T:FromStr + Default>(&self, column:&str) -> Result<T, GaError>
+    {
+        Ok(self.get(column)?.unwrap_or_default())
+    }
+
+    fn seconds(&self, column:&str) -> Result<Duration, GaError>
+    {
+        let seconds = self.or_default::<f64>(column)?;
+        Duration::try_from_secs_f64(seconds)
+            .map_err(|_| GaError::Parse{line:self.line, column:column.to_string(), value:seconds.to_string()})
+    }
+}
+
+/// Reads a file written by `save_stats_to_csv` or `StatsWriter`. Columns are matched by name,
+/// so files from versions with fewer columns load with defaults for the missing ones. The CSV
+/// only keeps the genotype of the best individual: it comes back with `best_fitness`, `firms`
+/// as its population size and no violation.
+pub fn load_stats_from_csv(file_name:&str) -> Result<Vec<GenerationStats>, GaError>
+{
+    let mut reader = csv::Reader::from_path(file_name)?;
+    let header = reader.headers()?.clone();
+    let mut loaded = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let row = Row {header:&header, record:&record, line:record.position().map_or(0, |p| p.line())};
+        let best_fitness = row.get("best_fitness")?.unwrap_or(f64::NAN);
+        let firms = row.or_default("firms")?;
+        loaded.push(GenerationStats {
+            generation:row.or_default("generation")?,
+            ind_out:row.or_default("ind_out")?,
+            price:row.or_default("price")?,
+            welfare:Welfare {
+                consumer_surplus:row.or_default("consumer_surplus")?,
+                producer_surplus:row.or_default("producer_surplus")?,
+                total:row.or_default("welfare")?,
+            },
+            variance:row.or_default("var")?,
+            mean_phenotype:row.or_default("mean_phenotype")?,
+            best_fitness,
+            worst_fitness:row.get("worst_fitness")?.unwrap_or(f64::NAN),
+            mean_fitness:row.get("mean_fitness")?.unwrap_or(f64::NAN),
+            median_fitness:row.get("median_fitness")?.unwrap_or(f64::NAN),
+            std_fitness:row.get("std_fitness")?.unwrap_or(f64::NAN),
+            best:row.get("best_data")?.map(|data| Chromosome {data, fitness:best_fitness, N:firms, violation:0.0}),
+            best_phenotype:row.get("best_phenotype")?,
+            diversity:row.or_default("diversity")?,
+            feasible_fraction:row.get("feasible_fraction")?.unwrap_or(1.0),
+            mean_violation:row.or_default("mean_violation")?,
+            best_feasible_fitness:row.get("best_feasible_fitness")?,
+            penalty:row.or_default("penalty")?,
+            degenerate:row.or_default("degenerate")?,
+            shock:row.or_default("shock")?,
+            firms,
+            entrants:row.or_default("entrants")?,
+            exits:row.or_default("exits")?,
+            non_finite:row.or_default("non_finite")?,
+            panics:row.or_default("panics")?,
+            timeouts:row.or_default("timeouts")?,
+            effective_size:row.get("effective_size")?,
+            stagnant_generations:row.or_default("stagnant_generations")?,
+            timings:PhaseTimings {
+                evaluation:row.seconds("evaluation_s")?,
+                selection:row.seconds("selection_s")?,
+                crossover:row.seconds("crossover_s")?,
+                mutation:row.seconds("mutation_s")?,
+            },
+        });
+    }
+    Ok(loaded)
+}
+
+/// Reads a file written by `save_run_to_json`.
+pub fn load_run_from_json(file_name:&str) -> Result<RunRecord, GaError>
+{
+    Ok(serde_json::from_str(&fs::read_to_string(file_name)?)?)
+}
+
+/// Reads the stats of every line written by `JsonlWriter`, skipping the manifest fields and
+/// the lines that close a run.
+pub fn load_stats_from_jsonl(file_name:&str) -> Result<Vec<GenerationStats>, GaError>
+{
+    let mut loaded = Vec::new();
+    for line in BufReader::new(File::open(file_name)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value:serde_json::Value = serde_json::from_str(&line)?;
+        if value.get("stop_reason").is_none() {
+            loaded.push(serde_json::from_value(value)?);
+        }
+    }
+    Ok(loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{save_run_to_json, JsonlWriter};
+    use crate::stats::save_stats_to_csv;
+    use crate::{problems, Run};
+
+    fn temp_path(extension:&str) -> String
+    {
+        std::env::temp_dir().join(format!("import_{}.{}", std::process::id(), extension)).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn saved_runs_load_back()
+    {
+        let mut run = Run::with_seed(0.6, 0.05, 10, 20, 4, 3).unwrap();
+        run.set_fitness(problems::onemax_fitness);
+        let result = run.run(5).unwrap();
+
+        let path = temp_path("csv");
+        save_stats_to_csv(run.history(), &path).unwrap();
+        let loaded = load_stats_from_csv(&path).unwrap();
+        assert_eq!(loaded.len(), 5);
+        for (loaded, saved) in loaded.iter().zip(run.history()) {
+            assert_eq!((loaded.generation, loaded.ind_out, loaded.variance), (saved.generation, saved.ind_out, saved.variance));
+            assert_eq!(loaded.best.as_ref().map(|b| b.data), saved.best.as_ref().map(|b| b.data));
+            assert_eq!(loaded.stagnant_generations, saved.stagnant_generations);
+        }
+        fs::remove_file(&path).unwrap();
+
+        let path = temp_path("json");
+        save_run_to_json(&run, &result, &path).unwrap();
+        let record = load_run_from_json(&path).unwrap();
+        assert_eq!((record.stats.as_slice(), record.stop_reason), (run.history(), Some(result.stop_reason.clone())));
+        fs::remove_file(&path).unwrap();
+
+        let path = temp_path("jsonl");
+        let mut writer = JsonlWriter::append(&path, run.manifest()).unwrap();
+        run.history().iter().for_each(|stats| writer.write(stats).unwrap());
+        writer.finish(&result).unwrap();
+        assert_eq!(load_stats_from_jsonl(&path).unwrap(), run.history());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn old_and_broken_csv()
+    {
+        let path = temp_path("old.csv");
+        fs::write(&path, "generation,ind_out,var\n1,40,2.5\n2,x,1.0\n").unwrap();
+        match load_stats_from_csv(&path) {
+            Err(GaError::Parse{line, column, value}) => assert_eq!((line, column.as_str(), value.as_str()), (3, "ind_out", "x")),
+            other => panic!("unexpected {:?}", other),
+        }
+        fs::write(&path, "generation,ind_out,var\n1,40,2.5\n").unwrap();
+        let loaded = load_stats_from_csv(&path).unwrap();
+        assert_eq!((loaded[0].ind_out, loaded[0].variance, loaded[0].best.is_none()), (40, 2.5, true));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+//! Initial distributions per gene. By default every genome type starts uniformly over its
+//! range; `initialize` re-draws a population with a distribution of your choice for each gene,
+//! for instance to start near a known good region. Bit genes are drawn on `[0, 1]` and set when
+//! the draw is at least one half. Permutations have no per-gene values and keep their uniform
+//! shuffles.
+use crate::bits::{BitGenome, BitRun};
+use crate::population::Population;
+use crate::real::{RealChromosome, RealRun};
+use crate::sampling;
+use crate::{Chromosome, Run};
+use rand::{Rng, RngCore};
+use std::fmt;
+use std::sync::Arc;
+
+pub type GeneSampler = Arc<dyn Fn(&mut dyn RngCore) -> f64 + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub enum GeneDistribution
+{
+    #[default]
+    Uniform,
+    /// Normal draws, redrawn a few times when they fall outside the gene's range and clamped
+    /// into it after that.
+    Normal{mean:f64, sd:f64},
+    /// Any distribution, clamped into the gene's range.
+    Custom(GeneSampler),
+}
+
+impl fmt::Debug for GeneDistribution
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            GeneDistribution::Uniform => write!(f, "Uniform"),
+            GeneDistribution::Normal{mean, sd} => f.debug_struct("Normal").field("mean", mean).field("sd", sd).finish(),
+            GeneDistribution::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+// draws past which a normal sample outside the range is clamped
+const NORMAL_REDRAWS:usize = 16;
+
+impl GeneDistribution
+{
+    pub fn custom<F>(sampler:F) -> Self
+        where F:Fn(&mut dyn RngCore) -> f64 + Send + Sync + 'static
+    {
+        GeneDistribution::Custom(Arc::new(sampler))
+    }
+
+    /// One value in `[low, high]`.
+    pub fn sample(&self, low:f64, high:f64, rng:&mut dyn RngCore) -> f64
+    {
+        match self {
+            GeneDistribution::Uniform => if low < high { rng.gen_range(low..high) } else { low },
+            GeneDistribution::Normal{mean, sd} => {
+                let mut value = mean + sd * sampling::gaussian(rng);
+                for _ in 0..NORMAL_REDRAWS {
+                    if (low..=high).contains(&value) {
+                        break;
+                    }
+                    value = mean + sd * sampling::gaussian(rng);
+                }
+                value.clamp(low, high)
+            }
+            GeneDistribution::Custom(sampler) => sampler(rng).clamp(low, high),
+        }
+    }
+
+    fn sample_bit(&self, rng:&mut dyn RngCore) -> bool
+    {
+        match self {
+            GeneDistribution::Uniform => rng.gen(),
+            _ => self.sample(0.0, 1.0, rng) >= 0.5,
+        }
+    }
+}
+
+// distribution of gene `i`, the last one covering genes past the end
+fn gene(genes:&[GeneDistribution], i:usize) -> &GeneDistribution
+{
+    assert!(!genes.is_empty(), "at least one gene distribution is needed");
+    &genes[i.min(genes.len() - 1)]
+}
+
+impl Run
+{
+    /// Re-draws the population, bit `k` of every genome from `genes[k]`. A single distribution
+    /// covers every bit.
+    pub fn initialize(&mut self, genes:&[GeneDistribution])
+    {
+        let (L, n) = (self.L as usize, self.n);
+        let rng = &mut self.rng;
+        self.population = (0..n).map(|_| {
+            let data = (0..L).filter(|&k| gene(genes, k).sample_bit(rng)).fold(0u64, |data, k| data | 1 << k);
+            Chromosome {data, fitness:0.0, N:n, violation:0.0}
+        }).collect::<Population>();
+        self.stale = None;
+    }
+}
+
+impl RealRun
+{
+    /// Re-draws the population, gene `i` from `genes[i]` within its bounds. A single
+    /// distribution covers every gene.
+    pub fn initialize(&mut self, genes:&[GeneDistribution])
+    {
+        let rng = &mut self.rng;
+        let bounds = &self.context;
+        for ind in self.population.iter_mut() {
+            *ind = RealChromosome::new(bounds.iter().enumerate().map(|(i, &(low, high))| gene(genes, i).sample(low, high, rng)).collect());
+        }
+    }
+}
+
+impl BitRun
+{
+    /// Re-draws the population, bit `i` from `genes[i]`. A single distribution covers every bit.
+    pub fn initialize(&mut self, genes:&[GeneDistribution])
+    {
+        let rng = &mut self.rng;
+        for ind in self.population.iter_mut() {
+            let mut genome = BitGenome::new(ind.len());
+            (0..genome.len()).for_each(|i| genome.set(i, gene(genes, i).sample_bit(rng)));
+            *ind = genome;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    #[test]
+    fn genes_follow_their_distributions()
+    {
+        let mut real = RealRun::new(vec![(-5.0, 5.0), (0.0, 10.0)], 200, |x:&[f64]| x[0]);
+        real.initialize(&[GeneDistribution::Normal{mean:2.0, sd:0.5}, GeneDistribution::custom(|_| 42.0)]);
+        let mean = real.population.iter().map(|ind| ind.genes[0]).sum::<f64>() / 200.0;
+        assert!((mean - 2.0).abs() < 0.2, "mean {}", mean);
+        assert!(real.population.iter().all(|ind| ind.genes[1] == 10.0));
+        let seeded = |seed| {
+            let mut real = RealRun::with_seed(vec![(-5.0, 5.0); 3], 20, seed, |x:&[f64]| x[0]);
+            real.initialize(&[GeneDistribution::Normal{mean:1.0, sd:2.0}]);
+            real.population
+        };
+        assert_eq!(seeded(3), seeded(3));
+        assert_ne!(seeded(3), seeded(4));
+
+        let mut bits = BitRun::new(70, 4, |genome:&BitGenome| genome.count_ones() as f64);
+        bits.initialize(&[GeneDistribution::custom(|_| 1.0), GeneDistribution::custom(|_| 0.0)]);
+        assert!(bits.population.iter().all(|genome| genome.count_ones() == 1 && genome.get(0)));
+        let seeded = |seed| {
+            let mut bits = BitRun::new(30, 10, |genome:&BitGenome| genome.count_ones() as f64);
+            bits.set_seed(seed);
+            bits.initialize(&[GeneDistribution::Normal{mean:0.5, sd:0.3}]);
+            bits.population
+        };
+        assert_eq!(seeded(3), seeded(3));
+
+        let mut test_run = Run::with_seed(0.6, 0.05, 12, 10, 4, 1).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.initialize(&[GeneDistribution::Normal{mean:0.9, sd:0.1}]);
+        assert!(test_run.population.data.iter().all(|&data| data < 1 << 12 && data.count_ones() >= 9));
+    }
+}
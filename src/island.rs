@@ -0,0 +1,269 @@
+//! Island model: several runs evolve on their own and every `interval` generations send copies
+//! of their best individuals to their neighbours, where they replace the worst once the next
+//! generation is evaluated. Migrants are taken from the populations evaluated in the last
+//! generation, so migration costs no extra evaluations. Islands keep diversity longer than one
+//! panmictic population of the same total size.
+use crate::budget::EvaluationBudget;
+use crate::events::LifecycleEvent;
+use crate::seeds::SeedSequence;
+use crate::{Chromosome, Run};
+use rand::prelude::*;
+use std::thread;
+
+/// Which islands each island sends its migrants to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology
+{
+    /// Island `i` sends to island `i + 1`, the last one to the first.
+    Ring,
+    /// Every island sends to every other.
+    FullyConnected,
+    /// Every island sends to one other island drawn at each migration.
+    Random,
+}
+
+#[derive(Debug, Clone)]
+pub struct IslandRun
+{
+    pub topology:Topology,
+    /// Generations between migrations.
+    pub interval:u32,
+    /// Individuals each island sends per destination.
+    pub migrants:usize,
+    /// Evolve the islands on separate threads between migrations.
+    pub parallel:bool,
+    islands:Vec<Run>,
+    rng:StdRng,
+}
+
+impl IslandRun
+{
+    /// Panics on an empty `islands` or a zero `interval`.
+    pub fn new(islands:Vec<Run>, topology:Topology, interval:u32, migrants:usize) -> Self
+    {
+        assert!(!islands.is_empty(), "an island model needs at least one island");
+        assert!(interval > 0, "the migration interval must be at least one generation");
+        IslandRun {topology, interval, migrants, parallel:false, islands, rng:StdRng::from_entropy()}
+    }
+
+    /// `count` islands built by `build` from seeds labelled `"island"` in `seeds`, with the
+    /// random topology seeded from `"topology"`, so the model reproduces from the master seed.
+    pub fn seeded<F>(seeds:&mut SeedSequence, count:usize, build:F, topology:Topology, interval:u32, migrants:usize) -> Self
+        where F:Fn(u64) -> Run
+    {
+        let islands = (0..count as u64).map(|index| build(seeds.derive("island", index))).collect();
+        let mut model = IslandRun::new(islands, topology, interval, migrants);
+        model.set_seed(seeds.derive("topology", 0));
+        model
+    }
+
+    /// Seeds the draws of the random topology, the islands keep their own RNGs.
+    pub fn set_seed(&mut self, seed:u64)
+    {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Shares `budget` among all islands, `run` returns early once it is spent.
+    pub fn set_evaluation_budget(&mut self, budget:EvaluationBudget)
+    {
+        for island in &mut self.islands {
+            island.set_evaluation_budget(budget.clone());
+        }
+    }
+
+    pub fn islands(&self) -> &[Run]
+    {
+        &self.islands
+    }
+
+    /// Generations run so far, the same on every island unless an evaluation budget ran out.
+    pub fn generation(&self) -> u32
+    {
+        self.islands[0].generation()
+    }
+
+    /// Best individual over all islands, by the first island's objective.
+    pub fn best(&self) -> Option<&Chromosome>
+    {
+        let objective = self.islands[0].objective();
+        self.islands.iter().filter_map(Run::best).min_by(|a, b| objective.best_first(a.fitness, b.fitness))
+    }
+
+    /// Runs `iterations` more generations on every island, migrating after each generation
+    /// that is a multiple of `interval`, until a shared evaluation budget is spent. Panics like
+    /// `Run::run` on any `GaError`.
+    pub fn run(&mut self, iterations:u32)
+    {
+        let end = self.generation() + iterations;
+        while self.generation() < end {
+            let next = ((self.generation() / self.interval + 1) * self.interval).min(end);
+            self.evolve(next - self.generation());
+            if self.islands.iter().any(Run::budget_exhausted) {
+                break;
+            }
+            if next.is_multiple_of(self.interval) {
+                self.migrate();
+            }
+        }
+    }
+
+    fn evolve(&mut self, generations:u32)
+    {
+        let migrants = self.migrants;
+        let step = |island:&mut Run| {
+            island.emigration = migrants;
+            for _ in 0..generations {
+                island.step();
+                if island.budget_exhausted() {
+                    break;
+                }
+            }
+        };
+        if self.parallel {
+            thread::scope(|scope| {
+                for island in &mut self.islands {
+                    scope.spawn(move || step(island));
+                }
+            });
+        }else{
+            self.islands.iter_mut().for_each(step);
+        }
+    }
+
+    fn destinations(&mut self, from:usize) -> Vec<usize>
+    {
+        let count = self.islands.len();
+        match self.topology {
+            Topology::Ring => vec![(from + 1) % count],
+            Topology::FullyConnected => (0..count).filter(|&to| to != from).collect(),
+            Topology::Random => vec![(from + self.rng.gen_range(1..count)) % count],
+        }
+    }
+
+    // emigrants are picked from the last evaluated population of each island, so migrating
+    // costs no evaluations
+    fn migrate(&mut self)
+    {
+        if self.islands.len() < 2 || self.migrants == 0 {
+            return;
+        }
+        let mut arriving:Vec<Vec<Chromosome>> = vec![Vec::new(); self.islands.len()];
+        let mut senders:Vec<Vec<(usize, usize)>> = vec![Vec::new(); self.islands.len()];
+        for from in 0..self.islands.len() {
+            let emigrants:Vec<Chromosome> = self.islands[from].emigrants.iter().take(self.migrants).cloned().collect();
+            for to in self.destinations(from) {
+                arriving[to].extend_from_slice(&emigrants);
+                senders[to].push((from, emigrants.len()));
+            }
+        }
+        for (to, (island, immigrants)) in self.islands.iter_mut().zip(arriving).enumerate() {
+            island.receive(&immigrants);
+            for &(from, migrants) in &senders[to] {
+                island.emit(LifecycleEvent::Migration{generation:island.period, from, to, migrants});
+            }
+        }
+    }
+}
+
+impl Run
+{
+    // keeps copies of the `emigration` best of the just evaluated population, feasible first
+    pub(crate) fn record_emigrants(&mut self)
+    {
+        if self.emigration == 0 {
+            return;
+        }
+        let (fitness, violation) = (&self.population.fitness, &self.population.violation);
+        let mut order:Vec<usize> = (0..fitness.len()).collect();
+        order.sort_by(|&a, &b| (violation[b] <= 0.0).cmp(&(violation[a] <= 0.0))
+            .then(self.objective.best_first(fitness[a], fitness[b])));
+        self.emigrants = order.into_iter().take(self.emigration).map(|slot| self.population.get(slot)).collect();
+    }
+
+    // queues `immigrants` with the fitness they were evaluated to at home, until the next
+    // generation has evaluated this population
+    pub(crate) fn receive(&mut self, immigrants:&[Chromosome])
+    {
+        self.immigrants.extend_from_slice(immigrants);
+    }
+
+    // the queued immigrants replace the worst members, the best of them if more arrived than
+    // fit, keeping their fitness like elites do
+    pub(crate) fn admit_immigrants(&mut self)
+    {
+        if self.immigrants.is_empty() {
+            return;
+        }
+        let mut immigrants = std::mem::take(&mut self.immigrants);
+        let objective = self.objective;
+        immigrants.sort_by(|a, b| (b.violation <= 0.0).cmp(&(a.violation <= 0.0)).then(objective.best_first(a.fitness, b.fitness)));
+        immigrants.truncate(self.population.len());
+        let slots = self.worst(immigrants.len());
+        for (slot, ind) in slots.into_iter().zip(immigrants) {
+            self.population.data[slot] = ind.data;
+            self.population.fitness[slot] = ind.fitness;
+            self.population.violation[slot] = ind.violation;
+        }
+        self.total_fitness = self.selection_weights().iter().sum();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    fn islands(count:u64) -> Vec<Run>
+    {
+        (0..count).map(|seed| {
+            let mut run = Run::with_seed(0.6, 0.05, 16, 10, 8, seed).unwrap();
+            run.set_fitness(problems::onemax_fitness);
+            run
+        }).collect()
+    }
+
+    #[test]
+    fn parallel_islands_match_sequential()
+    {
+        for topology in [Topology::Ring, Topology::FullyConnected, Topology::Random] {
+            let mut sequential = IslandRun::new(islands(4), topology, 5, 2);
+            sequential.set_seed(9);
+            let mut parallel = sequential.clone();
+            parallel.parallel = true;
+            sequential.run(23);
+            parallel.run(23);
+            assert_eq!(parallel.generation(), 23);
+            assert_eq!(sequential.best(), parallel.best());
+            // everything but the timings
+            let outputs = |model:&IslandRun| -> Vec<Vec<(u64, f64)>> {
+                model.islands().iter().map(|run| run.history().iter().map(|s| (s.ind_out, s.variance)).collect()).collect()
+            };
+            assert_eq!(outputs(&sequential), outputs(&parallel));
+        }
+    }
+
+    #[test]
+    fn migrants_replace_the_worst()
+    {
+        let mut model = IslandRun::new(islands(3), Topology::Ring, 1, 2);
+        model.islands[0].population.data.fill(0xffff);
+        model.islands[1].population.data.fill(0);
+        model.run(1);
+        assert_eq!(model.islands[1].immigrants.iter().map(|ind| (ind.data, ind.fitness)).collect::<Vec<_>>(), [(0xffff, 16.0); 2]);
+        model.islands[1].step();
+        let stats = &model.islands[1].history()[1];
+        assert_eq!((stats.best_fitness, stats.best.as_ref().unwrap().data), (16.0, 0xffff));
+        assert_eq!(model.islands.iter().map(Run::evaluations).sum::<usize>(), 40);
+    }
+
+    #[test]
+    fn the_best_arrivals_are_kept()
+    {
+        let mut run = islands(1).pop().unwrap();
+        let arrivals:Vec<Chromosome> = (0..12u32).map(|k| Chromosome {data:(1 << k) - 1, fitness:k as f64, N:10, violation:0.0}).collect();
+        run.receive(&arrivals);
+        run.step();
+        let stats = &run.history()[0];
+        assert_eq!((stats.best_fitness, stats.worst_fitness, stats.mean_fitness), (11.0, 2.0, 6.5));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{Chromosome, Objective, Run};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry
+{
+    pub data:u64,
+    pub fitness:f64,
+    pub objective:Objective,
+    pub Pcross:f32,
+    pub Pmut:f32,
+    pub L:u8,
+    pub n:usize,
+    pub z:u8,
+    pub generations:u32,
+    /// Seconds since the unix epoch.
+    pub recorded_at:u64,
+}
+
+/// Best solution per problem name across every run recorded on this machine, stored as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard
+{
+    entries:BTreeMap<String, LeaderboardEntry>,
+}
+
+impl Leaderboard
+{
+    /// `$HOME/.genetic_alg/leaderboard.json`, or `leaderboard.json` in the working directory
+    /// when no home directory is set.
+    pub fn default_path() -> PathBuf
+    {
+        match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".genetic_alg").join("leaderboard.json"),
+            None => PathBuf::from("leaderboard.json"),
+        }
+    }
+
+    /// Reads the leaderboard at `path`, a missing file is an empty leaderboard.
+    pub fn load(path:&Path) -> Result<Self, Box<dyn Error>>
+    {
+        if !path.exists() {
+            return Ok(Leaderboard::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path:&Path) -> Result<(), Box<dyn Error>>
+    {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, problem:&str) -> Option<&LeaderboardEntry>
+    {
+        self.entries.get(problem)
+    }
+
+    pub fn problems(&self) -> impl Iterator<Item = &str>
+    {
+        self.entries.keys().map(|k| k.as_str())
+    }
+
+    /// Records `entry` if it beats the current record for `problem`, returns true if it did.
+    pub fn submit(&mut self, problem:&str, entry:LeaderboardEntry) -> bool
+    {
+        let better = match self.entries.get(problem) {
+            Some(old) => entry.objective.is_better(entry.fitness, old.fitness),
+            None => true,
+        };
+        if better {
+            self.entries.insert(problem.to_string(), entry);
+        }
+        better
+    }
+}
+
+impl Run
+{
+    /// Updates the leaderboard at `path` under `problem` at the end of every `run`.
+    pub fn set_leaderboard(&mut self, path:impl Into<PathBuf>, problem:&str)
+    {
+        self.leaderboard = Some((path.into(), problem.to_string()));
+    }
+
+    fn leaderboard_entry(&self, best:&Chromosome) -> LeaderboardEntry
+    {
+        let recorded_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        LeaderboardEntry {
+            data:best.data,
+            fitness:best.fitness,
+            objective:self.objective,
+            Pcross:self.Pcross,
+            Pmut:self.Pmut,
+            L:self.L,
+            n:self.n,
+            z:self.z,
+            generations:self.period,
+            recorded_at,
+        }
+    }
+
+    /// Submits the best individual seen so far, returns true if it set a new record.
+    pub fn update_leaderboard(&self) -> Result<bool, Box<dyn Error>>
+    {
+        let (Some((path, problem)), Some(best)) = (&self.leaderboard, &self.best) else {
+            return Ok(false);
+        };
+        let mut leaderboard = Leaderboard::load(path)?;
+        let improved = leaderboard.submit(problem, self.leaderboard_entry(best));
+        if improved {
+            leaderboard.save(path)?;
+        }
+        Ok(improved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaderboard_keeps_record_per_problem()
+    {
+        let path = std::env::temp_dir().join(format!("ga_leaderboard_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.set_leaderboard(&path, "cournot");
+        test_run.run(10).unwrap();
+        let record = Leaderboard::load(&path).unwrap().get("cournot").cloned().unwrap();
+        assert_eq!(record.fitness, test_run.best().unwrap().fitness);
+
+        let mut worse = record.clone();
+        worse.fitness -= 1.0;
+        let mut leaderboard = Leaderboard::load(&path).unwrap();
+        assert!(!leaderboard.submit("cournot", worse));
+        assert!(leaderboard.submit("other", record.clone()));
+        assert_eq!(leaderboard.problems().collect::<Vec<_>>(), vec!["cournot", "other"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+//! Social-learning hybrids for the market model: after the genetic operators each firm applies a
+//! simple learning rule with probability `rate`, so pure GA dynamics can be compared with
+//! adaptive agents.
+use crate::{sampling, Run};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LearningRule
+{
+    /// Moves towards the best response to the other firms' current output, keeping `inertia`
+    /// of the old quantity (0 jumps straight to the best response).
+    BestResponse{inertia:f64},
+    /// Copies the quantity of the generation's best firm, as evaluated before reproduction.
+    Imitation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Learning
+{
+    pub rule:LearningRule,
+    /// Probability that a firm learns in a given generation.
+    pub rate:f64,
+}
+
+impl Run
+{
+    pub fn set_learning(&mut self, learning:Learning)
+    {
+        self.learning = Some(learning);
+    }
+
+    // `leader` is the genome of the generation's best firm, recorded before reproduction
+    // replaced the evaluated population
+    pub(crate) fn learn(&mut self, leader:Option<u64>)
+    {
+        let Some(learning) = self.learning else { return };
+        let firms = sampling::bernoulli_indices(&mut self.rng, learning.rate, self.population.len());
+        if let Some(stale) = &mut self.stale {
+            stale.extend(&firms);
+            stale.sort();
+            stale.dedup();
+        }
+        match learning.rule {
+            LearningRule::BestResponse{inertia} => {
+                let market = self.market;
+                let decoding = self.decoding.unwrap_or_default();
+                let mut data_sum = self.phenotypes().data_sum();
+                for i in firms {
+                    let q = decoding.decode(self.population.data[i]);
+                    let others = data_sum.saturating_sub(q) as f64;
+                    let best_response = (market.intercept - self.firm_cost(i) - market.cross_slope*others)
+                        / (2.0*market.own_slope);
+                    let target = inertia*q as f64 + (1.0 - inertia)*best_response;
+                    self.population.data[i] = decoding.encode(target.round().max(0.0) as u64, self.L);
+                    data_sum = data_sum.saturating_sub(q).saturating_add(decoding.decode(self.population.data[i]));
+                }
+            }
+            LearningRule::Imitation => {
+                if let Some(quantity) = leader {
+                    for i in firms {
+                        self.population.data[i] = quantity;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn best_response_reaches_nash()
+    {
+        let mut test_run = Run::new(0.0, 0.0, 12, 10, 2).unwrap();
+        test_run.set_learning(Learning {rule:LearningRule::BestResponse{inertia:0.5}, rate:1.0});
+        test_run.run(200).unwrap();
+        let (nash, _) = test_run.equilibria();
+        let output = test_run.population.data_sum() as f64;
+        assert!((output - nash.output).abs() / nash.output < 0.01, "output {} nash {}", output, nash.output);
+    }
+
+    #[test]
+    fn imitation_spreads_the_best_quantity()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 10, 10, 2, 3).unwrap();
+        test_run.set_learning(Learning {rule:LearningRule::Imitation, rate:1.0});
+        test_run.run(1).unwrap();
+        let leader = test_run.history()[0].best.as_ref().unwrap().data;
+        assert!(test_run.population.data.iter().all(|q| *q == leader));
+    }
+}
@@ -1,43 +1,220 @@
+#![allow(non_snake_case)]
+
 use rand::prelude::*;
-use csv::Writer;
-use std::error::Error;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub mod aco;
+pub mod allocation;
+pub mod archive;
+pub mod benchmarks;
+pub mod bits;
+pub mod bootstrap;
+pub mod budget;
+pub mod builder;
+pub mod checkpoint;
+pub mod coco;
+pub mod coloring;
+pub mod config;
+pub mod constraints;
+pub mod cournot;
+pub mod curve;
+pub mod entry;
+pub mod error;
+pub mod events;
+pub mod evolution;
+pub mod export;
+pub mod fixed;
+pub mod genome;
+pub mod groups;
+pub mod guard;
+pub mod guided;
+pub mod hall_of_fame;
+pub mod harness;
+pub mod health;
+pub mod heuristics;
+pub mod import;
+pub mod init;
+pub mod island;
+pub mod leaderboard;
+pub mod learning;
+pub mod local_search;
+pub mod maxsat;
+pub mod memory;
+pub mod metaheuristics;
+pub mod objective;
+pub mod observer;
+pub mod operators;
+pub mod ops;
+pub mod optimizer;
+pub mod parents;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
+pub mod pbil;
+pub mod permutation;
+#[cfg(feature = "plotting")]
+pub mod plot;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+mod population;
+pub mod portfolio;
+pub mod problems;
+pub mod racing;
+pub mod real;
+pub mod replicator;
+pub mod result;
+pub mod routing;
+mod sampling;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod seeds;
+pub mod shocks;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod stats;
+pub mod steady;
+pub mod sweep;
+pub mod vrp;
+
+pub use aco::AntColony;
+pub use allocation::{Allocation, Rationing};
+pub use archive::{save_archive_to_csv, ArchiveEntry, EpsilonArchive};
+pub use benchmarks::Benchmark;
+pub use bits::{BitCrossover, BitGenome, BitRun};
+pub use bootstrap::MAX_EXHAUSTIVE_LEN;
+pub use budget::EvaluationBudget;
+pub use builder::RunBuilder;
+pub use checkpoint::{Checkpoint, CheckpointDelta, CheckpointLog, GenomeCheckpoint};
+pub use coco::CocoExporter;
+pub use coloring::{Graph, GraphColoring};
+pub use config::{BudgetEstimate, ConfigError, RunConfig};
+pub use constraints::{AdaptivePenalty, Constraint, ConstraintHandling};
+pub use cournot::{save_firm_stats_to_csv, Decoding, Equilibrium, FirmStats, Market, Welfare};
+pub use curve::{CurveFit, FitResult};
+pub use entry::EntryExit;
+pub use error::GaError;
+pub use events::{LifecycleEvent, RunSummary};
+pub use evolution::{save_genome_stats_to_csv, Genome, GenomeConstraint, GenomeRun, GenomeStats};
+pub use export::{save_run_to_json, JsonlWriter, RunManifest, RunRecord};
+pub use fixed::FixedChromosome;
+pub use genome::{GenomeBuildHasher, GenomeId, GenomeInterner};
+pub use groups::MatingGroups;
+pub use guard::{NonFiniteFitness, NonFinitePolicy, PanicPolicy};
+pub use guided::{allele_frequencies, GuidedCrossover};
+pub use hall_of_fame::HallOfFame;
+pub use harness::{format_table, trace_of, SuccessReport, Target, Trace};
+pub use health::HealthWarning;
+pub use heuristics::{suggest_parameters, SuggestedParameters};
+pub use import::{load_run_from_json, load_stats_from_csv, load_stats_from_jsonl};
+pub use init::{GeneDistribution, GeneSampler};
+pub use island::{IslandRun, Topology};
+pub use leaderboard::{Leaderboard, LeaderboardEntry};
+pub use learning::{Learning, LearningRule};
+pub use local_search::RefinedSolution;
+pub use maxsat::MaxSat;
+pub use memory::MemoryUsage;
+pub use metaheuristics::{GreatDeluge, HarmonySearch, LateAcceptance};
+pub use objective::Objective;
+pub use observer::{GenomeObserver, Observer};
+pub use operators::{Crossover, Fitness, Mutation, Selection, Termination};
+pub use ops::MutationScheme;
+pub use optimizer::Optimizer;
+pub use parents::{save_parents_to_csv, ParentSelection};
+#[cfg(feature = "parquet")]
+pub use parquet_export::{save_population_to_parquet, save_stats_to_parquet};
+pub use pbil::Pbil;
+pub use permutation::{MutationMix, PermutationChromosome, PermutationCrossover, PermutationMutation, PermutationRun};
+#[cfg(feature = "plotting")]
+pub use plot::plot_convergence;
+#[cfg(feature = "plugins")]
+pub use plugin::{GenomePair, Plugin, PluginCrossover, PluginMutation, PluginSelection};
+pub use population::PopulationView;
+pub use portfolio::Portfolio;
+pub use problems::{LeadingOnes, Problem, ProblemFitness, ProblemRegistry};
+pub use racing::{Race, RaceResult};
+pub use real::{Initialization, RealChromosome, RealCrossover, RealMutation, RealRun};
+pub use replicator::{replicator_dynamics, save_replicator_comparison, ReplicatorPoint};
+pub use result::{RunResult, StopReason};
+pub use routing::{DistanceMatrix, RouteImprovement, RouteMove, RouteSearch};
+#[cfg(feature = "scripting")]
+pub use script::ScriptFitness;
+pub use seeds::{SeedDerivation, SeedSequence};
+pub use shocks::{MarketChange, RandomShocks, Shock, ShockSchedule};
+#[cfg(feature = "sqlite")]
+pub use sqlite::ExperimentStore;
+pub use stats::{effective_size, mean_hamming_distance, save_stats_to_csv, GenerationStats, PhaseTimings, PopulationStats, RetentionPolicy, StatsWriter};
+pub use steady::ReplacementPolicy;
+pub use sweep::{save_sweep_summary_to_csv, summarize, Sweep, SweepRun, SweepSummary};
+pub use vrp::{Cvrp, VrpSolution};
 
+use guard::{panic_message, EvaluationFailure};
+use population::Population;
 
+/// Fitness of a chromosome given the sum of `data` over the whole population.
+pub type FitnessFn = fn(&Chromosome, u64) -> f64;
 
-#[derive(Debug, Clone, PartialEq)]
+/// Cheap check run before the fitness function, `false` rejects the chromosome unevaluated.
+pub type Prefilter = fn(&Chromosome) -> bool;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Chromosome
 {
     pub data:u64,
     pub fitness:f64,
     pub N:usize,
+    pub violation:f64,
 }
 
 impl Chromosome
 {
     pub fn new(N:usize)-> Self
     {
-        let mut rng = rand::thread_rng();
+        Self::new_with(N, &mut rand::thread_rng())
+    }
+
+    /// Like `new` but drawing from `rng`.
+    pub fn new_with<R:Rng + ?Sized>(N:usize, rng:&mut R)-> Self
+    {
         let random_number = rng.gen_range(0..1023);
-        Chromosome {data:random_number, fitness:0.0 , N:N}
+        Chromosome {data:random_number, fitness:0.0 , N, violation:0.0}
     }
 
-    fn calculate_fitness(&mut self, data_sum:u64) -> f64
+    /// Uniformly random genome of `L` bits, `N` is set to the genome length.
+    pub fn random(L:u8)-> Self
     {
-        if let Some(diff) = data_sum.checked_sub(self.data){
-        
-            let fitness = ((20000 as i128 -  self.data as i128) as f64 - 0.52*diff as f64)*self.data as f64;
-            if fitness < 0.0{
-                return 0.0;
-            }else{
-                return fitness;
-            }
-        
-        }else{
-            return 0.0;
-        }
+        Self::random_with(L, &mut rand::thread_rng())
     }
+
+    /// Like `random` but drawing from `rng`.
+    pub fn random_with<R:Rng + ?Sized>(L:u8, rng:&mut R)-> Self
+    {
+        let data = if L >= 64 { rng.gen() } else { rng.gen_range(0..1u64 << L) };
+        Chromosome {data, fitness:0.0, N:L as usize, violation:0.0}
+    }
+
+    fn calculate_fitness(&self, data_sum:u64) -> f64
+    {
+        Market::default().profit(self.data, data_sum)
+    }
+}
+
+/// Sum of `data` over `population`, saturating instead of overflowing for long genomes.
+pub fn data_sum(population:&[Chromosome]) -> u64
+{
+    population.iter().fold(0, |sum, x| sum.saturating_add(x.data))
+}
+
+pub(crate) fn sum_data(data:&[u64]) -> u64
+{
+    data.iter().fold(0, |sum, x| sum.saturating_add(*x))
 }
 
 #[derive(Debug, Clone)]
@@ -49,174 +226,890 @@ pub struct Run
     n:usize,
     z:u8,
     period:u32,
-    population:Vec<Chromosome>,
+    population:Population,
     total_fitness:f64,
     data_sum:u64,
+    archive:Option<EpsilonArchive>,
+    hall_of_fame:Option<HallOfFame>,
+    constraints:Vec<Constraint>,
+    penalty:f64,
+    // `penalty` as configured, before any adaptive change
+    initial_penalty:f64,
+    constraint_handling:ConstraintHandling,
+    adaptive_penalty:Option<AdaptivePenalty>,
+    history:Vec<GenerationStats>,
+    warnings:Vec<HealthWarning>,
+    warning_callback:Option<fn(&HealthWarning)>,
+    non_finite_policy:NonFinitePolicy,
+    non_finite_count:usize,
+    panic_policy:PanicPolicy,
+    panic_count:usize,
+    evaluation_timeout:Option<Duration>,
+    timeout_count:usize,
+    objective:Objective,
+    best:Option<Chromosome>,
+    fitness:Arc<dyn Fitness>,
+    // set by set_fitness and set_fitness_function, market shocks then leave the fitness alone
+    custom_fitness:bool,
+    selection:Option<Arc<dyn Selection>>,
+    crossover:Option<Arc<dyn Crossover>>,
+    mutation:Option<Arc<dyn Mutation>>,
+    termination:Vec<Arc<dyn Termination>>,
+    observers:Vec<Arc<dyn Observer>>,
+    stop_requested:bool,
+    // generations since `best` last improved
+    stagnant_generations:u32,
+    // smallest change of the best fitness that counts as an improvement
+    epsilon:f64,
+    budget:Option<EvaluationBudget>,
+    leaderboard:Option<(PathBuf, String)>,
+    event_callback:Option<fn(&LifecycleEvent)>,
+    low_memory_path:Option<String>,
+    retention:Option<RetentionPolicy>,
+    firm_costs:Option<Vec<f64>>,
+    market:Market,
+    learning:Option<Learning>,
+    shocks:Option<ShockSchedule>,
+    entry_exit:Option<EntryExit>,
+    decoding:Option<Decoding>,
+    elitism:usize,
+    prefilter:Option<(Prefilter, f64)>,
+    rejected:usize,
+    steady_state:Option<ReplacementPolicy>,
+    // slots changed by a steady-state iteration since the last evaluation, `None` when every
+    // member needs evaluating
+    stale:Option<Vec<usize>>,
+    parent_log:Option<Vec<ParentSelection>>,
+    mutation_scheme:MutationScheme,
+    mating_pool:f64,
+    mating_groups:Option<MatingGroups>,
+    guided_crossover:Option<GuidedCrossover>,
+    // set by `IslandRun`: how many of the best to keep in `emigrants` every generation, and the
+    // individuals that arrived since the last one
+    emigration:usize,
+    emigrants:Vec<Chromosome>,
+    immigrants:Vec<Chromosome>,
+    rng:ChaCha12Rng,
+    seed:Option<u64>,
+    evaluations:usize,
+    last_step:Option<GenomeStats>,
+    zero_fitness_error:bool,
 }
 
 impl Run{
-    pub fn new(Pcross:f32, Pmut:f32, L:u8, n:usize, z:u8)-> Self
+    /// A run seeded from the operating system's entropy, so no two runs are alike. Every problem
+    /// found in the parameters is returned as `GaError::Config`, see `RunConfig::validate`.
+    pub fn new(Pcross:f32, Pmut:f32, L:u8, n:usize, z:u8)-> Result<Self, GaError>
+    {
+        RunConfig {Pcross, Pmut, L, n, z, ..RunConfig::default()}.validate()?;
+        Ok(Self::unchecked(Pcross, Pmut, L, n, z, None))
+    }
+
+    /// A reproducible run: the initial population and every selection, crossover, mutation,
+    /// shock, entry and learning draw come from an RNG seeded with `seed`, so two runs with
+    /// the same seed and settings produce the same history. Checks the parameters like `new`.
+    pub fn with_seed(Pcross:f32, Pmut:f32, L:u8, n:usize, z:u8, seed:u64)-> Result<Self, GaError>
+    {
+        RunConfig {Pcross, Pmut, L, n, z, ..RunConfig::default()}.validate()?;
+        Ok(Self::unchecked(Pcross, Pmut, L, n, z, Some(seed)))
+    }
+
+    // for parameters that were validated already
+    pub(crate) fn unchecked(Pcross:f32, Pmut:f32, L:u8, n:usize, z:u8, seed:Option<u64>)-> Self
+    {
+        let rng = seed.map_or_else(ChaCha12Rng::from_entropy, ChaCha12Rng::seed_from_u64);
+        Self::from_rng(Pcross, Pmut, L, n, z, rng, seed)
+    }
+
+    fn from_rng(Pcross:f32, Pmut:f32, L:u8, n:usize, z:u8, mut rng:ChaCha12Rng, seed:Option<u64>)-> Self
+    {
+        let population:Population = (0..n).map(|_| Chromosome {N:n, ..Chromosome::random_with(L, &mut rng)}).collect();
+        Run{Pcross, Pmut, L, n, z, period:0, population, total_fitness:0.0, data_sum:0, archive:None, hall_of_fame:None,
+            constraints:Vec::new(), penalty:1.0, initial_penalty:1.0, constraint_handling:ConstraintHandling::Penalty, adaptive_penalty:None,
+            history:Vec::new(), warnings:Vec::new(), warning_callback:None,
+            non_finite_policy:NonFinitePolicy::Worst, non_finite_count:0,
+            panic_policy:PanicPolicy::Propagate, panic_count:0, evaluation_timeout:None, timeout_count:0,
+            objective:Objective::Maximize, best:None,
+            fitness:Arc::new(problems::cournot_fitness as FitnessFn), custom_fitness:false,
+            selection:None, crossover:None, mutation:None, termination:Vec::new(), observers:Vec::new(), stop_requested:false, stagnant_generations:0, epsilon:0.0, budget:None, leaderboard:None, event_callback:None,
+            low_memory_path:None, retention:None, firm_costs:None,
+            market:Market::default(), learning:None, shocks:None, entry_exit:None, decoding:None, elitism:0,
+            prefilter:None, rejected:0, steady_state:None, stale:None,
+            parent_log:None, mutation_scheme:MutationScheme::PerIndividual,
+            mating_pool:1.0, mating_groups:None,
+            guided_crossover:None, emigration:0, emigrants:Vec::new(), immigrants:Vec::new(), rng, seed, evaluations:0, last_step:None,
+            zero_fitness_error:false}
+    }
+
+    pub fn set_archive(&mut self, archive:EpsilonArchive)
+    {
+        self.archive = Some(archive);
+    }
+
+    pub fn archive(&self) -> Option<&EpsilonArchive>
+    {
+        self.archive.as_ref()
+    }
+
+    /// Keeps the `capacity` best distinct genotypes evaluated from now on, see `HallOfFame`.
+    pub fn set_hall_of_fame(&mut self, capacity:usize)
+    {
+        self.hall_of_fame = Some(HallOfFame::new(capacity));
+    }
+
+    pub fn hall_of_fame(&self) -> Option<&HallOfFame>
+    {
+        self.hall_of_fame.as_ref()
+    }
+
+    pub fn add_constraint(&mut self, constraint:Constraint)
+    {
+        self.constraints.push(constraint);
+    }
+
+    pub fn set_penalty(&mut self, penalty:f64)
+    {
+        self.penalty = penalty;
+        self.initial_penalty = penalty;
+    }
+
+    pub fn set_constraint_handling(&mut self, constraint_handling:ConstraintHandling)
+    {
+        self.constraint_handling = constraint_handling;
+    }
+
+    pub fn set_adaptive_penalty(&mut self, adaptive_penalty:AdaptivePenalty)
+    {
+        self.adaptive_penalty = Some(adaptive_penalty);
+    }
+
+    pub fn set_non_finite_policy(&mut self, policy:NonFinitePolicy)
+    {
+        self.non_finite_policy = policy;
+    }
+
+    /// How panics in the fitness function are handled, by default they unwind out of the run.
+    pub fn set_panic_policy(&mut self, policy:PanicPolicy)
+    {
+        self.panic_policy = policy;
+    }
+
+    /// Gives each fitness evaluation at most `timeout`, for external or expensive fitness
+    /// functions. Every evaluation then runs on its own thread; one that overruns gets the worst
+    /// fitness and is counted in `GenerationStats::timeouts`, its thread is not stopped.
+    pub fn set_evaluation_timeout(&mut self, timeout:Duration)
+    {
+        self.evaluation_timeout = Some(timeout);
+    }
+
+    /// Stop with `GaError::ZeroFitness` when total fitness is zero, instead of falling back to
+    /// uniform selection and flagging the generation as `degenerate`.
+    pub fn set_zero_fitness_error(&mut self, error:bool)
+    {
+        self.zero_fitness_error = error;
+    }
+
+    /// The `k` best individuals, feasible ones first, skip selection, crossover and mutation
+    /// and are copied unchanged into the next generation, keeping their population slots.
+    pub fn set_elitism(&mut self, k:usize)
+    {
+        self.elitism = k;
+    }
+
+    /// Number of parents selected per generation as a multiple of the population size. The
+    /// next generation is filled from the pool in shuffled passes, so below one parents are
+    /// reused and above one some selected parents leave no offspring.
+    pub fn set_mating_pool(&mut self, factor:f64)
+    {
+        assert!(factor > 0.0 && factor.is_finite(), "mating pool factor must be positive, got {}", factor);
+        self.mating_pool = factor;
+    }
+
+    /// Whether `Pmut` is the chance of one flip per genome or of each bit flipping, see
+    /// `MutationScheme`. Ignored when a mutation operator is set.
+    pub fn set_mutation_scheme(&mut self, scheme:MutationScheme)
+    {
+        self.mutation_scheme = scheme;
+    }
+
+    /// Chromosomes whose decoded phenotype fails `prefilter` get `rejected_fitness` (usually
+    /// `objective().worst_value()`) and no violation, skipping constraints and fitness.
+    pub fn set_prefilter(&mut self, prefilter:Prefilter, rejected_fitness:f64)
+    {
+        self.prefilter = Some((prefilter, rejected_fitness));
+    }
+
+    /// The seed given to `with_seed`, `None` for runs seeded from entropy.
+    pub fn seed(&self) -> Option<u64>
+    {
+        self.seed
+    }
+
+    /// Fitness evaluations so far, including the ones of `refine`, entrants and `top_genomes`,
+    /// not counting the ones the prefilter saved.
+    pub fn evaluations(&self) -> usize
+    {
+        self.evaluations
+    }
+
+    /// Evaluations saved by the prefilter so far.
+    pub fn rejected(&self) -> usize
+    {
+        self.rejected
+    }
+
+    pub fn set_fitness(&mut self, fitness:FitnessFn)
+    {
+        self.fitness = Arc::new(fitness);
+        self.custom_fitness = true;
+    }
+
+    /// Tolerance for comparing best fitness across generations: the best only counts as
+    /// improved, resetting `stagnant_generations`, when it gets better by more than `epsilon`.
+    /// Guards `Stagnation` against jitter in noisy or rounding-prone fitness functions. The
+    /// best individual itself is still the strictly best one seen. Defaults to zero.
+    pub fn set_epsilon(&mut self, epsilon:f64)
+    {
+        assert!(epsilon >= 0.0 && epsilon.is_finite(), "epsilon must be non-negative, got {}", epsilon);
+        self.epsilon = epsilon;
+    }
+
+    pub fn epsilon(&self) -> f64
+    {
+        self.epsilon
+    }
+
+    /// Panics when switching to minimizing with entry and exit set, their thresholds being profits.
+    pub fn set_objective(&mut self, objective:Objective)
+    {
+        assert!(self.entry_exit.is_none() || objective == Objective::Maximize, "entry and exit need a maximizing run");
+        self.objective = objective;
+    }
+
+    pub fn objective(&self) -> Objective
+    {
+        self.objective
+    }
+
+    /// Best individual evaluated so far, feasible ones always rank above infeasible ones.
+    pub fn best(&self) -> Option<&Chromosome>
+    {
+        self.best.as_ref()
+    }
+
+    pub fn penalty(&self) -> f64
+    {
+        self.penalty
+    }
+
+    /// Thins out the in-memory stats of long runs, see `RetentionPolicy`. Use the `generation`
+    /// field of `history` to tell which generations were kept.
+    pub fn set_retention_policy(&mut self, policy:RetentionPolicy)
+    {
+        self.retention = Some(policy);
+    }
+
+    pub fn history(&self) -> &[GenerationStats]
     {
-        let population:Vec<Chromosome> = (0..n).map(|_| Chromosome::new(n)).collect();
-        Run{Pcross:Pcross, Pmut:Pmut, L:L, n:n, z:z, period:0, population:population, total_fitness:0.0, data_sum:0}
+        &self.history
     }
 
     fn calculate_data_sum(&mut self)
     {
-        self.data_sum = self.population.iter()
-        .map(|x| x.data as u64)
-        .sum::<u64>();
+        self.data_sum = self.phenotypes().data_sum();
     }
 
-    fn calculate_iteration_fitness(&mut self)->()
+    pub(crate) fn population_stats(&self, data_sum:u64) -> PopulationStats
     {
-        for ind in &mut self.population
-        {
-            let ind_fitness_old = ind.fitness;
-            ind.fitness = ind.calculate_fitness(self.data_sum);
-            self.total_fitness += ind.fitness - ind_fitness_old;
+        PopulationStats {generation:self.period, size:self.population.len(), data_sum}
+    }
+
+    // the one way genomes get evaluated: a genome the prefilter turns down gets the rejected
+    // fitness and counts in `rejected`, one the budget has no evaluation left for gets the
+    // worst fitness, and any other is evaluated and counts in `evaluations`. `None` if it was
+    // not evaluated, otherwise the outcome of `evaluate_fully`. `cost` is the firm's marginal
+    // cost and `data_sum` the sum of decoded quantities
+    fn evaluate(&mut self, ind:&mut Chromosome, data_sum:u64, cost:f64) -> Option<Result<bool, EvaluationFailure>>
+    {
+        if let Some((prefilter, rejected_fitness)) = self.prefilter {
+            if !prefilter(&Chromosome {data:self.quantity(ind.data), ..ind.clone()}) {
+                self.rejected += 1;
+                ind.fitness = rejected_fitness;
+                ind.violation = 0.0;
+                return None;
+            }
+        }
+        if self.budget.as_ref().is_some_and(|budget| !budget.try_take()) {
+            ind.fitness = self.objective.worst_value();
+            ind.violation = 0.0;
+            return None;
         }
+        self.evaluations += 1;
+        Some(self.evaluate_fully(ind, data_sum, cost))
     }
 
-    fn assign_probability(&self, ind:&Chromosome)->f64
+    // like `evaluate`, the error is a fitness panic caught under the `PanicPolicy` or a timeout,
+    // either of which leaves `ind` with the worst fitness
+    fn evaluate_fully(&self, ind:&mut Chromosome, data_sum:u64, cost:f64) -> Result<bool, EvaluationFailure>
     {
-        ind.fitness/self.total_fitness 
+        let phenotype = Chromosome {data:self.quantity(ind.data), ..ind.clone()};
+        ind.violation = constraints::total_violation(&self.constraints, &phenotype);
+        ind.fitness = match self.call_fitness(&phenotype, data_sum) {
+            Ok(fitness) => fitness,
+            Err(failure) => {
+                ind.fitness = self.objective.worst_value();
+                ind.violation = self.non_finite_policy.repair(ind.violation, f64::MAX, 0.0);
+                return Err(failure);
+            }
+        };
+        if cost != 0.0 {
+            ind.fitness -= cost * phenotype.data as f64;
+        }
+        let non_finite = !ind.fitness.is_finite() || !ind.violation.is_finite();
+        if non_finite && self.non_finite_policy == NonFinitePolicy::Error {
+            return Ok(true);
+        }
+        ind.fitness = self.non_finite_policy.repair(ind.fitness, self.objective.worst_value(), self.objective.best_value());
+        ind.violation = self.non_finite_policy.repair(ind.violation, f64::MAX, 0.0);
+        if self.constraint_handling == ConstraintHandling::Penalty {
+            ind.fitness = self.objective.penalize(ind.fitness, self.penalty*ind.violation);
+        }
+        Ok(non_finite)
     }
 
-    fn select(&self, probabilities:&Vec<f64>)->Chromosome
+    fn call_fitness(&self, phenotype:&Chromosome, data_sum:u64) -> Result<f64, EvaluationFailure>
     {
-        let rand_f:f64 = random();
+        let context = self.population_stats(data_sum);
+        if let Some(timeout) = self.evaluation_timeout {
+            return self.call_fitness_timed(phenotype.clone(), context, timeout);
+        }
+        if self.panic_policy == PanicPolicy::Propagate {
+            return Ok(self.fitness.evaluate(phenotype, &context));
+        }
+        panic::catch_unwind(AssertUnwindSafe(|| self.fitness.evaluate(phenotype, &context)))
+            .map_err(|payload| EvaluationFailure::Panic(panic_message(payload)))
+    }
 
-        let mut cumulative_sum = 0.0;
-        for i in 0..self.n{
-            cumulative_sum += probabilities[i];
-            if cumulative_sum >= rand_f
-            {
-                return self.population[i].clone();
+    // evaluates on a thread of its own, which is left to finish in the background if it overruns
+    fn call_fitness_timed(&self, phenotype:Chromosome, context:PopulationStats, timeout:Duration) -> Result<f64, EvaluationFailure>
+    {
+        let fitness = self.fitness.clone();
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let _ = sender.send(fitness.evaluate(&phenotype, &context));
+        });
+        match receiver.recv_timeout(timeout) {
+            Ok(value) => Ok(value),
+            Err(RecvTimeoutError::Timeout) => Err(EvaluationFailure::Timeout),
+            Err(RecvTimeoutError::Disconnected) => {
+                let payload = handle.join().expect_err("the evaluation thread ended without a result");
+                if self.panic_policy == PanicPolicy::Propagate {
+                    panic::resume_unwind(payload);
+                }
+                Err(EvaluationFailure::Panic(panic_message(payload)))
             }
         }
+    }
 
-        self.population[self.n - 1].clone()
+    fn calculate_iteration_fitness(&mut self) -> Result<(), GaError>
+    {
+        self.non_finite_count = 0;
+        self.panic_count = 0;
+        self.timeout_count = 0;
+        let slots = self.stale.take().unwrap_or_else(|| (0..self.population.len()).collect());
+        for index in slots
+        {
+            let mut ind = self.population.get(index);
+            let outcome = self.evaluate(&mut ind, self.data_sum, self.firm_cost(index));
+            self.population.fitness[index] = ind.fitness;
+            self.population.violation[index] = ind.violation;
+            let Some(outcome) = outcome else { continue };
+            match outcome {
+                Ok(true) => {
+                    self.non_finite_count += 1;
+                    if self.non_finite_policy == NonFinitePolicy::Error {
+                        return Err(NonFiniteFitness {generation:self.period, index, fitness:ind.fitness, violation:ind.violation}.into());
+                    }
+                }
+                Ok(false) => {}
+                Err(EvaluationFailure::Panic(message)) => {
+                    self.panic_count += 1;
+                    if self.panic_policy == PanicPolicy::Abort {
+                        return Err(GaError::FitnessPanic{generation:self.period, index, message});
+                    }
+                }
+                Err(EvaluationFailure::Timeout) => self.timeout_count += 1,
+            }
+        }
+        self.total_fitness = self.selection_weights().iter().sum();
+        Ok(())
     }
 
-    fn recomb(&mut self)->()
+    // returns true if the best individual improved this generation
+    fn update_best(&mut self) -> bool
     {
-        let cumulative_probabilities:Vec<f64> = self.population.iter().map(|x| self.assign_probability(x)).collect(); 
-        
-        let next_gen:Vec<Chromosome> = (0..self.n).map(|_| self.select(&cumulative_probabilities)).collect();
+        let objective = self.objective;
+        let population = &self.population;
+        let previous = self.best.clone();
+        let better = |i:usize, b:&Chromosome| {
+            let (a_feasible, b_feasible) = (population.violation[i] <= 0.0, constraints::is_feasible(b));
+            (a_feasible && !b_feasible) || (a_feasible == b_feasible && objective.is_better(population.fitness[i], b.fitness))
+        };
+        for i in 0..population.len() {
+            if self.best.as_ref().is_none_or(|best| better(i, best)) {
+                self.best = Some(population.get(i));
+            }
+        }
+        match (&previous, &self.best) {
+            (Some(old), Some(new)) => (constraints::is_feasible(new) && !constraints::is_feasible(old))
+                || objective.improves_on(new.fitness, old.fitness, self.epsilon),
+            (None, best) => best.is_some(),
+            (Some(_), None) => false,
+        }
+    }
 
-        self.population = next_gen;
+    // the generation's summary for `Optimizer::stats`
+    fn record_step(&mut self)
+    {
+        let fitness = &self.population.fitness;
+        self.last_step = Some(GenomeStats {
+            generation:self.period,
+            best_fitness:self.objective.best(fitness.iter().copied()).unwrap_or(self.objective.worst_value()),
+            mean_fitness:fitness.iter().sum::<f64>() / fitness.len() as f64,
+            evaluations:self.evaluations,
+            feasible_fraction:self.population.violation.iter().filter(|&&v| v <= 0.0).count() as f64 / fitness.len() as f64,
+        });
     }
 
-    fn pairs(&self, mut old_population: Vec<Chromosome>, rng: &mut ThreadRng) -> Vec<(Chromosome, Chromosome)> {
-        let mut pairs: Vec<(Chromosome, Chromosome)> = Vec::new();
-        let mut paired_indices = vec![false; self.n]; // Track paired chromosomes by index
+    fn elites(&self) -> Vec<usize>
+    {
+        let (fitness, violation) = (&self.population.fitness, &self.population.violation);
+        let mut order:Vec<usize> = (0..fitness.len()).collect();
+        order.sort_by(|&a, &b| (violation[b] <= 0.0).cmp(&(violation[a] <= 0.0))
+            .then(self.objective.best_first(fitness[a], fitness[b])));
+        order.truncate(self.elitism);
+        order
+    }
 
-        for i in 0..self.n {
-            if paired_indices[i] {
-                continue; // Skip already paired chromosomes
+    fn adapt_penalty(&mut self)
+    {
+        if let Some(adaptive) = &mut self.adaptive_penalty {
+            let fitness = &self.population.fitness;
+            let best = (0..fitness.len()).min_by(|&a, &b| self.objective.best_first(fitness[a], fitness[b]));
+            if let Some(best) = best {
+                self.penalty = adaptive.update(self.penalty, self.population.violation[best] <= 0.0);
             }
+        }
+    }
 
-            let mut partner_idx = rng.gen_range(0..self.n);
-            while paired_indices[partner_idx] || partner_idx == i {
-                // Ensure partner is not already paired and not the same as current
-                partner_idx = rng.gen_range(0..self.n);
-            }
+    fn selection_weights(&self) -> Vec<f64>
+    {
+        self.objective.selection_weights(&self.population.fitness)
+    }
 
-            // Mark both as paired
-            paired_indices[i] = true;
-            paired_indices[partner_idx] = true;
+    fn assign_probability(&self, weight:f64)->f64
+    {
+        weight/self.total_fitness 
+    }
 
-            // Push the pair
-            pairs.push((
-                old_population[i].clone(),
-                old_population[partner_idx].clone(),
-            ));
+    // fitness-proportionate selection has nothing to work with, recomb falls back to uniform selection
+    fn selection_degenerate(&self) -> bool
+    {
+        self.constraint_handling == ConstraintHandling::Penalty
+            && !(self.total_fitness > 0.0 && self.total_fitness.is_finite())
+    }
+
+    fn rank_probabilities(&mut self, pf:f64) -> Vec<f64>
+    {
+        let order = constraints::stochastic_ranking_columns(&self.population.fitness, &self.population.violation, pf, self.objective, &mut self.rng);
+        let n = self.n as f64;
+        let mut probabilities = vec![0.0; self.n];
+        for (rank, idx) in order.into_iter().enumerate() {
+            probabilities[idx] = 2.0*(n - rank as f64)/(n*(n + 1.0));
         }
+        probabilities
+    }
 
-        pairs
+    fn parent_probabilities(&mut self) -> Vec<f64>
+    {
+        match self.constraint_handling {
+            ConstraintHandling::Penalty if self.selection_degenerate() => vec![1.0 / self.n as f64; self.n],
+            ConstraintHandling::Penalty => self.selection_weights().into_iter().map(|w| self.assign_probability(w)).collect(),
+            ConstraintHandling::StochasticRanking{pf} => self.rank_probabilities(pf),
+        }
     }
 
-    fn cross(&mut self) -> () {
-        let mut thread_rng = rand::thread_rng();
-        let mut old_population:Vec<Chromosome> = self.population.drain(..).collect();
-        let pairs = self.pairs(old_population,&mut thread_rng);
-        let mut new_population:Vec<Chromosome> = Vec::new();
+    // population slots of `count` parents, drawn by the selection operator if one is set
+    fn select_parents(&mut self, count:usize) -> Vec<usize>
+    {
+        if let Some(selection) = &self.selection {
+            return selection.select(&self.selection_weights(), count, &mut self.rng);
+        }
+        let probabilities = self.parent_probabilities();
+        ops::select(&probabilities, count, &mut self.rng)
+    }
 
-        for pair in pairs.iter()
-        {
-            let mut clone1 = pair.0.clone();
-            let mut clone2 = pair.1.clone();
-                
-            if thread_rng.gen::<f32>() < self.Pcross
-            {
-                let temp1 = (clone1.data << (self.L - self.z)) >> (self.L - self.z);
-                let temp2 = (clone2.data << (self.L - self.z)) >> (self.L - self.z);
-                
-                for i  in 0..self.z     
-                {
-                    clone1.data &= !(1 << i);
-                    clone2.data &= !(1 << i);
+    // like `select_parents` but only among `slots`
+    pub(crate) fn select_parents_among(&mut self, slots:&[usize], count:usize) -> Vec<usize>
+    {
+        let picked = match &self.selection {
+            Some(selection) => {
+                let weights = self.selection_weights();
+                selection.select(&slots.iter().map(|&s| weights[s]).collect::<Vec<f64>>(), count, &mut self.rng)
+            }
+            None => {
+                let probabilities = self.parent_probabilities();
+                let mut restricted:Vec<f64> = slots.iter().map(|&s| probabilities[s]).collect();
+                let total:f64 = restricted.iter().sum();
+                if total > 0.0 && total.is_finite() {
+                    restricted.iter_mut().for_each(|p| *p /= total);
+                }else{
+                    restricted = vec![1.0 / slots.len() as f64; slots.len()];
                 }
-
-                clone1.data |= temp2;
-                clone2.data |= temp1;
-                
+                ops::select(&restricted, count, &mut self.rng)
             }
-            new_population.push(clone1);
-            new_population.push(clone2);
+        };
+        picked.into_iter().map(|i| slots[i]).collect()
+    }
+
+    // the n slots of the next generation taken from `pool` in shuffled passes, so every parent
+    // is used as evenly as the sizes allow
+    fn fill_from_pool(&mut self, pool:&[usize]) -> Vec<usize>
+    {
+        let mut selected = Vec::with_capacity(self.n);
+        while selected.len() < self.n {
+            let mut pass = pool.to_vec();
+            pass.shuffle(&mut self.rng);
+            pass.truncate(self.n - selected.len());
+            selected.extend(pass);
         }
+        selected
+    }
+
+    // returns the effective population size of the selection
+    fn recomb(&mut self) -> f64
+    {
+        let pool_size = ((self.n as f64 * self.mating_pool).round() as usize).max(1);
+        let pool = self.select_parents(pool_size);
+        self.log_parents(&pool);
+        let selected = if pool_size == self.n { pool } else { self.fill_from_pool(&pool) };
+        let mut offspring = vec![0; self.population.len()];
+        selected.iter().for_each(|&slot| offspring[slot] += 1);
+        self.population = self.population.gather(&selected);
+        stats::effective_size(&offspring)
+    }
 
+    fn cross(&mut self) {
+        let rng = &mut self.rng;
+        let pairs = ops::pair_up(self.n, rng);
+        let order:Vec<usize> = pairs.iter().flat_map(|&(a, b)| [a, b]).collect();
+        let mut new_population = self.population.gather(&order);
+        if let Some(guided) = self.guided_crossover {
+            let frequencies = guided::allele_frequencies(&self.population.data, self.L);
+            for k in sampling::bernoulli_indices(rng, self.Pcross as f64, pairs.len()) {
+                (new_population.data[2*k], new_population.data[2*k + 1]) =
+                    guided.crossover(new_population.data[2*k], new_population.data[2*k + 1], &frequencies, rng);
+            }
+            self.population = new_population;
+            return;
+        }
+        match &self.crossover {
+            Some(crossover) => {
+                for k in sampling::bernoulli_indices(rng, self.Pcross as f64, pairs.len()) {
+                    let (a, b) = crossover.crossover(new_population.data[2*k], new_population.data[2*k + 1], self.L, rng);
+                    new_population.data[2*k] = a;
+                    new_population.data[2*k + 1] = b;
+                }
+            }
+            None => new_population.data = ops::crossover(&self.population.data, &pairs, self.Pcross, self.L, self.z, rng),
+        }
         self.population = new_population;
-    }                            
+    }
 
     fn mutate(&mut self) {
-        let mut rng = rand::thread_rng();
-        for ind in &mut self.population {
-            if rng.gen::<f32>() < self.Pmut {
-                ind.data ^= 1 << rng.gen_range(0..self.L);
-            }
+        let rng = &mut self.rng;
+        match &self.mutation {
+            Some(mutation) => mutation.mutate(&mut self.population.data, self.L, rng),
+            None => self.population.data = self.mutation_scheme.apply(&self.population.data, self.Pmut, self.L, rng),
         }
     }
 
-    pub fn run(&mut self, iterations:u32)->(Vec<Chromosome>, Vec<(u64,f64)>)
+    /// Runs `iterations` generations. Stops early on a non-finite fitness under
+    /// `NonFinitePolicy::Error`, on a fitness panic under `PanicPolicy::Abort`, on zero total
+    /// fitness if `set_zero_fitness_error` asked for it, or when the low-memory stats file
+    /// can't be written.
+    pub fn run(&mut self, iterations:u32)->Result<RunResult, GaError>
     {
+        let mut stream = self.open_stream()?;
 
-        let mut stats : Vec<(u64,f64)> = Vec::new();
+        let kept_before = self.history.len();
+        if self.period == 0 {
+            self.check_configuration();
+        }
+        let started = Instant::now();
+        let config_hash = self.config_hash();
+        self.emit(LifecycleEvent::RunStarted{config_hash, generation:self.period});
+
+        let mut run_timings = PhaseTimings::default();
+        let evaluations_before = self.evaluations;
+        let mut stop_reason = StopReason::Iterations;
 
         for _ in 0..iterations
         {
-            self.calculate_data_sum();
-            self.calculate_iteration_fitness();
-            stats.push(self.iter_stats());
-            self.recomb();
-            self.cross();
-            self.mutate();
+            let generation_stats = self.run_generation(&mut stream)?;
+            run_timings += generation_stats.timings;
+            if let Some(reason) = self.stop_reason(&generation_stats) {
+                stop_reason = reason;
+                break;
+            }
+        }
+        if let Some(writer) = &mut stream {
+            writer.flush()?;
+        }
+
+        if let Err(e) = self.update_leaderboard() {
+            self.warn(HealthWarning::LeaderboardNotUpdated{reason:e.to_string()});
+        }
+        let elapsed = started.elapsed();
+        let summary = self.summary(elapsed, (self.evaluations - evaluations_before) as u64, run_timings, stop_reason.clone());
+        self.emit(LifecycleEvent::RunFinished{config_hash, summary});
+        Ok(RunResult {
+            population:self.population.to_chromosomes(),
+            best:self.best.clone(),
+            hall_of_fame:self.hall_of_fame.as_ref().map_or(Vec::new(), |hall| hall.members().to_vec()),
+            stats:self.history[kept_before..].to_vec(),
+            stop_reason,
+            elapsed,
+            evaluations:self.evaluations,
+        })
+    }
+
+    /// Runs one generation and returns its stats, for driving the loop yourself. Unlike
+    /// `run` it emits no lifecycle events and leaves the leaderboard alone.
+    pub fn try_step(&mut self) -> Result<GenerationStats, GaError>
+    {
+        let mut stream = self.open_stream()?;
+        if self.period == 0 {
+            self.check_configuration();
+        }
+        let generation_stats = self.run_generation(&mut stream)?;
+        if let Some(writer) = &mut stream {
+            writer.flush()?;
+        }
+        Ok(generation_stats)
+    }
+
+    /// Like `try_step` but panics on any `GaError`.
+    pub fn step(&mut self) -> GenerationStats
+    {
+        self.try_step().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Steps through generations until a termination criterion fires, after yielding the
+    /// generation that fired it. Without criteria it never ends, use `take`.
+    pub fn generations(&mut self) -> Generations<'_>
+    {
+        Generations {run:self, stopped:false}
+    }
+
+    /// Whether a termination criterion fires on `stats`, or an observer asked to stop at the
+    /// end of the last generation.
+    pub fn should_stop(&self, stats:&GenerationStats) -> bool
+    {
+        self.stop_reason(stats).is_some()
+    }
+
+    /// Why the run stops after `stats`: an observer's request first, then a spent evaluation
+    /// budget, then the first criterion that fires, in the order they were added.
+    pub fn stop_reason(&self, stats:&GenerationStats) -> Option<StopReason>
+    {
+        if self.stop_requested {
+            return Some(StopReason::Observer);
+        }
+        if self.budget_exhausted() {
+            return Some(StopReason::BudgetExhausted);
         }
+        self.termination.iter().find(|t| t.should_stop(stats)).map(|t| t.reason())
+    }
 
-        (self.population.clone(), stats)
+    fn open_stream(&self) -> Result<Option<StatsWriter>, GaError>
+    {
+        Ok(match &self.low_memory_path {
+            Some(path) => Some(StatsWriter::append(path)?),
+            None => None,
+        })
     }
 
-    fn iter_stats(&self)->(u64,f64)
+    fn run_generation(&mut self, stream:&mut Option<StatsWriter>) -> Result<GenerationStats, GaError>
     {
-        let sum = self.population.iter().map(|chromosome| chromosome.data).sum::<u64>();
-        let mean = sum as f64 /  self.n as f64;
+        let mut timings = PhaseTimings::default();
+        let phase = Instant::now();
+        self.period += 1;
+        self.notify_start();
+        let shock = self.apply_shocks();
+        if shock {
+            self.stale = None;
+        }
+        self.calculate_data_sum();
+        self.calculate_iteration_fitness()?;
+        self.admit_immigrants();
+        timings.evaluation = phase.elapsed();
+        self.check_generation();
+        let improved = self.update_best();
+        self.stagnant_generations = if improved { 0 } else { self.stagnant_generations + 1 };
+        self.record_step();
+        self.record_emigrants();
+        self.notify_evaluated();
+        if let Some(archive) = &mut self.archive {
+            self.population.iter().for_each(|ind| { archive.insert(&ind, self.objective); });
+        }
+        if let Some(hall) = &mut self.hall_of_fame {
+            self.population.iter().for_each(|ind| { hall.insert(&ind, self.objective); });
+        }
+        let retained = self.retention.is_none_or(|policy| policy.retains(self.period, improved));
+        let phenotypes = self.phenotypes();
+        let mut generation_stats = GenerationStats::from_columns(self.period, &phenotypes, self.objective);
+        // the best and the diversity are about genomes, not the decoded quantities
+        generation_stats.best = stats::best_slot(&self.population, self.objective).map(|i| self.population.get(i));
+        generation_stats.best_phenotype = self.decoding.zip(generation_stats.best.as_ref()).map(|(decoding, best)| decoding.decode(best.data));
+        generation_stats.diversity = stats::mean_hamming_distance(&self.population.data);
+        generation_stats.price = self.market.market_price(&phenotypes.data);
+        generation_stats.welfare = self.market.welfare(&phenotypes.data, self.firm_costs.as_deref());
+        drop(phenotypes);
+        generation_stats.shock = shock;
+        generation_stats.penalty = self.penalty;
+        generation_stats.degenerate = self.selection_degenerate();
+        if generation_stats.degenerate && self.zero_fitness_error {
+            return Err(GaError::ZeroFitness{generation:self.period});
+        }
+        generation_stats.non_finite = self.non_finite_count;
+        generation_stats.panics = self.panic_count;
+        generation_stats.timeouts = self.timeout_count;
+        generation_stats.stagnant_generations = self.stagnant_generations;
+        self.adapt_penalty();
+        (generation_stats.entrants, generation_stats.exits) = self.apply_entry_exit();
+        generation_stats.firms = self.n;
+        let elite_slots = self.elites();
+        let elites = self.population.gather(&elite_slots);
+        let leader = generation_stats.best.as_ref().map(|best| best.data);
 
-        let variance = self.population.iter()
-            .map(|chromosome| (chromosome.data as f64 - mean).powi(2))
-            .sum::<f64>() / self.n as f64;
+        match (self.steady_state, self.mating_groups) {
+            (Some(policy), _) => {
+                let phase = Instant::now();
+                let parents = self.select_parents(2);
+                self.log_parents(&parents);
+                timings.selection = phase.elapsed();
+                let phase = Instant::now();
+                self.replace_with_offspring(&parents, policy);
+                self.learn(leader);
+                timings.crossover = phase.elapsed();
+            }
+            (None, Some(groups)) => {
+                let phase = Instant::now();
+                generation_stats.effective_size = Some(self.recomb_groups());
+                timings.selection = phase.elapsed();
+                let phase = Instant::now();
+                self.cross_groups();
+                timings.crossover = phase.elapsed();
+                let phase = Instant::now();
+                self.mutate_groups(groups);
+                self.learn(leader);
+                timings.mutation = phase.elapsed();
+            }
+            (None, None) => {
+                let phase = Instant::now();
+                generation_stats.effective_size = Some(self.recomb());
+                timings.selection = phase.elapsed();
+                let phase = Instant::now();
+                self.cross();
+                timings.crossover = phase.elapsed();
+                let phase = Instant::now();
+                self.mutate();
+                self.learn(leader);
+                timings.mutation = phase.elapsed();
+            }
+        }
+        for (k, &slot) in elite_slots.iter().enumerate() {
+            self.population.data[slot] = elites.data[k];
+            self.population.fitness[slot] = elites.fitness[k];
+            self.population.violation[slot] = elites.violation[k];
+        }
+        if let Some(stale) = &mut self.stale {
+            stale.retain(|slot| !elite_slots.contains(slot));
+        }
+
+        generation_stats.timings = timings;
+        match stream {
+            Some(writer) => writer.write(&generation_stats)?,
+            None if retained => self.history.push(generation_stats.clone()),
+            None => {}
+        }
+        self.notify_end(&generation_stats);
+        Ok(generation_stats)
+    }
 
-        (sum, variance)
+    /// Extends a finished run by `extra_iterations` generations from its current state and
+    /// returns the stats of the new generations, thinned like `RunResult::stats` under a
+    /// `RetentionPolicy` and empty in low-memory mode. `history` holds the earlier ones.
+    pub fn continue_for(&mut self, extra_iterations:u32)->Result<Vec<GenerationStats>, GaError>
+    {
+        Ok(self.run(extra_iterations)?.stats)
     }
 
+    pub fn generation(&self) -> u32
+    {
+        self.period
+    }
+
+    /// The current population without copying it.
+    pub fn population(&self) -> PopulationView<'_>
+    {
+        PopulationView::new(&self.population)
+    }
+
+    /// A copy of the current population that outlives the run's next step.
+    pub fn population_snapshot(&self) -> Vec<Chromosome>
+    {
+        self.population.to_chromosomes()
+    }
+
+    pub fn distinct_genotypes(&self) -> usize
+    {
+        let mut seen:HashSet<u64, GenomeBuildHasher> = HashSet::default();
+        self.population.data.iter().filter(|data| seen.insert(**data)).count()
+    }
+
+}
+
+/// Iterator over the generations of a run, see `Run::generations`.
+#[derive(Debug)]
+pub struct Generations<'a>
+{
+    run:&'a mut Run,
+    stopped:bool,
 }
 
-pub fn save_iter_to_csv(data: &Vec<(u64, f64)>, file_name: &str) -> Result<(), Box<dyn Error>> {
+impl Iterator for Generations<'_>
+{
+    type Item = GenerationStats;
+
+    fn next(&mut self) -> Option<GenerationStats>
+    {
+        if self.stopped {
+            return None;
+        }
+        let stats = self.run.step();
+        self.stopped = self.run.should_stop(&stats);
+        Some(stats)
+    }
+}
+
+pub fn save_iter_to_csv(data: &[(u64, f64)], file_name: &str) -> Result<(), GaError> {
     let file = File::create(file_name)?;
     let mut writer = csv::Writer::from_writer(file);
 
     
-    writer.write_record(&["ind_out", "var"])?;
+    writer.write_record(["ind_out", "var"])?;
 
     
     for iter in data {
@@ -231,6 +1124,7 @@ pub fn save_iter_to_csv(data: &Vec<(u64, f64)>, file_name: &str) -> Result<(), B
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[test]
     fn new_chromosome() {
@@ -240,23 +1134,23 @@ mod tests {
 
     #[test]
     fn new_run(){
-        let run = Run::new(0.2, 0.5, 32, 32, 16);
+        let run = Run::new(0.2, 0.5, 32, 32, 16).unwrap();
         assert!(run.Pcross == 0.2 && run.Pmut == 0.5 && run.L == 32 && run.n == 32 && run.z == 16 && run.period == 0 && run.population.len() == 32);
     }
 
     #[test]
     fn select_test(){
-        let test_run = Run::new(0.2, 0.5, 32, 32, 16);
+        let test_run = Run::new(0.2, 0.5, 32, 32, 16).unwrap();
         let mut probabilities:Vec<f64> = (0..32).map(|_| random()).collect();
         let sum:f64 = probabilities.iter().sum();
         probabilities.iter_mut().for_each(|x| *x /= sum);
-        let new_var = test_run.select(&probabilities);
-        assert!(1==1);
+        let selected = ops::select(&probabilities, 1, &mut rand::thread_rng());
+        assert!(selected[0] < test_run.population.len());
     }
 
     #[test]
     fn recomb_test(){
-        let mut test_run = Run::new(0.2, 0.5, 32, 32, 16);
+        let mut test_run = Run::new(0.2, 0.5, 32, 32, 16).unwrap();
         test_run.recomb();
 
         assert_eq!(test_run.population.len(), 32);
@@ -267,7 +1161,7 @@ mod tests {
         let mut number = 0b0000_1100;
         let mut shift = 14;
         let n = 3;
-        for i in (0..n)
+        for i in 0..n
         {
             number &= !(1 << i);
         }
@@ -286,7 +1180,7 @@ mod tests {
 
     #[test]
     fn cross_test(){
-        let mut test_run = Run::new(0.2, 0.5, 64, 32, 16);
+        let mut test_run = Run::new(0.2, 0.5, 64, 32, 16).unwrap();
         let old_population = test_run.population.clone();
         test_run.cross();
         assert!((test_run.population != old_population)&&(old_population.len() == test_run.population.len()))
@@ -295,18 +1189,18 @@ mod tests {
     #[test]
     fn run_test()
     {
-        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2);
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
 
-        let mut old_population = test_run.population.clone();
+        let mut old_population = test_run.population.to_chromosomes();
 
-        let result = test_run.run(10000).0;
-        old_population.sort_by(|a, b| a.data.cmp(&b.data));
+        let result = test_run.run(10000).unwrap().population;
+        old_population.sort_by_key(|a| a.data);
         let mut sorted_result = result.clone();
-        sorted_result.sort_by(|a, b| a.data.cmp(&b.data));
+        sorted_result.sort_by_key(|a| a.data);
         
         let sum = result.iter().fold(0.0, |a,b| a + b.fitness);
 
-        for (idx, ind) in result.iter().enumerate() {
+        for ind in result.iter() {
             println!("idx: {}",ind.fitness/sum);
         }
         let ind_out = result.iter().fold(0, |a,b| a + b.data);
@@ -315,4 +1209,347 @@ mod tests {
         assert!((result != old_population)&&(result.len() == old_population.len()))
     }
 
+    #[test]
+    fn archive_test()
+    {
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        test_run.set_archive(EpsilonArchive::new(vec![1000.0]));
+        test_run.run(100).unwrap();
+
+        let archive = test_run.archive().unwrap();
+        assert!(!archive.is_empty());
+        let best = archive.entries().iter().map(|e| e.chromosome.fitness).fold(0.0, f64::max);
+        assert!(best > 0.0);
+    }
+
+    #[test]
+    fn continue_for_test()
+    {
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        let first = test_run.run(10).unwrap().stats;
+        let stats = test_run.continue_for(5).unwrap();
+
+        assert_eq!(test_run.population().len(), 30);
+        assert_eq!(stats.iter().map(|s| s.generation).collect::<Vec<u32>>(), (11..=15).collect::<Vec<u32>>());
+        assert_eq!(&test_run.history()[..10], &first[..]);
+        assert_eq!(test_run.generation(), 15);
+        let generations:Vec<u32> = test_run.history().iter().map(|s| s.generation).collect();
+        assert_eq!(generations, (1..=15).collect::<Vec<u32>>());
+
+        let mut thinned = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        thinned.set_retention_policy(RetentionPolicy {keep_first:2, every:5, keep_improvements:false});
+        thinned.run(10).unwrap();
+        let stats = thinned.continue_for(10).unwrap();
+        assert_eq!(stats.iter().map(|s| s.generation).collect::<Vec<u32>>(), vec![15, 20]);
+    }
+
+    #[test]
+    fn zero_fitness_fallback_test()
+    {
+        fn never(_ind:&Chromosome) -> f64
+        {
+            1.0
+        }
+
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.set_fitness(|_, _| 0.0);
+        test_run.add_constraint(never);
+        test_run.set_penalty(1e12);
+        test_run.run(10).unwrap();
+
+        assert!(test_run.history().iter().all(|s| s.degenerate));
+        assert!(test_run.history().iter().all(|s| s.ind_out > 0));
+        let first = test_run.population.data[0];
+        assert!(test_run.population.data.iter().any(|data| *data != first));
+    }
+
+    #[test]
+    fn non_finite_policy_test()
+    {
+        fn broken(ind:&Chromosome) -> f64
+        {
+            if ind.data.is_multiple_of(2) { f64::NAN } else { 0.0 }
+        }
+
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.add_constraint(broken);
+        test_run.run(5).unwrap();
+        assert!(test_run.history().iter().any(|s| s.non_finite > 0));
+        assert!(test_run.history().iter().all(|s| s.ind_out > 0));
+
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.population.data.iter_mut().for_each(|data| *data = 2);
+        test_run.add_constraint(broken);
+        test_run.set_non_finite_policy(NonFinitePolicy::Error);
+        let Err(GaError::NonFiniteFitness(error)) = test_run.run(5) else {
+            panic!("expected a non-finite fitness error");
+        };
+        assert_eq!(error.generation, 1);
+        assert_eq!(error.index, 0);
+        assert!(error.violation.is_nan());
+    }
+
+    #[test]
+    fn minimize_test()
+    {
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.set_objective(Objective::Minimize);
+        test_run.run(100).unwrap();
+
+        let history = test_run.history();
+        assert!(history.last().unwrap().ind_out < history[0].ind_out / 2);
+    }
+
+    #[test]
+    fn retention_policy_test()
+    {
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.set_retention_policy(RetentionPolicy {keep_first:10, every:25, keep_improvements:false});
+        let stats = test_run.run(100).unwrap().stats;
+
+        let generations:Vec<u32> = test_run.history().iter().map(|s| s.generation).collect();
+        assert_eq!(generations, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 25, 50, 75, 100]);
+        assert_eq!(stats.len(), generations.len());
+    }
+
+    #[test]
+    fn phase_timings_test()
+    {
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.run(20).unwrap();
+
+        let total:Duration = test_run.history().iter().map(|s| s.timings.total()).sum();
+        assert!(total > Duration::ZERO);
+        assert!(test_run.history().iter().all(|s| s.timings.evaluation > Duration::ZERO));
+    }
+
+    #[test]
+    fn constraint_stats_test()
+    {
+        fn below_500(ind:&Chromosome) -> f64
+        {
+            ind.data as f64 - 500.0
+        }
+
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        test_run.add_constraint(below_500);
+        test_run.set_penalty(1e6);
+        test_run.run(50).unwrap();
+
+        let history = test_run.history();
+        assert_eq!(history.len(), 50);
+        assert!(history.iter().all(|s| s.best_feasible_fitness.is_some() == (s.feasible_fraction > 0.0)));
+        assert!(history.iter().any(|s| s.feasible_fraction > history[0].feasible_fraction));
+    }
+
+    #[test]
+    fn stochastic_ranking_test()
+    {
+        fn below_500(ind:&Chromosome) -> f64
+        {
+            ind.data as f64 - 500.0
+        }
+
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        test_run.add_constraint(below_500);
+        test_run.set_constraint_handling(ConstraintHandling::StochasticRanking{pf:0.0});
+        test_run.run(50).unwrap();
+
+        let history = test_run.history();
+        assert!(history.iter().any(|s| s.feasible_fraction > history[0].feasible_fraction));
+        assert!(history.iter().skip(1).all(|s| s.penalty == history[0].penalty));
+    }
+
+    #[test]
+    fn adaptive_penalty_test()
+    {
+        fn below_500(ind:&Chromosome) -> f64
+        {
+            ind.data as f64 - 500.0
+        }
+
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        test_run.add_constraint(below_500);
+        test_run.set_penalty(1.0);
+        test_run.set_adaptive_penalty(AdaptivePenalty::new(2, 10.0, 2.0));
+        test_run.run(30).unwrap();
+
+        let penalties:Vec<f64> = test_run.history().iter().map(|s| s.penalty).collect();
+        assert_eq!(penalties[0], 1.0);
+        assert!(penalties.windows(2).any(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn elitism_keeps_the_best()
+    {
+        let mut test_run = Run::new(1.0, 1.0, 16, 20, 8).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.set_elitism(2);
+        test_run.run(30).unwrap();
+        let best:Vec<f64> = test_run.history().iter().map(|s| s.best_feasible_fitness.unwrap()).collect();
+        assert!(best.windows(2).all(|w| w[1] >= w[0]), "{:?}", best);
+        assert!(test_run.population.fitness.contains(&test_run.best().unwrap().fitness));
+    }
+
+    #[test]
+    fn mating_pool_limits_parents()
+    {
+        let mut test_run = Run::new(0.0, 0.0, 8, 20, 4).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.set_mating_pool(0.25);
+        test_run.record_parents(true);
+        test_run.run(1).unwrap();
+        assert_eq!(test_run.parent_log()[0].parents.len(), 5);
+        let mut slots:Vec<u64> = test_run.population.data.clone();
+        slots.sort();
+        slots.dedup();
+        assert!(slots.len() <= 5);
+        assert_eq!(test_run.population.len(), 20);
+    }
+
+    #[test]
+    fn stepping_matches_run()
+    {
+        let mut stepped = Run::with_seed(0.6, 0.05, 10, 30, 2, 4).unwrap();
+        let mut ran = stepped.clone();
+        let stats:Vec<GenerationStats> = (0..10).map(|_| stepped.step()).collect();
+        ran.run(10).unwrap();
+        assert_eq!(stats.iter().map(|s| s.generation).collect::<Vec<u32>>(), (1..=10).collect::<Vec<u32>>());
+        assert_eq!(stepped.population, ran.population);
+        assert_eq!(stepped.history().len(), 10);
+
+        stepped.add_termination(Box::new(operators::MaxGenerations(15)));
+        assert_eq!(stepped.generations().map(|s| s.generation).collect::<Vec<u32>>(), (11..=15).collect::<Vec<u32>>());
+        assert_eq!(stepped.generations().take(3).count(), 1);
+    }
+
+    #[test]
+    fn panic_policies()
+    {
+        fn fragile(ind:&Chromosome, _data_sum:u64) -> f64
+        {
+            if ind.data.is_multiple_of(3) { panic!("no multiples of three") } else { ind.data as f64 }
+        }
+
+        let mut test_run = Run::with_seed(0.6, 0.05, 8, 20, 4, 1).unwrap();
+        test_run.set_fitness(fragile);
+        test_run.set_panic_policy(PanicPolicy::Worst);
+        test_run.run(20).unwrap();
+        assert!(test_run.history().iter().any(|s| s.panics > 0));
+        assert!(!test_run.best().unwrap().data.is_multiple_of(3));
+
+        test_run.set_panic_policy(PanicPolicy::Abort);
+        let Err(GaError::FitnessPanic{message, ..}) = test_run.run(1000) else { panic!("the panic was not reported") };
+        assert_eq!(message, "no multiples of three");
+        assert_eq!(test_run.population.len(), 20);
+
+        // failures get f64::MAX when minimizing, which must not flatten the other weights
+        let mut test_run = Run::with_seed(0.6, 0.05, 8, 20, 4, 1).unwrap();
+        test_run.set_fitness(fragile);
+        test_run.set_objective(Objective::Minimize);
+        test_run.set_panic_policy(PanicPolicy::Worst);
+        test_run.run(3).unwrap();
+        assert!(test_run.history().iter().all(|s| s.panics > 0 && !s.degenerate));
+    }
+
+    #[test]
+    fn slow_evaluations_time_out()
+    {
+        fn slow_on_odd(ind:&Chromosome, _data_sum:u64) -> f64
+        {
+            if ind.data % 2 == 1 {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            ind.data as f64 + 1.0
+        }
+
+        let mut test_run = Run::with_seed(0.6, 0.05, 8, 10, 4, 2).unwrap();
+        test_run.set_fitness(slow_on_odd);
+        test_run.set_evaluation_timeout(Duration::from_millis(20));
+        let odd = test_run.population.data.iter().filter(|d| *d % 2 == 1).count();
+        assert!(odd > 0);
+        assert_eq!(test_run.step().timeouts, odd);
+        assert_eq!(test_run.best().unwrap().data % 2, 0);
+    }
+
+    #[test]
+    fn fallible_construction_and_zero_fitness()
+    {
+        let Err(GaError::Config(errors)) = Run::new(0.3, 0.01, 0, 30, 2) else { panic!("L = 0 accepted") };
+        assert_eq!(errors, vec![ConfigError::GenomeLength{L:0}]);
+        assert!(Run::new(0.3, 0.01, 10, 30, 2).is_ok());
+
+        let mut test_run = Run::new(0.3, 0.01, 10, 30, 2).unwrap();
+        test_run.set_fitness(|_, _| 0.0);
+        test_run.set_zero_fitness_error(true);
+        assert!(matches!(test_run.run(5), Err(GaError::ZeroFitness{generation:1})));
+    }
+
+    #[test]
+    fn seeded_runs_repeat()
+    {
+        let seeded = |seed| {
+            let mut test_run = Run::with_seed(0.6, 0.05, 10, 30, 2, seed).unwrap();
+            test_run.set_constraint_handling(ConstraintHandling::StochasticRanking{pf:0.45});
+            test_run.set_mating_pool(0.5);
+            test_run.run(20).unwrap();
+            test_run.population_snapshot()
+        };
+        assert_eq!(seeded(7), seeded(7));
+        assert_ne!(seeded(7), seeded(8));
+    }
+
+    #[test]
+    fn negative_fitness_is_maximized()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 10, 20, 4, 3).unwrap();
+        test_run.set_fitness(|ind, _| -((ind.data as f64 - 500.0).powi(2)));
+        test_run.set_elitism(1);
+        test_run.run(40).unwrap();
+        let history = test_run.history();
+        // only a population of equals leaves selection nothing to work with
+        assert!(history.iter().all(|s| s.worst_fitness < 0.0 && s.degenerate == (s.best_fitness == s.worst_fitness)));
+        assert!(history.iter().any(|s| !s.degenerate));
+        assert!(history[39].best_fitness > history[0].best_fitness);
+    }
+
+    #[test]
+    fn prefilter_skips_evaluation()
+    {
+        let mut test_run = Run::new(0.6, 0.1, 8, 20, 4).unwrap();
+        test_run.set_fitness(|ind, _| if ind.data % 2 == 0 { 1.0 + ind.data as f64 } else { panic!("evaluated an odd genome") });
+        test_run.set_prefilter(|ind| ind.data % 2 == 0, test_run.objective().worst_value());
+        test_run.run(10).unwrap();
+        assert!(test_run.rejected() > 0);
+        assert_eq!(test_run.evaluations() + test_run.rejected(), 200);
+        assert_eq!(test_run.best().unwrap().data % 2, 0);
+        assert!(test_run.history().iter().all(|s| s.best.as_ref().is_some_and(|best| best.data % 2 == 0)));
+
+        // evaluated once more, the odd genomes the GA bred hold the rejected fitness
+        test_run.calculate_data_sum();
+        test_run.calculate_iteration_fitness().unwrap();
+        for (data, fitness) in test_run.population.data.iter().zip(&test_run.population.fitness) {
+            assert!(data % 2 == 0 || *fitness == f64::MIN);
+        }
+        let rejected = test_run.rejected();
+        let refined = test_run.refine(&[Chromosome {data:1, fitness:0.0, N:20, violation:0.0}], 3);
+        assert_eq!(refined[0].start.fitness, f64::MIN);
+        assert_eq!((refined[0].refined.data, test_run.rejected()), (2, rejected + 1));
+        assert_eq!(test_run.evaluations() + test_run.rejected(), 223);
+    }
+
+    #[test]
+    fn jitter_within_epsilon_is_not_improvement()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 16, 20, 8, 4).unwrap();
+        test_run.set_fitness(|ind, _| ind.data.count_ones() as f64 + 1e-9 * (ind.data % 1000) as f64);
+        test_run.set_epsilon(1e-3);
+        test_run.run(40).unwrap();
+        let mut best_count = 0.0;
+        for stats in test_run.history() {
+            let improved = stats.best_fitness.floor() > best_count;
+            best_count = f64::max(best_count, stats.best_fitness.floor());
+            assert_eq!(stats.stagnant_generations == 0, improved, "generation {}", stats.generation);
+        }
+    }
 }
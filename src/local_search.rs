@@ -0,0 +1,99 @@
+use crate::{Chromosome, Run};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefinedSolution
+{
+    pub start:Chromosome,
+    pub refined:Chromosome,
+    pub evaluations:usize,
+}
+
+impl Run
+{
+    /// Bit-flip hill climbing from each of `starts`, at most `max_evaluations` fitness calls each.
+    ///
+    /// Every start is evaluated against the current population with itself swapped for the
+    /// candidate, so frequency-dependent fitness sees the same market the GA finished in.
+    /// The population itself is left untouched, and firm costs are not applied since a start
+    /// does not belong to any firm. Candidates go through the prefilter and evaluation budget
+    /// and count in `evaluations` like the population's.
+    pub fn refine(&mut self, starts:&[Chromosome], max_evaluations:usize) -> Vec<RefinedSolution>
+    {
+        let population_sum = self.phenotypes().data_sum();
+        starts.iter().map(|start| {
+            let others = population_sum.saturating_sub(self.quantity(start.data));
+            let mut best = start.clone();
+            let data_sum = others.saturating_add(self.quantity(best.data));
+            self.evaluate(&mut best, data_sum, 0.0);
+            let start = best.clone();
+            let mut evaluations = 1;
+
+            let mut improved = true;
+            while improved && evaluations < max_evaluations {
+                improved = false;
+                for bit in 0..self.L {
+                    if evaluations >= max_evaluations {
+                        break;
+                    }
+                    let mut candidate = best.clone();
+                    candidate.data ^= 1 << bit;
+                    let data_sum = others.saturating_add(self.quantity(candidate.data));
+                    self.evaluate(&mut candidate, data_sum, 0.0);
+                    evaluations += 1;
+                    if self.objective.is_better(candidate.fitness, best.fitness) {
+                        best = candidate;
+                        improved = true;
+                    }
+                }
+            }
+
+            RefinedSolution {start, refined:best, evaluations}
+        }).collect()
+    }
+
+    /// Refines the `k` fittest distinct genotypes of the current population.
+    pub fn refine_best(&mut self, k:usize, max_evaluations:usize) -> Vec<RefinedSolution>
+    {
+        let mut sorted = self.population.to_chromosomes();
+        sorted.sort_by(|a, b| self.objective.best_first(a.fitness, b.fitness));
+        let mut starts:Vec<Chromosome> = Vec::new();
+        for ind in sorted {
+            if !starts.iter().any(|s| s.data == ind.data) {
+                starts.push(ind);
+            }
+            if starts.len() == k {
+                break;
+            }
+        }
+        self.refine(&starts, max_evaluations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refinement_never_gets_worse()
+    {
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        test_run.run(20).unwrap();
+
+        let refined = test_run.refine_best(3, 200);
+        assert!(!refined.is_empty() && refined.len() <= 3);
+        for solution in refined {
+            assert!(solution.refined.fitness >= solution.start.fitness);
+            assert!(solution.evaluations <= 200);
+        }
+    }
+
+    #[test]
+    fn budget_is_respected()
+    {
+        let mut test_run = Run::new(0.322, 0.00322, 10, 30, 2).unwrap();
+        let start = test_run.population.get(0);
+        let refined = test_run.refine(&[start], 5);
+        assert_eq!(refined[0].evaluations, 5);
+        assert_eq!(test_run.evaluations(), 5);
+    }
+}
@@ -0,0 +1,121 @@
+//! MAX-SAT: fitness is the number of satisfied clauses of a CNF formula read from a DIMACS
+//! `.cnf` file. On a bitstring genome bit `i` is the value of variable `i + 1`.
+use crate::operators::Fitness;
+use crate::stats::PopulationStats;
+use crate::Chromosome;
+use std::error::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxSat
+{
+    pub variables:usize,
+    /// Literals in DIMACS form: `v` is variable `v` and `-v` its negation.
+    pub clauses:Vec<Vec<i64>>,
+}
+
+impl MaxSat
+{
+    /// Parses `c` comment lines, a `p cnf <variables> <clauses>` line and clauses of literals
+    /// terminated by `0`, which may span lines. A `%` line ends the formula as in SATLIB files.
+    pub fn parse_dimacs(text:&str) -> Result<Self, Box<dyn Error>>
+    {
+        let mut variables:Option<usize> = None;
+        let mut clauses = Vec::new();
+        let mut clause = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+            if line.starts_with('%') {
+                break;
+            }
+            if line.starts_with('p') {
+                let fields:Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 4 || fields[1] != "cnf" {
+                    return Err(format!("line {}: expected 'p cnf <variables> <clauses>'", number + 1).into());
+                }
+                variables = Some(fields[2].parse()?);
+                continue;
+            }
+            let max = variables.ok_or(format!("line {}: clause before the problem line", number + 1))?;
+            for literal in line.split_whitespace() {
+                let literal:i64 = literal.parse()?;
+                if literal == 0 {
+                    clauses.push(std::mem::take(&mut clause));
+                }else if literal.unsigned_abs() as usize > max {
+                    return Err(format!("line {}: variable {} out of range", number + 1, literal).into());
+                }else{
+                    clause.push(literal);
+                }
+            }
+        }
+        if !clause.is_empty() {
+            clauses.push(clause);
+        }
+        let variables = variables.ok_or("missing problem line")?;
+        Ok(MaxSat {variables, clauses})
+    }
+
+    pub fn from_dimacs(file_name:&str) -> Result<Self, Box<dyn Error>>
+    {
+        Self::parse_dimacs(&std::fs::read_to_string(file_name)?)
+    }
+
+    /// Satisfied clauses under `assignment`, where `assignment[i]` is variable `i + 1`.
+    pub fn satisfied(&self, assignment:&[bool]) -> usize
+    {
+        self.count(|variable| assignment[variable - 1])
+    }
+
+    /// Satisfied clauses with variables read from the bits of `data`, variables beyond 64 are false.
+    pub fn satisfied_bits(&self, data:u64) -> usize
+    {
+        self.count(|variable| variable <= 64 && data >> (variable - 1) & 1 == 1)
+    }
+
+    fn count<F:Fn(usize) -> bool>(&self, value:F) -> usize
+    {
+        self.clauses.iter()
+            .filter(|clause| clause.iter().any(|&literal| value(literal.unsigned_abs() as usize) == (literal > 0)))
+            .count()
+    }
+}
+
+impl Fitness for MaxSat
+{
+    fn evaluate(&self, ind:&Chromosome, _context:&PopulationStats) -> f64
+    {
+        self.satisfied_bits(ind.data) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Run;
+
+    const FORMULA:&str = "c example\np cnf 4 5\n1 -2 0\n2 3\n 0\n-1 -3 0\n4 0\n-4 1 2 0\n";
+
+    #[test]
+    fn parses_and_counts()
+    {
+        let formula = MaxSat::parse_dimacs(FORMULA).unwrap();
+        assert_eq!(formula.variables, 4);
+        assert_eq!(formula.clauses[1], vec![2, 3]);
+        assert_eq!(formula.satisfied(&[true, true, false, true]), 5);
+        assert_eq!(formula.satisfied_bits(0b1011), 5);
+        assert_eq!(formula.satisfied_bits(0), 3);
+        assert!(MaxSat::parse_dimacs("p cnf 2 1\n1 3 0\n").is_err());
+    }
+
+    #[test]
+    fn run_satisfies_formula()
+    {
+        let formula = MaxSat::parse_dimacs(FORMULA).unwrap();
+        let mut test_run = Run::with_seed(0.6, 0.1, 4, 40, 2, 1).unwrap();
+        test_run.set_fitness_function(Box::new(formula));
+        test_run.run(50).unwrap();
+        assert_eq!(test_run.best().unwrap().fitness, 5.0);
+    }
+}
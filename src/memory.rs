@@ -0,0 +1,80 @@
+use crate::{ArchiveEntry, GenerationStats, Run};
+use std::mem::size_of;
+
+/// Approximate heap usage of a run in bytes, based on allocated capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage
+{
+    pub population_bytes:usize,
+    pub history_bytes:usize,
+    pub archive_bytes:usize,
+}
+
+impl MemoryUsage
+{
+    pub fn total(&self) -> usize
+    {
+        self.population_bytes + self.history_bytes + self.archive_bytes
+    }
+}
+
+impl Run
+{
+    pub fn memory_usage(&self) -> MemoryUsage
+    {
+        let archive_bytes = self.archive.as_ref().map_or(0, |archive| {
+            archive.entries().iter()
+                .map(|entry| size_of::<ArchiveEntry>() + entry.objectives.capacity() * size_of::<f64>())
+                .sum()
+        });
+        MemoryUsage {
+            population_bytes:self.population.capacity_bytes(),
+            history_bytes:self.history.capacity() * size_of::<GenerationStats>(),
+            archive_bytes,
+        }
+    }
+
+    /// Low-memory mode: per-generation stats are appended to the CSV file at `path` as the run
+    /// goes instead of being kept in `history`, and `run` returns an empty stats vector.
+    pub fn set_low_memory_mode(&mut self, path:&str)
+    {
+        self.low_memory_path = Some(path.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn history_grows_memory()
+    {
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        let before = test_run.memory_usage();
+        test_run.run(50).unwrap();
+        let after = test_run.memory_usage();
+        assert!(after.history_bytes >= 50 * size_of::<GenerationStats>());
+        assert!(after.total() > before.total());
+    }
+
+    #[test]
+    fn low_memory_mode_streams_to_disk()
+    {
+        let path = std::env::temp_dir().join(format!("ga_low_memory_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+
+        let mut test_run = Run::new(0.322, 0.01, 10, 30, 2).unwrap();
+        test_run.set_low_memory_mode(path);
+        let stats = test_run.run(20).unwrap().stats;
+        test_run.run(5).unwrap();
+
+        assert!(stats.is_empty());
+        assert!(test_run.history().is_empty());
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        let generations:Vec<u32> = reader.records().map(|r| r.unwrap()[0].parse().unwrap()).collect();
+        assert_eq!(generations, (1..=25).collect::<Vec<u32>>());
+        fs::remove_file(path).unwrap();
+    }
+}
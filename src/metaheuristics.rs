@@ -0,0 +1,419 @@
+//! Baseline metaheuristics over bounded real vectors, for comparison against the GA: harmony
+//! search (Geem et al. 2001), the great deluge (Dueck 1993) and late-acceptance hill climbing
+//! (Burke and Bykov 2017). All implement `Optimizer`, so their traces feed `SuccessReport`
+//! like the other engines'. Each evaluation takes one step, except the first step of
+//! harmony search, which fills its memory.
+use crate::evolution::GenomeStats;
+use crate::objective::Objective;
+use crate::optimizer::Optimizer;
+use crate::real::{RealChromosome, RealFitnessFn, RealMutation};
+use rand::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+// state every method here shares: the problem, the RNG and the bookkeeping
+#[derive(Clone)]
+struct Search
+{
+    bounds:Vec<(f64, f64)>,
+    fitness:RealFitnessFn,
+    objective:Objective,
+    rng:StdRng,
+    period:u32,
+    evaluations:usize,
+    history:Vec<GenomeStats>,
+    best:Option<RealChromosome>,
+}
+
+impl fmt::Debug for Search
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("Search").field("bounds", &self.bounds).field("objective", &self.objective)
+            .field("period", &self.period).field("evaluations", &self.evaluations).field("best", &self.best).finish()
+    }
+}
+
+impl Search
+{
+    fn new<F>(bounds:Vec<(f64, f64)>, fitness:F) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        Search {bounds, fitness:Arc::new(fitness), objective:Objective::Maximize, rng:StdRng::from_entropy(), period:0,
+            evaluations:0, history:Vec::new(), best:None}
+    }
+
+    fn evaluate(&mut self, genes:Vec<f64>) -> RealChromosome
+    {
+        let mut candidate = RealChromosome::new(genes);
+        candidate.fitness = (self.fitness)(&candidate.genes);
+        self.evaluations += 1;
+        if self.best.as_ref().is_none_or(|best| self.objective.is_better(candidate.fitness, best.fitness)) {
+            self.best = Some(candidate.clone());
+        }
+        candidate
+    }
+
+    fn random(&mut self) -> Vec<f64>
+    {
+        RealChromosome::random(&self.bounds, &mut self.rng).genes
+    }
+
+    // `genes` with one random gene moved by `mutation`
+    fn neighbour(&mut self, genes:&[f64], mutation:RealMutation) -> Vec<f64>
+    {
+        let mut genes = genes.to_vec();
+        if !genes.is_empty() {
+            let i = self.rng.gen_range(0..genes.len());
+            mutation.apply(&mut genes[i..i + 1], 1.0, &self.bounds[i..i + 1], &mut self.rng);
+        }
+        genes
+    }
+
+    // `a` is at least as good as `b`
+    fn accepts(&self, a:f64, b:f64) -> bool
+    {
+        !self.objective.is_better(b, a)
+    }
+
+    fn record(&mut self, best_fitness:f64, mean_fitness:f64)
+    {
+        self.period += 1;
+        self.history.push(GenomeStats {generation:self.period, best_fitness, mean_fitness, evaluations:self.evaluations,
+            feasible_fraction:1.0});
+    }
+}
+
+/// Improvises one new vector per step, each gene taken from a random member of the harmony
+/// memory (possibly pitch-adjusted) or drawn uniformly, and replaces the memory's worst member
+/// if the new vector is better.
+#[derive(Debug, Clone)]
+pub struct HarmonySearch
+{
+    /// Chance each gene is taken from memory rather than drawn uniformly.
+    pub memory_rate:f64,
+    /// Chance a gene taken from memory is moved by up to `bandwidth`.
+    pub pitch_rate:f64,
+    /// Largest pitch adjustment as a fraction of the gene's range.
+    pub bandwidth:f64,
+    memory_size:usize,
+    memory:Vec<RealChromosome>,
+    search:Search,
+}
+
+impl HarmonySearch
+{
+    /// Maximizes `fitness` within `bounds` with a memory of `memory_size` vectors, memory rate
+    /// 0.9, pitch rate 0.3 and bandwidth 0.01.
+    pub fn new<F>(bounds:Vec<(f64, f64)>, memory_size:usize, fitness:F) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        assert!(memory_size >= 1, "harmony memory needs at least one vector");
+        HarmonySearch {memory_rate:0.9, pitch_rate:0.3, bandwidth:0.01, memory_size, memory:Vec::new(),
+            search:Search::new(bounds, fitness)}
+    }
+
+    pub fn set_objective(&mut self, objective:Objective)
+    {
+        self.search.objective = objective;
+    }
+
+    pub fn set_seed(&mut self, seed:u64)
+    {
+        self.search.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn history(&self) -> &[GenomeStats]
+    {
+        &self.search.history
+    }
+
+    pub fn memory(&self) -> &[RealChromosome]
+    {
+        &self.memory
+    }
+
+    fn improvise(&mut self) -> Vec<f64>
+    {
+        let search = &mut self.search;
+        search.bounds.iter().enumerate().map(|(i, &(low, high))| {
+            if search.rng.gen::<f64>() >= self.memory_rate {
+                return if low < high { search.rng.gen_range(low..high) } else { low };
+            }
+            let mut gene = self.memory[search.rng.gen_range(0..self.memory.len())].genes[i];
+            if search.rng.gen::<f64>() < self.pitch_rate {
+                gene += self.bandwidth * (high - low) * search.rng.gen_range(-1.0..=1.0);
+            }
+            gene.clamp(low, high)
+        }).collect()
+    }
+}
+
+impl Optimizer for HarmonySearch
+{
+    type Solution = RealChromosome;
+
+    fn step(&mut self)
+    {
+        if self.memory.is_empty() {
+            self.memory = (0..self.memory_size).map(|_| {
+                let genes = self.search.random();
+                self.search.evaluate(genes)
+            }).collect();
+        }else{
+            let genes = self.improvise();
+            let candidate = self.search.evaluate(genes);
+            let objective = self.search.objective;
+            let worst = (0..self.memory.len()).max_by(|&a, &b| objective.best_first(self.memory[a].fitness, self.memory[b].fitness)).unwrap();
+            if objective.is_better(candidate.fitness, self.memory[worst].fitness) {
+                self.memory[worst] = candidate;
+            }
+        }
+        let best = self.search.objective.best(self.memory.iter().map(|h| h.fitness)).unwrap();
+        let mean = self.memory.iter().map(|h| h.fitness).sum::<f64>() / self.memory.len() as f64;
+        self.search.record(best, mean);
+    }
+
+    fn best(&self) -> Option<&RealChromosome>
+    {
+        self.search.best.as_ref()
+    }
+
+    fn stats(&self) -> Option<GenomeStats>
+    {
+        self.search.history.last().copied()
+    }
+
+    fn objective(&self) -> Objective
+    {
+        self.search.objective
+    }
+}
+
+/// Moves to a neighbour if it is no worse than the current solution or than a water level,
+/// which starts at the first solution's fitness and rises (falls when minimizing) by `rain`
+/// every step. Worse moves are accepted early on, once the level passes the current fitness
+/// the search turns into hill climbing.
+#[derive(Debug, Clone)]
+pub struct GreatDeluge
+{
+    /// How far the water level moves towards better fitness per step.
+    pub rain:f64,
+    /// How a neighbour's single changed gene is moved, Gaussian with sigma 0.1 by default.
+    pub mutation:RealMutation,
+    level:f64,
+    current:Option<RealChromosome>,
+    search:Search,
+}
+
+impl GreatDeluge
+{
+    /// Maximizes `fitness` within `bounds`. A useful `rain` is the distance between the
+    /// expected start and target fitness divided by the number of steps.
+    pub fn new<F>(bounds:Vec<(f64, f64)>, rain:f64, fitness:F) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        GreatDeluge {rain, mutation:RealMutation::default(), level:0.0, current:None, search:Search::new(bounds, fitness)}
+    }
+
+    pub fn set_objective(&mut self, objective:Objective)
+    {
+        self.search.objective = objective;
+    }
+
+    pub fn set_seed(&mut self, seed:u64)
+    {
+        self.search.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn history(&self) -> &[GenomeStats]
+    {
+        &self.search.history
+    }
+
+    /// Current water level, meaningless before the first step.
+    pub fn level(&self) -> f64
+    {
+        self.level
+    }
+}
+
+impl Optimizer for GreatDeluge
+{
+    type Solution = RealChromosome;
+
+    fn step(&mut self)
+    {
+        let current = match self.current.take() {
+            None => {
+                let genes = self.search.random();
+                let first = self.search.evaluate(genes);
+                self.level = first.fitness;
+                first
+            }
+            Some(current) => {
+                let genes = self.search.neighbour(&current.genes, self.mutation);
+                let candidate = self.search.evaluate(genes);
+                let accepted = self.search.accepts(candidate.fitness, self.level) || self.search.accepts(candidate.fitness, current.fitness);
+                self.level = match self.search.objective {
+                    Objective::Maximize => self.level + self.rain,
+                    Objective::Minimize => self.level - self.rain,
+                };
+                if accepted { candidate } else { current }
+            }
+        };
+        self.search.record(current.fitness, current.fitness);
+        self.current = Some(current);
+    }
+
+    fn best(&self) -> Option<&RealChromosome>
+    {
+        self.search.best.as_ref()
+    }
+
+    fn stats(&self) -> Option<GenomeStats>
+    {
+        self.search.history.last().copied()
+    }
+
+    fn objective(&self) -> Objective
+    {
+        self.search.objective
+    }
+}
+
+/// Hill climbing that also accepts a neighbour no worse than the current solution was
+/// `length` steps ago, so a longer history explores more before settling.
+#[derive(Debug, Clone)]
+pub struct LateAcceptance
+{
+    /// How a neighbour's single changed gene is moved, Gaussian with sigma 0.1 by default.
+    pub mutation:RealMutation,
+    length:usize,
+    // fitness of the current solution over the last `length` steps, indexed by step modulo `length`
+    recent:Vec<f64>,
+    current:Option<RealChromosome>,
+    search:Search,
+}
+
+impl LateAcceptance
+{
+    /// Maximizes `fitness` within `bounds`, comparing against the current fitness `length`
+    /// steps back.
+    pub fn new<F>(bounds:Vec<(f64, f64)>, length:usize, fitness:F) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        assert!(length >= 1, "late acceptance needs a history of at least one step");
+        LateAcceptance {mutation:RealMutation::default(), length, recent:Vec::new(), current:None, search:Search::new(bounds, fitness)}
+    }
+
+    pub fn set_objective(&mut self, objective:Objective)
+    {
+        self.search.objective = objective;
+    }
+
+    pub fn set_seed(&mut self, seed:u64)
+    {
+        self.search.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn history(&self) -> &[GenomeStats]
+    {
+        &self.search.history
+    }
+}
+
+impl Optimizer for LateAcceptance
+{
+    type Solution = RealChromosome;
+
+    fn step(&mut self)
+    {
+        let current = match self.current.take() {
+            None => {
+                let genes = self.search.random();
+                let first = self.search.evaluate(genes);
+                self.recent = vec![first.fitness; self.length];
+                first
+            }
+            Some(current) => {
+                let genes = self.search.neighbour(&current.genes, self.mutation);
+                let candidate = self.search.evaluate(genes);
+                let slot = self.search.period as usize % self.length;
+                let accepted = self.search.accepts(candidate.fitness, self.recent[slot]) || self.search.accepts(candidate.fitness, current.fitness);
+                let current = if accepted { candidate } else { current };
+                self.recent[slot] = current.fitness;
+                current
+            }
+        };
+        self.search.record(current.fitness, current.fitness);
+        self.current = Some(current);
+    }
+
+    fn best(&self) -> Option<&RealChromosome>
+    {
+        self.search.best.as_ref()
+    }
+
+    fn stats(&self) -> Option<GenomeStats>
+    {
+        self.search.history.last().copied()
+    }
+
+    fn objective(&self) -> Objective
+    {
+        self.search.objective
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere(x:&[f64]) -> f64
+    {
+        x.iter().map(|v| v * v).sum()
+    }
+
+    fn solve<O:Optimizer<Solution = RealChromosome>>(mut optimizer:O, steps:u32) -> f64
+    {
+        let trace = optimizer.run_traced(steps);
+        assert_eq!(trace.len(), steps as usize);
+        assert!(trace.windows(2).all(|w| w[1].1 <= w[0].1));
+        optimizer.best().unwrap().fitness
+    }
+
+    #[test]
+    fn pack_minimizes_sphere()
+    {
+        let bounds = vec![(-5.0, 5.0); 3];
+        let mut harmony = HarmonySearch::new(bounds.clone(), 10, sphere);
+        harmony.set_objective(Objective::Minimize);
+        let mut deluge = GreatDeluge::new(bounds.clone(), 0.02, sphere);
+        deluge.set_objective(Objective::Minimize);
+        let mut late = LateAcceptance::new(bounds, 20, sphere);
+        late.set_objective(Objective::Minimize);
+
+        let harmony = solve(harmony, 4000);
+        assert!(harmony < 0.1, "harmony search {}", harmony);
+        let deluge = solve(deluge, 4000);
+        assert!(deluge < 0.1, "great deluge {}", deluge);
+        let late = solve(late, 4000);
+        assert!(late < 0.1, "late acceptance {}", late);
+    }
+
+    #[test]
+    fn seeded_searches_repeat()
+    {
+        let seeded = |seed| {
+            let mut late = LateAcceptance::new(vec![(0.0, 1.0); 2], 5, |x:&[f64]| x[0] - x[1]);
+            late.set_seed(seed);
+            late.run(50);
+            late.best().unwrap().clone()
+        };
+        assert_eq!(seeded(3), seeded(3));
+        let mut harmony = HarmonySearch::new(vec![(0.0, 1.0)], 4, |x:&[f64]| x[0]);
+        harmony.step();
+        assert_eq!((harmony.memory().len(), harmony.evaluations()), (4, 4));
+    }
+}
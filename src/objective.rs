@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Whether larger or smaller `Chromosome::fitness` values are better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Objective
+{
+    #[default]
+    Maximize,
+    Minimize,
+}
+
+impl Objective
+{
+    pub fn is_better(self, a:f64, b:f64) -> bool
+    {
+        match self {
+            Objective::Maximize => a > b,
+            Objective::Minimize => a < b,
+        }
+    }
+
+    /// Whether `a` beats `b` by more than `epsilon`, differences up to `epsilon` being ties.
+    pub fn improves_on(self, a:f64, b:f64, epsilon:f64) -> bool
+    {
+        match self {
+            Objective::Maximize => a - b > epsilon,
+            Objective::Minimize => b - a > epsilon,
+        }
+    }
+
+    /// Ordering that sorts the best fitness first.
+    pub fn best_first(self, a:f64, b:f64) -> Ordering
+    {
+        match self {
+            Objective::Maximize => b.total_cmp(&a),
+            Objective::Minimize => a.total_cmp(&b),
+        }
+    }
+
+    pub fn best<I:IntoIterator<Item = f64>>(self, values:I) -> Option<f64>
+    {
+        values.into_iter().min_by(|a, b| self.best_first(*a, *b))
+    }
+
+    /// The value assigned to individuals that could not be evaluated.
+    pub fn worst_value(self) -> f64
+    {
+        match self {
+            Objective::Maximize => f64::MIN,
+            Objective::Minimize => f64::MAX,
+        }
+    }
+
+    pub fn best_value(self) -> f64
+    {
+        match self {
+            Objective::Maximize => f64::MAX,
+            Objective::Minimize => f64::MIN,
+        }
+    }
+
+    /// Applies a constraint penalty so that it always makes the fitness worse, leaving the
+    /// fitness untouched when there is none.
+    pub fn penalize(self, fitness:f64, penalty:f64) -> f64
+    {
+        match self {
+            _ if penalty <= 0.0 => fitness,
+            Objective::Maximize => fitness - penalty,
+            Objective::Minimize => fitness + penalty,
+        }
+    }
+
+    /// Non-negative roulette weights, larger is better. When minimizing each weight is the
+    /// distance to the worst fitness of the generation, so the worst individual gets zero;
+    /// when maximizing it is the fitness itself, shifted up by the lowest one if that is
+    /// negative. Individuals holding `worst_value`, the ones that failed evaluation, get zero
+    /// too and do not count as the worst, which would flatten every other weight.
+    pub fn selection_weights(self, fitness:&[f64]) -> Vec<f64>
+    {
+        match self {
+            Objective::Maximize => {
+                let evaluated = |f:f64| f.is_finite() && f > self.worst_value();
+                let lowest = fitness.iter().copied().filter(|&f| evaluated(f)).fold(0.0, f64::min);
+                fitness.iter().map(|&f| if evaluated(f) { f - lowest } else { 0.0 }).collect()
+            }
+            Objective::Minimize => {
+                let evaluated = |f:f64| f.is_finite() && f < self.worst_value();
+                let worst = fitness.iter().copied().filter(|&f| evaluated(f)).fold(f64::MIN, f64::max);
+                fitness.iter().map(|&f| if evaluated(f) { worst - f } else { 0.0 }).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimize_prefers_small_values()
+    {
+        assert!(Objective::Minimize.is_better(1.0, 2.0));
+        assert!(Objective::Maximize.is_better(2.0, 1.0));
+        assert!(!Objective::Maximize.improves_on(1.0 + 1e-12, 1.0, 1e-9));
+        assert!(Objective::Minimize.improves_on(1.0, 1.1, 1e-9));
+        assert!(!Objective::Minimize.improves_on(1.1, 1.0, 1e-9));
+        assert_eq!(Objective::Minimize.best([3.0, 1.0, 2.0]), Some(1.0));
+        assert_eq!(Objective::Maximize.best([3.0, 1.0, 2.0]), Some(3.0));
+        assert_eq!(Objective::Minimize.selection_weights(&[3.0, 1.0, 2.0]), vec![0.0, 2.0, 1.0]);
+        assert_eq!(Objective::Minimize.selection_weights(&[3.0, f64::MAX, 1.0]), vec![0.0, 0.0, 2.0]);
+        assert_eq!(Objective::Minimize.penalize(1.0, 2.0), 3.0);
+        assert_eq!(Objective::Maximize.penalize(1.0, 2.0), -1.0);
+        assert_eq!(Objective::Maximize.penalize(-1.0, 0.0), -1.0);
+        assert_eq!(Objective::Maximize.selection_weights(&[3.0, 1.0, 2.0]), vec![3.0, 1.0, 2.0]);
+        assert_eq!(Objective::Maximize.selection_weights(&[-3.0, f64::MIN, -1.0]), vec![0.0, 0.0, 2.0]);
+    }
+}
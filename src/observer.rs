@@ -0,0 +1,132 @@
+//! Hooks into every generation of a `Run`, for streaming metrics, custom early stopping or
+//! taking snapshots from outside the crate. Observers only get read access; like the operator
+//! traits they take `&self`, so one that keeps state needs a `Mutex`, an atomic or a channel.
+use crate::events::LifecycleEvent;
+use crate::evolution::GenomeStats;
+use crate::population::PopulationView;
+use crate::stats::GenerationStats;
+use crate::Run;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Every method has an empty default, implement the ones you need.
+pub trait Observer: Debug + Send + Sync
+{
+    /// Before anything happens in `generation`, counted from 1.
+    fn on_generation_start(&self, _generation:u32) {}
+
+    /// Once the population of `generation` is evaluated, before it reproduces.
+    fn on_evaluated(&self, _generation:u32, _population:PopulationView) {}
+
+    /// With the stats of a finished generation. Returning `true` ends the run there, like a
+    /// `Termination` criterion.
+    fn on_generation_end(&self, _stats:&GenerationStats) -> bool
+    {
+        false
+    }
+
+    /// With every lifecycle event the run emits, after the event callback.
+    fn on_event(&self, _event:&LifecycleEvent) {}
+}
+
+/// The `Observer` of a `GenomeRun`, which has no `PopulationView` or `GenerationStats` to pass.
+pub trait GenomeObserver<G>: Send + Sync
+{
+    /// Before anything happens in `generation`, counted from 1.
+    fn on_generation_start(&self, _generation:u32) {}
+
+    /// Once the population of `generation` is evaluated, before it reproduces.
+    fn on_evaluated(&self, _generation:u32, _population:&[G]) {}
+
+    /// With the stats of a finished generation. Returning `true` ends the run there.
+    fn on_generation_end(&self, _stats:&GenomeStats) -> bool
+    {
+        false
+    }
+}
+
+impl Run
+{
+    /// Observers are called in the order they were added.
+    pub fn add_observer(&mut self, observer:Box<dyn Observer>)
+    {
+        self.observers.push(Arc::from(observer));
+    }
+
+    pub(crate) fn notify_start(&self)
+    {
+        self.observers.iter().for_each(|o| o.on_generation_start(self.period));
+    }
+
+    pub(crate) fn notify_evaluated(&self)
+    {
+        self.observers.iter().for_each(|o| o.on_evaluated(self.period, self.population()));
+    }
+
+    // every observer sees the stats even when an earlier one already asked to stop
+    pub(crate) fn notify_end(&mut self, stats:&GenerationStats)
+    {
+        self.stop_requested = self.observers.iter().fold(false, |stop, o| o.on_generation_end(stats) | stop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Recorder
+    {
+        calls:Mutex<Vec<String>>,
+        best:Mutex<Vec<f64>>,
+    }
+
+    #[derive(Debug)]
+    struct Shared(Arc<Recorder>);
+
+    impl Observer for Shared
+    {
+        fn on_generation_start(&self, generation:u32)
+        {
+            self.0.calls.lock().unwrap().push(format!("start {}", generation));
+        }
+
+        fn on_evaluated(&self, generation:u32, population:PopulationView)
+        {
+            assert_eq!(population.len(), 10);
+            let best = population.fitness().iter().copied().fold(f64::MIN, f64::max);
+            self.0.best.lock().unwrap().push(best);
+            self.0.calls.lock().unwrap().push(format!("evaluated {}", generation));
+        }
+
+        fn on_generation_end(&self, stats:&GenerationStats) -> bool
+        {
+            self.0.calls.lock().unwrap().push(format!("end {}", stats.generation));
+            stats.generation.is_multiple_of(3)
+        }
+    }
+
+    #[test]
+    fn observers_see_each_generation_and_can_stop()
+    {
+        let recorder = Arc::new(Recorder::default());
+        let mut run = Run::with_seed(0.6, 0.05, 8, 10, 4, 3).unwrap();
+        run.set_fitness(problems::onemax_fitness);
+        run.add_observer(Box::new(Shared(recorder.clone())));
+        assert_eq!(run.run(20).unwrap().stop_reason, crate::StopReason::Observer);
+        assert_eq!(run.generation(), 3);
+        assert_eq!(recorder.calls.lock().unwrap()[..3], ["start 1", "evaluated 1", "end 1"]);
+        assert_eq!(recorder.calls.lock().unwrap().len(), 9);
+        let best = recorder.best.lock().unwrap();
+        assert_eq!(best.len(), 3);
+        assert_eq!(best.iter().copied().fold(f64::MIN, f64::max), run.best().unwrap().fitness);
+        drop(best);
+
+        // `step` still notifies, and a request to stop only holds for the generation that made it
+        run.step();
+        assert_eq!(recorder.calls.lock().unwrap().len(), 12);
+        assert_eq!(run.generations().count(), 2);
+    }
+}
@@ -0,0 +1,488 @@
+//! Object-safe operator traits. Every trait can be used as `Box<dyn ...>`, so a pipeline can be
+//! put together at runtime (from a config file or a plugin) and handed to `Run`. An operator
+//! that is not set keeps the run's built-in behaviour.
+use crate::objective::Objective;
+use crate::result::StopReason;
+use crate::stats::{GenerationStats, PopulationStats};
+use crate::{ops, Chromosome, FitnessFn, Run};
+use rand::{Rng, RngCore};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Fitness of `ind` in the population described by `context`. Plain `FitnessFn`s only see
+/// `context.data_sum`; implement the trait to use the rest.
+pub trait Fitness: Debug + Send + Sync
+{
+    fn evaluate(&self, ind:&Chromosome, context:&PopulationStats) -> f64;
+}
+
+impl Fitness for FitnessFn
+{
+    fn evaluate(&self, ind:&Chromosome, context:&PopulationStats) -> f64
+    {
+        self(ind, context.data_sum)
+    }
+}
+
+/// Picks `count` parent indices. `weights` are non-negative and larger is better whatever the
+/// objective, see `Objective::selection_weights`.
+pub trait Selection: Debug + Send + Sync
+{
+    fn select(&self, weights:&[f64], count:usize, rng:&mut dyn RngCore) -> Vec<usize>;
+}
+
+/// Recombines two `L`-bit genomes, called only for pairs that cross.
+pub trait Crossover: Debug + Send + Sync
+{
+    fn crossover(&self, a:u64, b:u64, L:u8, rng:&mut dyn RngCore) -> (u64, u64);
+}
+
+/// Mutates the whole population of `L`-bit genomes in place.
+pub trait Mutation: Debug + Send + Sync
+{
+    fn mutate(&self, data:&mut [u64], L:u8, rng:&mut dyn RngCore);
+}
+
+/// Ends a run early once the stats of a generation meet the criterion.
+pub trait Termination: Debug + Send + Sync
+{
+    fn should_stop(&self, stats:&GenerationStats) -> bool;
+
+    /// How the run reports this criterion when it fires.
+    fn reason(&self) -> StopReason
+    {
+        StopReason::Criterion(format!("{:?}", self))
+    }
+}
+
+/// Fitness-proportionate selection, uniform when all weights are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Roulette;
+
+impl Selection for Roulette
+{
+    fn select(&self, weights:&[f64], count:usize, rng:&mut dyn RngCore) -> Vec<usize>
+    {
+        let total:f64 = weights.iter().sum();
+        let probabilities:Vec<f64> = if total > 0.0 && total.is_finite() {
+            weights.iter().map(|w| w / total).collect()
+        }else{
+            vec![1.0 / weights.len() as f64; weights.len()]
+        };
+        ops::select(&probabilities, count, rng)
+    }
+}
+
+/// Each parent is the best of `size` individuals drawn uniformly with replacement. Only the
+/// order of the weights matters, so near-equal fitness values keep their selection pressure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tournament
+{
+    pub size:usize,
+}
+
+impl Selection for Tournament
+{
+    fn select(&self, weights:&[f64], count:usize, rng:&mut dyn RngCore) -> Vec<usize>
+    {
+        (0..count).map(|_| {
+            (0..self.size.max(1))
+                .map(|_| rng.gen_range(0..weights.len()))
+                .reduce(|best, i| if weights[i] > weights[best] { i } else { best })
+                .unwrap()
+        }).collect()
+    }
+}
+
+// indices sorted from the best (largest) weight to the worst, ties keep their order
+fn ranked(weights:&[f64]) -> Vec<usize>
+{
+    let mut order:Vec<usize> = (0..weights.len()).collect();
+    order.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+    order
+}
+
+/// Linear ranking: the best individual is picked with probability `pressure / n`, the worst
+/// with `(2 - pressure) / n` and the ranks in between are interpolated. `pressure` lies in
+/// `1.0..=2.0`, one is uniform selection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearRank
+{
+    pub pressure:f64,
+}
+
+impl Selection for LinearRank
+{
+    fn select(&self, weights:&[f64], count:usize, rng:&mut dyn RngCore) -> Vec<usize>
+    {
+        let n = weights.len() as f64;
+        let pressure = self.pressure.clamp(1.0, 2.0);
+        let mut probabilities = vec![0.0; weights.len()];
+        for (rank, i) in ranked(weights).into_iter().enumerate() {
+            let position = if n > 1.0 { 1.0 - rank as f64 / (n - 1.0) } else { 0.5 };
+            probabilities[i] = (2.0 - pressure + 2.0 * (pressure - 1.0) * position) / n;
+        }
+        ops::select(&probabilities, count, rng)
+    }
+}
+
+/// Exponential ranking: the probability falls by a factor `e^-pressure` from the best to the
+/// worst rank, so the pressure is unbounded. Zero is uniform selection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExponentialRank
+{
+    pub pressure:f64,
+}
+
+impl Selection for ExponentialRank
+{
+    fn select(&self, weights:&[f64], count:usize, rng:&mut dyn RngCore) -> Vec<usize>
+    {
+        let steps = weights.len().saturating_sub(1).max(1) as f64;
+        let mut probabilities = vec![0.0; weights.len()];
+        for (rank, i) in ranked(weights).into_iter().enumerate() {
+            probabilities[i] = (-self.pressure.max(0.0) * rank as f64 / steps).exp();
+        }
+        let total:f64 = probabilities.iter().sum();
+        probabilities.iter_mut().for_each(|p| *p /= total);
+        ops::select(&probabilities, count, rng)
+    }
+}
+
+/// The run's original crossover, swapping the low `z` bits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TailSwap
+{
+    pub z:u8,
+}
+
+impl Crossover for TailSwap
+{
+    fn crossover(&self, a:u64, b:u64, L:u8, rng:&mut dyn RngCore) -> (u64, u64)
+    {
+        let children = ops::crossover(&[a, b], &[(0, 1)], 1.0, L, self.z, rng);
+        (children[0], children[1])
+    }
+}
+
+/// Swaps the bits below a cut drawn uniformly from `1..L`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SinglePoint;
+
+impl Crossover for SinglePoint
+{
+    fn crossover(&self, a:u64, b:u64, L:u8, rng:&mut dyn RngCore) -> (u64, u64)
+    {
+        if L < 2 {
+            return (a, b);
+        }
+        ops::swap_bits(a, b, ops::low_mask(rng.gen_range(1..L)))
+    }
+}
+
+/// Swaps the bits between two distinct cuts drawn from `0..=L`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TwoPoint;
+
+impl Crossover for TwoPoint
+{
+    fn crossover(&self, a:u64, b:u64, L:u8, rng:&mut dyn RngCore) -> (u64, u64)
+    {
+        if L == 0 {
+            return (a, b);
+        }
+        let cuts = rand::seq::index::sample(rng, L as usize + 1, 2);
+        let (low, high) = (cuts.index(0).min(cuts.index(1)) as u8, cuts.index(0).max(cuts.index(1)) as u8);
+        ops::swap_bits(a, b, ops::low_mask(high) & !ops::low_mask(low))
+    }
+}
+
+/// Swaps each of the `L` bits independently with probability one half.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Uniform;
+
+impl Crossover for Uniform
+{
+    fn crossover(&self, a:u64, b:u64, L:u8, rng:&mut dyn RngCore) -> (u64, u64)
+    {
+        ops::swap_bits(a, b, rng.next_u64() & ops::low_mask(L))
+    }
+}
+
+/// Flips one random bit in each genome with probability `Pmut`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitFlip
+{
+    pub Pmut:f32,
+}
+
+impl Mutation for BitFlip
+{
+    fn mutate(&self, data:&mut [u64], L:u8, rng:&mut dyn RngCore)
+    {
+        let mutated = ops::mutate(data, self.Pmut, L, rng);
+        data.copy_from_slice(&mutated);
+    }
+}
+
+/// Stops after the given generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaxGenerations(pub u32);
+
+impl Termination for MaxGenerations
+{
+    fn should_stop(&self, stats:&GenerationStats) -> bool
+    {
+        stats.generation >= self.0
+    }
+
+    fn reason(&self) -> StopReason
+    {
+        StopReason::MaxGenerations
+    }
+}
+
+/// Stops once the run's best has not improved for the given number of generations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stagnation(pub u32);
+
+impl Termination for Stagnation
+{
+    fn should_stop(&self, stats:&GenerationStats) -> bool
+    {
+        stats.stagnant_generations >= self.0
+    }
+
+    fn reason(&self) -> StopReason
+    {
+        StopReason::Stagnation
+    }
+}
+
+/// Stops once a feasible individual reaches `target`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetFitness
+{
+    pub target:f64,
+    pub objective:Objective,
+}
+
+impl Termination for TargetFitness
+{
+    fn should_stop(&self, stats:&GenerationStats) -> bool
+    {
+        stats.best_feasible_fitness.is_some_and(|best| !self.objective.is_better(self.target, best))
+    }
+
+    fn reason(&self) -> StopReason
+    {
+        StopReason::TargetFitness
+    }
+}
+
+/// Stops at the first generation ending past a deadline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeLimit
+{
+    pub deadline:Instant,
+}
+
+impl TimeLimit
+{
+    /// A deadline `limit` from now.
+    pub fn from_now(limit:Duration) -> Self
+    {
+        TimeLimit {deadline:Instant::now() + limit}
+    }
+}
+
+impl Termination for TimeLimit
+{
+    fn should_stop(&self, _stats:&GenerationStats) -> bool
+    {
+        Instant::now() >= self.deadline
+    }
+
+    fn reason(&self) -> StopReason
+    {
+        StopReason::TimeLimit
+    }
+}
+
+/// Stops a run from another thread: keep a clone, add the other as a criterion and `cancel`
+/// when needed. The run ends after the generation in progress.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation
+{
+    cancelled:Arc<AtomicBool>,
+}
+
+impl Cancellation
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn cancel(&self)
+    {
+        self.cancelled.store(true, AtomicOrdering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool
+    {
+        self.cancelled.load(AtomicOrdering::Relaxed)
+    }
+}
+
+impl Termination for Cancellation
+{
+    fn should_stop(&self, _stats:&GenerationStats) -> bool
+    {
+        self.is_cancelled()
+    }
+
+    fn reason(&self) -> StopReason
+    {
+        StopReason::Cancelled
+    }
+}
+
+impl Run
+{
+    pub fn set_fitness_function(&mut self, fitness:Box<dyn Fitness>)
+    {
+        self.fitness = Arc::from(fitness);
+        self.custom_fitness = true;
+    }
+
+    /// Replaces roulette or stochastic-ranking selection.
+    pub fn set_selection(&mut self, selection:Box<dyn Selection>)
+    {
+        self.selection = Some(Arc::from(selection));
+    }
+
+    /// Replaces the tail swap, e.g. with `SinglePoint`, `TwoPoint` or `Uniform`; `Pcross` still
+    /// decides which pairs cross.
+    pub fn set_crossover(&mut self, crossover:Box<dyn Crossover>)
+    {
+        self.crossover = Some(Arc::from(crossover));
+    }
+
+    /// Replaces the built-in mutation, `Pmut` is then up to the operator.
+    pub fn set_mutation(&mut self, mutation:Box<dyn Mutation>)
+    {
+        self.mutation = Some(Arc::from(mutation));
+    }
+
+    /// The run stops at the first generation where any termination criterion fires.
+    pub fn add_termination(&mut self, termination:Box<dyn Termination>)
+    {
+        self.termination.push(Arc::from(termination));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roulette_falls_back_to_uniform()
+    {
+        let mut rng = rand::thread_rng();
+        assert_eq!(Roulette.select(&[0.0, 3.0], 5, &mut rng), vec![1; 5]);
+        assert!(Roulette.select(&[0.0, 0.0], 5, &mut rng).iter().all(|i| *i < 2));
+    }
+
+    #[test]
+    fn crossovers_recombine_within_genome()
+    {
+        let mut rng = rand::thread_rng();
+        let (a, b) = (0b1010_1010_u64 | 1 << 40, 0b0110_0101_u64);
+        let operators:[&dyn Crossover; 4] = [&SinglePoint, &TwoPoint, &Uniform, &TailSwap{z:3}];
+        for operator in operators {
+            for _ in 0..50 {
+                let (c, d) = operator.crossover(a, b, 8, &mut rng);
+                assert_eq!((c & d, c ^ d), (a & b, a ^ b), "{:?}", operator);
+                assert_eq!(c >> 8, a >> 8, "{:?} touched bits above L", operator);
+            }
+        }
+        let (c, _) = SinglePoint.crossover(0, u64::MAX, 8, &mut rng);
+        assert!(c.trailing_ones() >= 1 && c.count_ones() == c.trailing_ones() && c < 0xff);
+    }
+
+    #[test]
+    fn tournament_prefers_better_weights()
+    {
+        let mut rng = rand::thread_rng();
+        let weights = [1.0, 1.001, 1.002, 1.003];
+        let picks = Tournament{size:4}.select(&weights, 1000, &mut rng);
+        let best = picks.iter().filter(|&&i| i == 3).count();
+        // 1 - (3/4)^4 of the tournaments contain the best individual
+        assert!(best > 600, "best picked {} times", best);
+        assert!(Tournament{size:1}.select(&weights, 100, &mut rng).iter().any(|&i| i != 3));
+    }
+
+    #[test]
+    fn rank_selection_ignores_fitness_scale()
+    {
+        let mut rng = rand::thread_rng();
+        // one individual dominates the total fitness, ranking keeps the others in play
+        let weights = [1e9, 1.0, 2.0, 3.0];
+        let picks = LinearRank{pressure:2.0}.select(&weights, 1000, &mut rng);
+        let best = picks.iter().filter(|&&i| i == 0).count();
+        assert!((400..600).contains(&best), "best picked {} times", best);
+        assert!(!picks.contains(&1));
+
+        let uniform = ExponentialRank{pressure:0.0}.select(&weights, 1000, &mut rng);
+        assert!((0..4).all(|i| uniform.contains(&i)));
+        let steep = ExponentialRank{pressure:20.0}.select(&weights, 1000, &mut rng);
+        assert!(steep.iter().filter(|&&i| i == 0).count() > 900);
+    }
+
+    #[test]
+    fn fitness_sees_population_context()
+    {
+        #[derive(Debug)]
+        struct Context;
+        impl Fitness for Context
+        {
+            fn evaluate(&self, _ind:&Chromosome, context:&PopulationStats) -> f64
+            {
+                (context.generation as usize * 100 + context.size) as f64
+            }
+        }
+
+        let mut test_run = Run::new(0.6, 0.1, 10, 20, 4).unwrap();
+        test_run.set_fitness_function(Box::new(Context));
+        test_run.run(5).unwrap();
+        assert_eq!(test_run.best().unwrap().fitness, 520.0);
+        assert_eq!(PopulationStats::new(4, 10).mean(), 2.5);
+    }
+
+    #[test]
+    fn dynamic_pipeline()
+    {
+        fn ones(ind:&Chromosome, _data_sum:u64) -> f64
+        {
+            ind.data.count_ones() as f64
+        }
+
+        let pipeline:(Box<dyn Selection>, Box<dyn Crossover>, Box<dyn Mutation>) =
+            (Box::new(Roulette), Box::new(TailSwap{z:4}), Box::new(BitFlip{Pmut:0.1}));
+        let mut test_run = Run::new(0.6, 0.1, 10, 20, 4).unwrap();
+        test_run.set_fitness_function(Box::new(ones as FitnessFn));
+        test_run.set_selection(pipeline.0);
+        test_run.set_crossover(pipeline.1);
+        test_run.set_mutation(pipeline.2);
+        test_run.add_termination(Box::new(TargetFitness{target:10.0, objective:Objective::Maximize}));
+        test_run.add_termination(Box::new(MaxGenerations(50)));
+        test_run.run(1000).unwrap();
+
+        assert!(test_run.generation() <= 50);
+        let last = test_run.history().last().unwrap();
+        assert!(last.generation == 50 || last.best_feasible_fitness == Some(10.0));
+    }
+}
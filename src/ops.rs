@@ -0,0 +1,194 @@
+//! The phases of a generation as standalone functions over slices. They never touch a `Run`,
+//! take their randomness from the caller and return new vectors, so they can be benchmarked
+//! one by one or chained into a custom loop.
+use crate::sampling;
+use crate::{Chromosome, FitnessFn};
+use rand::Rng;
+
+/// Raw fitness of every genome against the market formed by all of `data`. `N` is passed on
+/// to the fitness function; constraints and penalties are not applied.
+pub fn evaluate(data:&[u64], N:usize, fitness:FitnessFn) -> Vec<f64>
+{
+    let data_sum = crate::sum_data(data);
+    data.iter()
+        .map(|&data| fitness(&Chromosome {data, fitness:0.0, N, violation:0.0}, data_sum))
+        .collect()
+}
+
+/// Roulette-wheel selection of `count` indices, `probabilities` should sum to one.
+/// Draws that fall past the end of the wheel pick the last individual.
+pub fn select<R:Rng + ?Sized>(probabilities:&[f64], count:usize, rng:&mut R) -> Vec<usize>
+{
+    (0..count).map(|_| {
+        let rand_f:f64 = rng.gen();
+        let mut cumulative_sum = 0.0;
+        for (i, probability) in probabilities.iter().enumerate() {
+            cumulative_sum += probability;
+            if cumulative_sum >= rand_f {
+                return i;
+            }
+        }
+        probabilities.len() - 1
+    }).collect()
+}
+
+/// Random mating pairs covering `0..n` once each, `n` must be even.
+pub fn pair_up<R:Rng + ?Sized>(n:usize, rng:&mut R) -> Vec<(usize, usize)>
+{
+    assert!(n.is_multiple_of(2), "pairing needs an even population, got {}", n);
+    let mut pairs = Vec::with_capacity(n / 2);
+    let mut paired = vec![false; n];
+
+    for i in 0..n {
+        if paired[i] {
+            continue;
+        }
+        let mut partner = rng.gen_range(0..n);
+        while paired[partner] || partner == i {
+            partner = rng.gen_range(0..n);
+        }
+        paired[i] = true;
+        paired[partner] = true;
+        pairs.push((i, partner));
+    }
+    pairs
+}
+
+/// Children of `pairs` in order, two per pair. A pair crosses with probability `Pcross` by
+/// swapping the low `z` bits of its `L`-bit genomes, otherwise the parents are copied.
+pub fn crossover<R:Rng + ?Sized>(data:&[u64], pairs:&[(usize, usize)], Pcross:f32, L:u8, z:u8, rng:&mut R) -> Vec<u64>
+{
+    let mut children:Vec<u64> = pairs.iter().flat_map(|&(a, b)| [data[a], data[b]]).collect();
+    let mask = low_mask(z.min(L));
+    for k in sampling::bernoulli_indices(rng, Pcross as f64, pairs.len()) {
+        (children[2*k], children[2*k + 1]) = swap_bits(children[2*k], children[2*k + 1], mask);
+    }
+    children
+}
+
+/// Mask of the low `bits` bits.
+pub fn low_mask(bits:u8) -> u64
+{
+    if bits >= 64 { u64::MAX } else { (1 << bits) - 1 }
+}
+
+/// Exchanges the bits of `a` and `b` selected by `mask`.
+pub fn swap_bits(a:u64, b:u64, mask:u64) -> (u64, u64)
+{
+    let diff = (a ^ b) & mask;
+    (a ^ diff, b ^ diff)
+}
+
+/// Copy of `data` where each genome has one of its `L` bits flipped with probability `Pmut`.
+pub fn mutate<R:Rng + ?Sized>(data:&[u64], Pmut:f32, L:u8, rng:&mut R) -> Vec<u64>
+{
+    let mut mutated = data.to_vec();
+    for i in sampling::bernoulli_indices(rng, Pmut as f64, data.len()) {
+        mutated[i] ^= 1 << rng.gen_range(0..L);
+    }
+    mutated
+}
+
+/// Copy of `data` where each of the `L` bits of every genome is flipped independently with
+/// probability `Pmut`.
+pub fn mutate_per_locus<R:Rng + ?Sized>(data:&[u64], Pmut:f32, L:u8, rng:&mut R) -> Vec<u64>
+{
+    let mut mutated = data.to_vec();
+    let L = L as usize;
+    for bit in sampling::bernoulli_indices(rng, Pmut as f64, data.len() * L) {
+        mutated[bit / L] ^= 1 << (bit % L);
+    }
+    mutated
+}
+
+/// How the built-in mutation reads `Pmut`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MutationScheme
+{
+    /// Each genome has one random bit flipped with probability `Pmut`, see `mutate`.
+    #[default]
+    PerIndividual,
+    /// Each bit is flipped with probability `Pmut`, see `mutate_per_locus`. `1 / L` flips one
+    /// bit per genome on average whatever the genome length.
+    PerLocus,
+}
+
+impl MutationScheme
+{
+    pub fn apply<R:Rng + ?Sized>(self, data:&[u64], Pmut:f32, L:u8, rng:&mut R) -> Vec<u64>
+    {
+        match self {
+            MutationScheme::PerIndividual => mutate(data, Pmut, L, rng),
+            MutationScheme::PerLocus => mutate_per_locus(data, Pmut, L, rng),
+        }
+    }
+
+    /// Mean number of bits the built-in mutation flips in one `L`-bit genome.
+    pub fn expected_flips(self, Pmut:f32, L:u8) -> f64
+    {
+        match self {
+            MutationScheme::PerIndividual => Pmut as f64,
+            MutationScheme::PerLocus => Pmut as f64 * L as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_follows_probabilities()
+    {
+        let mut rng = rand::thread_rng();
+        assert_eq!(select(&[0.0, 1.0, 0.0], 10, &mut rng), vec![1; 10]);
+    }
+
+    #[test]
+    fn pair_up_covers_everyone_once()
+    {
+        let mut rng = rand::thread_rng();
+        let mut seen:Vec<usize> = pair_up(10, &mut rng).into_iter().flat_map(|(a, b)| [a, b]).collect();
+        seen.sort();
+        assert_eq!(seen, (0..10).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn phases_leave_input_alone()
+    {
+        let mut rng = rand::thread_rng();
+        let data = vec![0b1111_0000, 0b0000_1111];
+        assert_eq!(crossover(&data, &[(0, 1)], 0.0, 8, 4, &mut rng), data);
+        assert_eq!(mutate(&data, 0.0, 8, &mut rng), data);
+        let mutated = mutate(&data, 1.0, 8, &mut rng);
+        assert!(mutated.iter().zip(&data).all(|(m, d)| (m ^ d).count_ones() == 1));
+        assert_eq!(data, vec![0b1111_0000, 0b0000_1111]);
+    }
+
+    #[test]
+    fn crossover_swaps_low_bits()
+    {
+        let mut rng = rand::thread_rng();
+        let children = crossover(&[0b1111_0000, 0b0000_1111], &[(0, 1)], 1.0, 8, 3, &mut rng);
+        assert_eq!(children, vec![0b1111_0111, 0b0000_1000]);
+        assert_eq!(swap_bits(0b1100, 0b1010, low_mask(64)), (0b1010, 0b1100));
+    }
+
+    #[test]
+    fn per_locus_flips_each_bit()
+    {
+        let mut rng = rand::thread_rng();
+        let data = vec![0; 100];
+        assert_eq!(mutate_per_locus(&data, 1.0, 12, &mut rng), vec![0xfff; 100]);
+        let flips:u32 = MutationScheme::PerLocus.apply(&data, 0.25, 40, &mut rng).iter().map(|d| d.count_ones()).sum();
+        // 4000 trials, mean 1000 and standard deviation about 27
+        assert!((850..1150).contains(&flips), "{} flips", flips);
+    }
+
+    #[test]
+    fn evaluate_matches_fitness_fn()
+    {
+        let fitness = evaluate(&[3, 7], 2, crate::problems::onemax_fitness);
+        assert_eq!(fitness, vec![2.0, 3.0]);
+    }
+}
@@ -0,0 +1,185 @@
+//! A common interface for the iterative optimizers in the crate, so experiment code can drive
+//! and compare them without knowing which algorithm it holds. One `step` is one iteration of
+//! the method, a generation for population methods or a single move for single-solution ones.
+use crate::aco::AntColony;
+use crate::bits::BitGenome;
+use crate::evolution::{Genome, GenomeRun, GenomeStats};
+use crate::harness::{trace_of, Trace};
+use crate::objective::Objective;
+use crate::pbil::Pbil;
+use crate::permutation::PermutationChromosome;
+use crate::{Chromosome, Run};
+
+pub trait Optimizer
+{
+    type Solution;
+
+    fn step(&mut self);
+    /// Best solution evaluated so far, `None` before the first step.
+    fn best(&self) -> Option<&Self::Solution>;
+    /// Summary of the last step, `None` before the first.
+    fn stats(&self) -> Option<GenomeStats>;
+    fn objective(&self) -> Objective;
+
+    fn evaluations(&self) -> usize
+    {
+        self.stats().map_or(0, |stats| stats.evaluations)
+    }
+
+    fn run(&mut self, iterations:u32)
+    {
+        for _ in 0..iterations {
+            self.step();
+        }
+    }
+
+    /// Like `run`, returning the best fitness so far after each step.
+    fn run_traced(&mut self, iterations:u32) -> Trace
+    {
+        let mut steps = Vec::with_capacity(iterations as usize);
+        for _ in 0..iterations {
+            self.step();
+            steps.extend(self.stats());
+        }
+        trace_of(&steps, self.objective())
+    }
+}
+
+impl Optimizer for Run
+{
+    type Solution = Chromosome;
+
+    /// One generation, see `Run::step`.
+    fn step(&mut self)
+    {
+        Run::step(self);
+    }
+
+    fn best(&self) -> Option<&Chromosome>
+    {
+        Run::best(self)
+    }
+
+    fn stats(&self) -> Option<GenomeStats>
+    {
+        self.last_step
+    }
+
+    fn objective(&self) -> Objective
+    {
+        Run::objective(self)
+    }
+}
+
+impl<G:Genome> Optimizer for GenomeRun<G>
+{
+    type Solution = G;
+
+    fn step(&mut self)
+    {
+        GenomeRun::run(self, 1);
+    }
+
+    fn best(&self) -> Option<&G>
+    {
+        GenomeRun::best(self)
+    }
+
+    fn stats(&self) -> Option<GenomeStats>
+    {
+        self.history().last().copied()
+    }
+
+    fn objective(&self) -> Objective
+    {
+        GenomeRun::objective(self)
+    }
+}
+
+impl Optimizer for Pbil
+{
+    type Solution = BitGenome;
+
+    fn step(&mut self)
+    {
+        Pbil::run(self, 1);
+    }
+
+    fn best(&self) -> Option<&BitGenome>
+    {
+        Pbil::best(self)
+    }
+
+    fn stats(&self) -> Option<GenomeStats>
+    {
+        self.history().last().copied()
+    }
+
+    fn objective(&self) -> Objective
+    {
+        Pbil::objective(self)
+    }
+}
+
+impl Optimizer for AntColony
+{
+    type Solution = PermutationChromosome;
+
+    fn step(&mut self)
+    {
+        AntColony::run(self, 1);
+    }
+
+    fn best(&self) -> Option<&PermutationChromosome>
+    {
+        AntColony::best(self)
+    }
+
+    fn stats(&self) -> Option<GenomeStats>
+    {
+        self.history().last().copied()
+    }
+
+    /// Tour costs are minimized.
+    fn objective(&self) -> Objective
+    {
+        Objective::Minimize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metaheuristics::GreatDeluge;
+    use crate::problems;
+    use crate::real::RealRun;
+
+    // best fitness reached after `steps` steps, whatever the algorithm
+    fn best_after<O:Optimizer>(optimizer:&mut O, steps:u32) -> f64
+    {
+        let trace = optimizer.run_traced(steps);
+        assert_eq!(trace.len(), steps as usize);
+        assert_eq!(trace.last().unwrap().0, optimizer.evaluations());
+        trace.last().unwrap().1
+    }
+
+    #[test]
+    fn algorithms_share_the_interface()
+    {
+        let mut run = Run::with_seed(0.6, 0.1, 8, 20, 4, 5).unwrap();
+        run.set_fitness(problems::onemax_fitness);
+        assert!(best_after(&mut run, 30) >= 7.0);
+        assert_eq!(Optimizer::stats(&run).unwrap().generation, 30);
+        assert_eq!(Optimizer::evaluations(&run), 600);
+
+        let sphere = |x:&[f64]| x.iter().map(|v| v * v).sum();
+        let mut real = RealRun::new(vec![(-5.0, 5.0); 2], 20, sphere);
+        real.set_objective(Objective::Minimize);
+        let mut deluge = GreatDeluge::new(vec![(-5.0, 5.0); 2], 0.05, sphere);
+        deluge.set_objective(Objective::Minimize);
+        assert!(best_after(&mut real, 50) < 0.5);
+        assert!(best_after(&mut deluge, 1000) < 0.5);
+        let mut pbil = Pbil::new(20, 10, |genome:&BitGenome| genome.count_ones() as f64);
+        assert!(best_after(&mut pbil, 50) >= 18.0);
+    }
+}
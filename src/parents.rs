@@ -0,0 +1,99 @@
+//! Optional record of the parents picked by selection each generation, for studying selection
+//! bias and effective population size. Slot `i` is the population slot at selection time.
+use crate::error::GaError;
+use crate::Run;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParentSelection
+{
+    pub generation:u32,
+    /// Population size at selection time.
+    pub size:usize,
+    /// Selected slots in the order they were drawn, a slot appears once per selection.
+    pub parents:Vec<usize>,
+}
+
+impl ParentSelection
+{
+    /// Times each slot was selected.
+    pub fn counts(&self) -> Vec<usize>
+    {
+        let mut counts = vec![0; self.size];
+        for &parent in &self.parents {
+            counts[parent] += 1;
+        }
+        counts
+    }
+}
+
+impl Run
+{
+    /// Starts or stops recording the selected parents, see `parent_log`.
+    pub fn record_parents(&mut self, record:bool)
+    {
+        self.parent_log = record.then(|| self.parent_log.take().unwrap_or_default());
+    }
+
+    /// Parents selected in every generation since recording started.
+    pub fn parent_log(&self) -> &[ParentSelection]
+    {
+        self.parent_log.as_deref().unwrap_or(&[])
+    }
+
+    pub(crate) fn log_parents(&mut self, parents:&[usize])
+    {
+        let (generation, size) = (self.period, self.population.len());
+        if let Some(log) = &mut self.parent_log {
+            log.push(ParentSelection {generation, size, parents:parents.to_vec()});
+        }
+    }
+}
+
+/// Writes one `generation,slot,count` row per slot selected at least once.
+pub fn save_parents_to_csv(log:&[ParentSelection], file_name:&str) -> Result<(), GaError>
+{
+    let file = File::create(file_name)?;
+    let mut writer = csv::Writer::from_writer(file);
+
+    writer.write_record(["generation", "slot", "count"])?;
+
+    for selection in log {
+        for (slot, count) in selection.counts().into_iter().enumerate().filter(|(_, count)| *count > 0) {
+            writer.write_record(&[selection.generation.to_string(), slot.to_string(), count.to_string()])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    #[test]
+    fn records_and_exports_parents()
+    {
+        let mut test_run = Run::new(0.6, 0.1, 8, 10, 4).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.run(2).unwrap();
+        assert!(test_run.parent_log().is_empty());
+        test_run.record_parents(true);
+        test_run.run(3).unwrap();
+        let log = test_run.parent_log();
+        assert_eq!(log.iter().map(|s| s.generation).collect::<Vec<u32>>(), vec![3, 4, 5]);
+        assert!(log.iter().all(|s| s.parents.len() == 10 && s.counts().iter().sum::<usize>() == 10));
+        assert!(test_run.history().iter().all(|s| s.effective_size.is_some_and(|ne| ne > 0.0)));
+
+        let file_name = std::env::temp_dir().join(format!("ga_parents_{}.csv", std::process::id()));
+        let file_name = file_name.to_str().unwrap();
+        save_parents_to_csv(log, file_name).unwrap();
+        let mut reader = csv::Reader::from_path(file_name).unwrap();
+        let total:usize = reader.records().map(|r| r.unwrap()[2].parse::<usize>().unwrap()).sum();
+        assert_eq!(total, 30);
+        std::fs::remove_file(file_name).unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+//! Parquet output for large experiments, behind the `parquet` feature. The files are columnar
+//! and compressed, so thousands of generations over hundreds of configurations stay small and
+//! load quickly into pandas, polars or DuckDB. Stats columns carry the names of the CSV
+//! columns written by `save_stats_to_csv`.
+use crate::error::GaError;
+use crate::stats::GenerationStats;
+use crate::Chromosome;
+use parquet::basic::Compression;
+use parquet::data_type::{BoolType, DoubleType, Int32Type, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{SerializedColumnWriter, SerializedFileWriter};
+use parquet::schema::parser::parse_message_type;
+use std::fs::File;
+use std::sync::Arc;
+
+const STATS_SCHEMA:&str = "message generation_stats {
+    REQUIRED INT32 generation (INTEGER(32,false));
+    REQUIRED INT64 ind_out (INTEGER(64,false));
+    REQUIRED DOUBLE price;
+    REQUIRED DOUBLE consumer_surplus;
+    REQUIRED DOUBLE producer_surplus;
+    REQUIRED DOUBLE welfare;
+    REQUIRED DOUBLE var;
+    REQUIRED DOUBLE mean_phenotype;
+    REQUIRED DOUBLE best_fitness;
+    REQUIRED DOUBLE worst_fitness;
+    REQUIRED DOUBLE mean_fitness;
+    REQUIRED DOUBLE median_fitness;
+    REQUIRED DOUBLE std_fitness;
+    OPTIONAL INT64 best_data (INTEGER(64,false));
+    OPTIONAL INT64 best_phenotype (INTEGER(64,false));
+    REQUIRED DOUBLE diversity;
+    REQUIRED DOUBLE feasible_fraction;
+    REQUIRED DOUBLE mean_violation;
+    OPTIONAL DOUBLE best_feasible_fitness;
+    REQUIRED DOUBLE penalty;
+    REQUIRED BOOLEAN degenerate;
+    REQUIRED BOOLEAN shock;
+    REQUIRED INT64 firms (INTEGER(64,false));
+    REQUIRED INT64 entrants (INTEGER(64,false));
+    REQUIRED INT64 exits (INTEGER(64,false));
+    REQUIRED INT64 non_finite (INTEGER(64,false));
+    REQUIRED INT64 panics (INTEGER(64,false));
+    REQUIRED INT64 timeouts (INTEGER(64,false));
+    OPTIONAL DOUBLE effective_size;
+    REQUIRED INT32 stagnant_generations (INTEGER(32,false));
+    REQUIRED DOUBLE evaluation_s;
+    REQUIRED DOUBLE selection_s;
+    REQUIRED DOUBLE crossover_s;
+    REQUIRED DOUBLE mutation_s;
+}";
+
+const POPULATION_SCHEMA:&str = "message population {
+    REQUIRED INT64 data (INTEGER(64,false));
+    REQUIRED DOUBLE fitness;
+    REQUIRED INT64 N (INTEGER(64,false));
+    REQUIRED DOUBLE violation;
+}";
+
+// rows per row group, bounding the memory held while writing
+const ROW_GROUP_SIZE:usize = 1 << 16;
+
+// the values of one column of a row group, unsigned ones stored as their bit pattern
+enum Column
+{
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    Double(Vec<f64>),
+    Boolean(Vec<bool>),
+    OptionalInt64(Vec<Option<i64>>),
+    OptionalDouble(Vec<Option<f64>>),
+}
+
+fn levels<T>(values:&[Option<T>]) -> Vec<i16>
+{
+    values.iter().map(|value| value.is_some() as i16).collect()
+}
+
+fn write_column(writer:&mut SerializedColumnWriter, column:&Column) -> Result<(), GaError>
+{
+    match column {
+        Column::Int32(values) => writer.typed::<Int32Type>().write_batch(values, None, None)?,
+        Column::Int64(values) => writer.typed::<Int64Type>().write_batch(values, None, None)?,
+        Column::Double(values) => writer.typed::<DoubleType>().write_batch(values, None, None)?,
+        Column::Boolean(values) => writer.typed::<BoolType>().write_batch(values, None, None)?,
+        Column::OptionalInt64(values) => {
+            let present:Vec<i64> = values.iter().flatten().copied().collect();
+            writer.typed::<Int64Type>().write_batch(&present, Some(&levels(values)), None)?
+        }
+        Column::OptionalDouble(values) => {
+            let present:Vec<f64> = values.iter().flatten().copied().collect();
+            writer.typed::<DoubleType>().write_batch(&present, Some(&levels(values)), None)?
+        }
+    };
+    Ok(())
+}
+
+// writes `rows` in row groups, `columns` turning each group into columns in schema order
+fn write_parquet<T>(file_name:&str, schema:&str, rows:&[T], columns:impl Fn(&[T]) -> Vec<Column>) -> Result<(), GaError>
+{
+    let schema = Arc::new(parse_message_type(schema)?);
+    let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+    let mut writer = SerializedFileWriter::new(File::create(file_name)?, schema, properties)?;
+    for group in rows.chunks(ROW_GROUP_SIZE) {
+        let mut row_group = writer.next_row_group()?;
+        for column in columns(group) {
+            let mut column_writer = row_group.next_column()?.expect("schema and columns out of step");
+            write_column(&mut column_writer, &column)?;
+            column_writer.close()?;
+        }
+        row_group.close()?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+fn stats_columns(stats:&[GenerationStats]) -> Vec<Column>
+{
+    let double = |field:fn(&GenerationStats) -> f64| Column::Double(stats.iter().map(field).collect());
+    let count = |field:fn(&GenerationStats) -> usize| Column::Int64(stats.iter().map(|s| field(s) as i64).collect());
+    vec![
+        Column::Int32(stats.iter().map(|s| s.generation as i32).collect()),
+        Column::Int64(stats.iter().map(|s| s.ind_out as i64).collect()),
+        double(|s| s.price),
+        double(|s| s.welfare.consumer_surplus),
+        double(|s| s.welfare.producer_surplus),
+        double(|s| s.welfare.total),
+        double(|s| s.variance),
+        double(|s| s.mean_phenotype),
+        double(|s| s.best_fitness),
+        double(|s| s.worst_fitness),
+        double(|s| s.mean_fitness),
+        double(|s| s.median_fitness),
+        double(|s| s.std_fitness),
+        Column::OptionalInt64(stats.iter().map(|s| s.best.as_ref().map(|best| best.data as i64)).collect()),
+        Column::OptionalInt64(stats.iter().map(|s| s.best_phenotype.map(|phenotype| phenotype as i64)).collect()),
+        double(|s| s.diversity),
+        double(|s| s.feasible_fraction),
+        double(|s| s.mean_violation),
+        Column::OptionalDouble(stats.iter().map(|s| s.best_feasible_fitness).collect()),
+        double(|s| s.penalty),
+        Column::Boolean(stats.iter().map(|s| s.degenerate).collect()),
+        Column::Boolean(stats.iter().map(|s| s.shock).collect()),
+        count(|s| s.firms),
+        count(|s| s.entrants),
+        count(|s| s.exits),
+        count(|s| s.non_finite),
+        count(|s| s.panics),
+        count(|s| s.timeouts),
+        Column::OptionalDouble(stats.iter().map(|s| s.effective_size).collect()),
+        Column::Int32(stats.iter().map(|s| s.stagnant_generations as i32).collect()),
+        double(|s| s.timings.evaluation.as_secs_f64()),
+        double(|s| s.timings.selection.as_secs_f64()),
+        double(|s| s.timings.crossover.as_secs_f64()),
+        double(|s| s.timings.mutation.as_secs_f64()),
+    ]
+}
+
+fn population_columns(population:&[Chromosome]) -> Vec<Column>
+{
+    vec![
+        Column::Int64(population.iter().map(|ind| ind.data as i64).collect()),
+        Column::Double(population.iter().map(|ind| ind.fitness).collect()),
+        Column::Int64(population.iter().map(|ind| ind.N as i64).collect()),
+        Column::Double(population.iter().map(|ind| ind.violation).collect()),
+    ]
+}
+
+/// Writes one row per generation, Snappy-compressed.
+pub fn save_stats_to_parquet(stats:&[GenerationStats], file_name:&str) -> Result<(), GaError>
+{
+    write_parquet(file_name, STATS_SCHEMA, stats, stats_columns)
+}
+
+/// Writes one row per individual, for instance the `population` of a `RunResult`.
+pub fn save_population_to_parquet(population:&[Chromosome], file_name:&str) -> Result<(), GaError>
+{
+    write_parquet(file_name, POPULATION_SCHEMA, population, population_columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{problems, Run};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    #[test]
+    fn stats_and_population_round_trip()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 10, 20, 4, 2).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        let result = test_run.run(8).unwrap();
+
+        let path = std::env::temp_dir().join(format!("stats_{}.parquet", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_stats_to_parquet(&result.stats, path).unwrap();
+        let reader = SerializedFileReader::try_from(path).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 8);
+        let rows:Vec<_> = reader.get_row_iter(None).unwrap().map(|row| row.unwrap()).collect();
+        assert_eq!(rows[7].get_uint(0).unwrap(), 8);
+        assert_eq!(rows[7].get_ulong(1).unwrap(), result.stats[7].ind_out);
+        assert_eq!(rows[7].get_double(8).unwrap(), result.stats[7].best_fitness);
+        assert_eq!(rows[7].get_ulong(13).unwrap(), result.stats[7].best.as_ref().unwrap().data);
+
+        save_population_to_parquet(&result.population, path).unwrap();
+        let reader = SerializedFileReader::try_from(path).unwrap();
+        let data:Vec<u64> = reader.get_row_iter(None).unwrap().map(|row| row.unwrap().get_ulong(0).unwrap()).collect();
+        assert_eq!(data, result.population.iter().map(|ind| ind.data).collect::<Vec<u64>>());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+//! Population-Based Incremental Learning (Baluja 1994): instead of a population, a vector of
+//! bit probabilities is sampled every generation and moved towards the best sample. Genomes are
+//! `BitGenome`s and the history is kept as `GenomeStats`, so traces and exports work as for a
+//! `GenomeRun`.
+use crate::bits::BitGenome;
+use crate::evolution::GenomeStats;
+use crate::objective::Objective;
+use rand::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct Pbil
+{
+    /// Probability of a one at each locus.
+    pub probabilities:Vec<f64>,
+    /// Samples drawn per generation.
+    pub samples:usize,
+    pub learning_rate:f64,
+    /// Chance of each probability being mutated per generation.
+    pub mutation_probability:f64,
+    /// How far a mutated probability moves towards a random 0 or 1.
+    pub mutation_shift:f64,
+    fitness:Arc<dyn Fn(&BitGenome) -> f64 + Send + Sync>,
+    objective:Objective,
+    period:u32,
+    evaluations:usize,
+    history:Vec<GenomeStats>,
+    best:Option<BitGenome>,
+    rng:StdRng,
+}
+
+impl fmt::Debug for Pbil
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("Pbil").field("probabilities", &self.probabilities).field("samples", &self.samples)
+            .field("learning_rate", &self.learning_rate).field("mutation_probability", &self.mutation_probability)
+            .field("mutation_shift", &self.mutation_shift).field("objective", &self.objective)
+            .field("period", &self.period).field("best", &self.best).finish()
+    }
+}
+
+impl Pbil
+{
+    /// Uniform probabilities over `len` bits and `samples` samples per generation, maximizing
+    /// `fitness`. Learning rate 0.1, mutation probability 0.02 and shift 0.05 as in Baluja's paper.
+    pub fn new<F>(len:usize, samples:usize, fitness:F) -> Self
+        where F:Fn(&BitGenome) -> f64 + Send + Sync + 'static
+    {
+        assert!(samples >= 1, "PBIL needs at least one sample per generation");
+        Pbil {probabilities:vec![0.5; len], samples, learning_rate:0.1, mutation_probability:0.02, mutation_shift:0.05,
+            fitness:Arc::new(fitness), objective:Objective::Maximize, period:0, evaluations:0, history:Vec::new(), best:None,
+            rng:StdRng::from_entropy()}
+    }
+
+    /// Like `new` with every sample and probability mutation drawn from an RNG seeded with
+    /// `seed`.
+    pub fn with_seed<F>(len:usize, samples:usize, seed:u64, fitness:F) -> Self
+        where F:Fn(&BitGenome) -> f64 + Send + Sync + 'static
+    {
+        let mut pbil = Self::new(len, samples, fitness);
+        pbil.set_seed(seed);
+        pbil
+    }
+
+    pub fn set_seed(&mut self, seed:u64)
+    {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn set_objective(&mut self, objective:Objective)
+    {
+        self.objective = objective;
+    }
+
+    pub fn objective(&self) -> Objective
+    {
+        self.objective
+    }
+
+    pub fn best(&self) -> Option<&BitGenome>
+    {
+        self.best.as_ref()
+    }
+
+    pub fn history(&self) -> &[GenomeStats]
+    {
+        &self.history
+    }
+
+    pub fn evaluations(&self) -> usize
+    {
+        self.evaluations
+    }
+
+    pub fn sample<R:Rng + ?Sized>(&self, rng:&mut R) -> BitGenome
+    {
+        let mut genome = BitGenome::new(self.probabilities.len());
+        for (bit, &p) in self.probabilities.iter().enumerate() {
+            genome.set(bit, rng.gen::<f64>() < p);
+        }
+        genome
+    }
+
+    pub fn run(&mut self, iterations:u32)
+    {
+        let mut rng = self.rng.clone();
+        for _ in 0..iterations {
+            self.period += 1;
+            let samples:Vec<BitGenome> = (0..self.samples).map(|_| {
+                let mut genome = self.sample(&mut rng);
+                genome.fitness = (self.fitness)(&genome);
+                genome
+            }).collect();
+            self.evaluations += samples.len();
+
+            let objective = self.objective;
+            let elite = samples.iter().min_by(|a, b| objective.best_first(a.fitness, b.fitness)).unwrap();
+            if self.best.as_ref().is_none_or(|best| objective.is_better(elite.fitness, best.fitness)) {
+                self.best = Some(elite.clone());
+            }
+            let mean_fitness = samples.iter().map(|s| s.fitness).sum::<f64>() / samples.len() as f64;
+            self.history.push(GenomeStats {generation:self.period, best_fitness:elite.fitness, mean_fitness, evaluations:self.evaluations,
+                feasible_fraction:1.0});
+
+            for (bit, p) in self.probabilities.iter_mut().enumerate() {
+                let target = if elite.get(bit) { 1.0 } else { 0.0 };
+                *p = *p * (1.0 - self.learning_rate) + target * self.learning_rate;
+                if rng.gen::<f64>() < self.mutation_probability {
+                    let direction = if rng.gen() { 1.0 } else { 0.0 };
+                    *p = *p * (1.0 - self.mutation_shift) + direction * self.mutation_shift;
+                }
+            }
+        }
+        self.rng = rng;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn learns_onemax()
+    {
+        let mut pbil = Pbil::new(80, 30, |genome:&BitGenome| genome.count_ones() as f64);
+        pbil.run(200);
+        assert!(pbil.best().unwrap().fitness >= 76.0, "{:?}", pbil.best().map(|b| b.fitness));
+        assert!(pbil.probabilities.iter().filter(|&&p| p > 0.8).count() >= 70);
+        assert_eq!(pbil.evaluations(), 6000);
+        assert_eq!(pbil.trace().len(), 200);
+
+        let file_name = std::env::temp_dir().join(format!("ga_pbil_{}.csv", std::process::id()));
+        let file_name = file_name.to_str().unwrap();
+        crate::evolution::save_genome_stats_to_csv(pbil.history(), file_name).unwrap();
+        assert_eq!(std::fs::read_to_string(file_name).unwrap().lines().count(), 201);
+        std::fs::remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn seeded_runs_repeat()
+    {
+        let run = |seed| {
+            let mut pbil = Pbil::with_seed(20, 10, seed, |genome:&BitGenome| genome.count_ones() as f64);
+            pbil.run(15);
+            (pbil.history().to_vec(), pbil.probabilities)
+        };
+        assert_eq!(run(3), run(3));
+        assert_ne!(run(3).1, run(4).1);
+    }
+}
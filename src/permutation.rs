@@ -0,0 +1,493 @@
+//! Permutation genomes for ordering problems (routing, scheduling). A genome is an ordering of
+//! `0..len`, so operators must keep every element exactly once. `PermutationRun` evolves them
+//! with the generic `GenomeRun` loop, not with `Run`, so the bit-string engine's features listed
+//! in the `evolution` module docs (steady state, islands, stats exports and so on) are not
+//! available to them.
+use crate::evolution::{Genome, GenomeRun};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+/// `GenomeRun` over orderings, the operators need no context.
+pub type PermutationRun = GenomeRun<PermutationChromosome>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermutationChromosome
+{
+    pub order:Vec<usize>,
+    pub fitness:f64,
+    pub violation:f64,
+}
+
+impl PermutationChromosome
+{
+    /// Panics if `order` is not a permutation of `0..order.len()`.
+    pub fn new(order:Vec<usize>) -> Self
+    {
+        assert!(is_permutation(&order), "not a permutation: {:?}", order);
+        PermutationChromosome {order, fitness:0.0, violation:0.0}
+    }
+
+    pub fn identity(len:usize) -> Self
+    {
+        PermutationChromosome {order:(0..len).collect(), fitness:0.0, violation:0.0}
+    }
+
+    pub fn random<R:Rng + ?Sized>(len:usize, rng:&mut R) -> Self
+    {
+        let mut ind = Self::identity(len);
+        ind.order.shuffle(rng);
+        ind
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.order.is_empty()
+    }
+}
+
+pub fn is_permutation(order:&[usize]) -> bool
+{
+    let mut seen = vec![false; order.len()];
+    order.iter().all(|&x| x < seen.len() && !std::mem::replace(&mut seen[x], true))
+}
+
+// two distinct positions in 0..len, ordered
+fn two_points<R:Rng + ?Sized>(len:usize, rng:&mut R) -> (usize, usize)
+{
+    let a = rng.gen_range(0..len);
+    let mut b = rng.gen_range(0..len - 1);
+    if b >= a {
+        b += 1;
+    }
+    (a.min(b), a.max(b))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PermutationMutation
+{
+    /// Exchanges two elements.
+    #[default]
+    Swap,
+    /// Reverses a segment.
+    Inversion,
+    /// Moves one element to another position.
+    Insertion,
+    /// Moves a whole segment to another position, keeping its order.
+    Displacement,
+    /// Shuffles a segment.
+    Scramble,
+}
+
+impl PermutationMutation
+{
+    pub const ALL:[PermutationMutation; 5] = [PermutationMutation::Swap, PermutationMutation::Inversion,
+        PermutationMutation::Insertion, PermutationMutation::Displacement, PermutationMutation::Scramble];
+
+    /// Mutates `order` in place, orderings shorter than two elements are left alone.
+    pub fn apply<R:Rng + ?Sized>(self, order:&mut [usize], rng:&mut R)
+    {
+        if order.len() < 2 {
+            return;
+        }
+        let (i, j) = two_points(order.len(), rng);
+        match self {
+            PermutationMutation::Swap => order.swap(i, j),
+            PermutationMutation::Inversion => order[i..=j].reverse(),
+            PermutationMutation::Insertion => {
+                if rng.gen() { order[i..=j].rotate_left(1) } else { order[i..=j].rotate_right(1) }
+            }
+            PermutationMutation::Displacement => {
+                let segment:Vec<usize> = order[i..=j].to_vec();
+                let mut rest:Vec<usize> = order[..i].iter().chain(&order[j + 1..]).copied().collect();
+                let at = rng.gen_range(0..=rest.len());
+                rest.splice(at..at, segment);
+                order.copy_from_slice(&rest);
+            }
+            PermutationMutation::Scramble => order[i..=j].shuffle(rng),
+        }
+    }
+}
+
+/// Weighted mix of permutation mutations, one of them is drawn per application. A single
+/// mutation converts into a mix of itself, the default being swap alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationMix
+{
+    weights:Vec<(PermutationMutation, f64)>,
+}
+
+impl Default for MutationMix
+{
+    fn default() -> Self
+    {
+        PermutationMutation::default().into()
+    }
+}
+
+impl From<PermutationMutation> for MutationMix
+{
+    fn from(mutation:PermutationMutation) -> Self
+    {
+        MutationMix {weights:vec![(mutation, 1.0)]}
+    }
+}
+
+impl MutationMix
+{
+    /// Panics unless the weights are non-negative with a positive sum.
+    pub fn new(weights:Vec<(PermutationMutation, f64)>) -> Self
+    {
+        assert!(weights.iter().all(|(_, w)| *w >= 0.0) && weights.iter().map(|(_, w)| w).sum::<f64>() > 0.0,
+            "mutation weights must be non-negative with a positive sum");
+        MutationMix {weights}
+    }
+
+    /// Every mutation with the same weight.
+    pub fn uniform() -> Self
+    {
+        Self::new(PermutationMutation::ALL.iter().map(|m| (*m, 1.0)).collect())
+    }
+
+    pub fn choose<R:Rng + ?Sized>(&self, rng:&mut R) -> PermutationMutation
+    {
+        let total:f64 = self.weights.iter().map(|(_, w)| w).sum();
+        let mut target = rng.gen::<f64>() * total;
+        for (mutation, weight) in &self.weights {
+            if target < *weight {
+                return *mutation;
+            }
+            target -= weight;
+        }
+        self.weights.iter().rev().find(|(_, w)| *w > 0.0).map(|(m, _)| *m).unwrap_or(PermutationMutation::Swap)
+    }
+
+    pub fn apply<R:Rng + ?Sized>(&self, order:&mut [usize], rng:&mut R)
+    {
+        self.choose(rng).apply(order, rng);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PermutationCrossover
+{
+    /// Partially mapped crossover (PMX): a segment of `a` is kept in place and the rest comes
+    /// from `b`, with clashes resolved through the mapping between the two segments.
+    PartiallyMapped,
+    /// Order crossover (OX): a segment of `a` is kept in place and the remaining positions are
+    /// filled in `b`'s order, starting after the segment.
+    #[default]
+    Order,
+    /// Cycle crossover (CX): every element keeps the position it has in one of the parents,
+    /// taking alternate position cycles from `a` and `b`.
+    Cycle,
+    /// Edge recombination (ERX): builds the child from the union of both parents' tour edges,
+    /// always moving to the neighbour with the fewest remaining edges. Orderings are read as
+    /// closed tours, so the last and first elements are adjacent.
+    EdgeRecombination,
+}
+
+impl PermutationCrossover
+{
+    /// One child from parents `a` and `b`, which must be permutations of the same length.
+    pub fn apply<R:Rng + ?Sized>(self, a:&[usize], b:&[usize], rng:&mut R) -> Vec<usize>
+    {
+        assert_eq!(a.len(), b.len(), "parents must have the same length");
+        if a.len() < 2 {
+            return a.to_vec();
+        }
+        match self {
+            PermutationCrossover::PartiallyMapped => {
+                let (i, j) = two_points(a.len(), rng);
+                partially_mapped(a, b, i, j)
+            }
+            PermutationCrossover::Order => {
+                let (i, j) = two_points(a.len(), rng);
+                order_crossover(a, b, i, j)
+            }
+            PermutationCrossover::Cycle => cycle_crossover(a, b),
+            PermutationCrossover::EdgeRecombination => edge_recombination(a, b, rng),
+        }
+    }
+}
+
+// position of every element in `order`
+fn positions(order:&[usize]) -> Vec<usize>
+{
+    let mut position = vec![0; order.len()];
+    for (i, &x) in order.iter().enumerate() {
+        position[x] = i;
+    }
+    position
+}
+
+fn partially_mapped(a:&[usize], b:&[usize], i:usize, j:usize) -> Vec<usize>
+{
+    let position_in_a = positions(a);
+    let mut child = b.to_vec();
+    child[i..=j].copy_from_slice(&a[i..=j]);
+    for k in (0..i).chain(j + 1..a.len()) {
+        let mut x = b[k];
+        // x is taken by the segment, follow the mapping until an element outside of it
+        while (i..=j).contains(&position_in_a[x]) {
+            x = b[position_in_a[x]];
+        }
+        child[k] = x;
+    }
+    child
+}
+
+fn order_crossover(a:&[usize], b:&[usize], i:usize, j:usize) -> Vec<usize>
+{
+    let len = a.len();
+    let mut taken = vec![false; len];
+    a[i..=j].iter().for_each(|&x| taken[x] = true);
+    let mut child = a.to_vec();
+    let mut fill = (j + 1..len).chain(0..i);
+    for k in (j + 1..len).chain(0..=j) {
+        if !taken[b[k]] {
+            child[fill.next().unwrap()] = b[k];
+        }
+    }
+    child
+}
+
+fn cycle_crossover(a:&[usize], b:&[usize]) -> Vec<usize>
+{
+    let position_in_a = positions(a);
+    let mut child = vec![usize::MAX; a.len()];
+    let mut from_a = true;
+    for start in 0..a.len() {
+        if child[start] != usize::MAX {
+            continue;
+        }
+        let mut k = start;
+        loop {
+            child[k] = if from_a { a[k] } else { b[k] };
+            k = position_in_a[b[k]];
+            if k == start {
+                break;
+            }
+        }
+        from_a = !from_a;
+    }
+    child
+}
+
+impl Genome for PermutationChromosome
+{
+    type Context = ();
+    type Crossover = PermutationCrossover;
+    type Mutation = MutationMix;
+
+    fn fitness(&self) -> f64
+    {
+        self.fitness
+    }
+
+    fn set_fitness(&mut self, fitness:f64)
+    {
+        self.fitness = fitness;
+    }
+
+    fn crossover(a:&Self, b:&Self, operator:PermutationCrossover, _context:&(), rng:&mut dyn RngCore) -> Self
+    {
+        PermutationChromosome {order:operator.apply(&a.order, &b.order, rng), fitness:0.0, violation:0.0}
+    }
+
+    /// `rate` is the probability of one mutation per genome, drawn from the mix.
+    fn mutate(&mut self, operator:&MutationMix, rate:f64, _context:&(), rng:&mut dyn RngCore)
+    {
+        if rng.gen::<f64>() < rate {
+            operator.apply(&mut self.order, rng);
+        }
+    }
+}
+
+impl PermutationRun
+{
+    /// `n` random orderings of `0..len`, maximizing `fitness` by default, with order
+    /// crossover and swap mutation.
+    pub fn new<F>(len:usize, n:usize, fitness:F) -> Self
+        where F:Fn(&[usize]) -> f64 + Send + Sync + 'static
+    {
+        Self::from_rng(len, n, fitness, ChaCha12Rng::from_entropy())
+    }
+
+    /// Like `new` but reproducible: the initial orderings and every later draw come from an
+    /// RNG seeded with `seed`.
+    pub fn with_seed<F>(len:usize, n:usize, seed:u64, fitness:F) -> Self
+        where F:Fn(&[usize]) -> f64 + Send + Sync + 'static
+    {
+        Self::from_rng(len, n, fitness, ChaCha12Rng::seed_from_u64(seed))
+    }
+
+    fn from_rng<F>(len:usize, n:usize, fitness:F, mut rng:ChaCha12Rng) -> Self
+        where F:Fn(&[usize]) -> f64 + Send + Sync + 'static
+    {
+        let population = (0..n).map(|_| PermutationChromosome::random(len, &mut rng)).collect();
+        let mut run = GenomeRun::from_population(population, (), move |ind:&PermutationChromosome| fitness(&ind.order));
+        run.rng = rng;
+        run
+    }
+}
+
+// union of the cyclic neighbours of every element in both parents
+fn edge_map(a:&[usize], b:&[usize]) -> Vec<Vec<usize>>
+{
+    let len = a.len();
+    let mut edges = vec![Vec::with_capacity(4); len];
+    for parent in [a, b] {
+        for i in 0..len {
+            let (x, next) = (parent[i], parent[(i + 1) % len]);
+            if x == next {
+                continue;
+            }
+            if !edges[x].contains(&next) {
+                edges[x].push(next);
+            }
+            if !edges[next].contains(&x) {
+                edges[next].push(x);
+            }
+        }
+    }
+    edges
+}
+
+fn edge_recombination<R:Rng + ?Sized>(a:&[usize], b:&[usize], rng:&mut R) -> Vec<usize>
+{
+    let len = a.len();
+    if len == 0 {
+        return Vec::new();
+    }
+    let mut edges = edge_map(a, b);
+    let mut visited = vec![false; len];
+    let mut child = Vec::with_capacity(len);
+    let mut current = if rng.gen() { a[0] } else { b[0] };
+    loop {
+        child.push(current);
+        visited[current] = true;
+        if child.len() == len {
+            return child;
+        }
+        // visited elements are dropped from every edge list, so the remaining neighbours are unvisited
+        let candidates = std::mem::take(&mut edges[current]);
+        for &neighbour in &candidates {
+            edges[neighbour].retain(|&x| x != current);
+        }
+        current = if candidates.is_empty() {
+            let unvisited:Vec<usize> = (0..len).filter(|&x| !visited[x]).collect();
+            *unvisited.choose(rng).unwrap()
+        }else{
+            let fewest = candidates.iter().map(|&x| edges[x].len()).min().unwrap();
+            let ties:Vec<usize> = candidates.into_iter().filter(|&x| edges[x].len() == fewest).collect();
+            *ties.choose(rng).unwrap()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutations_keep_permutations()
+    {
+        let mut rng = rand::thread_rng();
+        for mutation in PermutationMutation::ALL {
+            for len in [2, 3, 10] {
+                let mut ind = PermutationChromosome::random(len, &mut rng);
+                for _ in 0..50 {
+                    mutation.apply(&mut ind.order, &mut rng);
+                    assert!(is_permutation(&ind.order), "{:?} broke {:?}", mutation, ind.order);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn displacement_keeps_segment_order()
+    {
+        let mut rng = rand::thread_rng();
+        let mut order:Vec<usize> = (0..8).collect();
+        PermutationMutation::Displacement.apply(&mut order, &mut rng);
+        // every element except the block boundaries still follows its predecessor
+        let adjacent = order.windows(2).filter(|w| w[1] == w[0] + 1).count();
+        assert!(adjacent >= 4);
+    }
+
+    #[test]
+    fn mix_respects_weights()
+    {
+        let mut rng = rand::thread_rng();
+        let mix = MutationMix::new(vec![(PermutationMutation::Swap, 0.0), (PermutationMutation::Scramble, 1.0)]);
+        assert!((0..100).all(|_| mix.choose(&mut rng) == PermutationMutation::Scramble));
+        let mut order:Vec<usize> = (0..6).collect();
+        MutationMix::uniform().apply(&mut order, &mut rng);
+        assert!(is_permutation(&order));
+        assert_eq!(MutationMix::default(), MutationMix::new(vec![(PermutationMutation::Swap, 1.0)]));
+
+        // a run mutates through its mix
+        let mut test_run = PermutationRun::with_seed(10, 20, 4, |order:&[usize]| order[0] as f64);
+        test_run.mutation = MutationMix::new(vec![(PermutationMutation::Swap, 0.0), (PermutationMutation::Inversion, 2.0)]);
+        test_run.Pmut = 1.0;
+        test_run.run(5);
+        assert!(test_run.population.iter().all(|ind| is_permutation(&ind.order)));
+    }
+
+    #[test]
+    fn textbook_crossovers()
+    {
+        let a = [0, 1, 2, 3, 4, 5, 6, 7];
+        let b = [2, 4, 6, 0, 7, 5, 3, 1];
+        assert_eq!(partially_mapped(&a, &b, 3, 5), vec![2, 7, 6, 3, 4, 5, 0, 1]);
+        assert_eq!(order_crossover(&a, &b, 3, 5), vec![6, 0, 7, 3, 4, 5, 1, 2]);
+        let child = cycle_crossover(&a, &b);
+        assert!(child.iter().enumerate().all(|(k, &x)| x == a[k] || x == b[k]));
+        assert_eq!(child[0], 0);
+
+        let mut rng = rand::thread_rng();
+        for crossover in [PermutationCrossover::PartiallyMapped, PermutationCrossover::Order, PermutationCrossover::Cycle] {
+            for _ in 0..50 {
+                let a = PermutationChromosome::random(9, &mut rng);
+                let b = PermutationChromosome::random(9, &mut rng);
+                assert!(is_permutation(&crossover.apply(&a.order, &b.order, &mut rng)), "{:?}", crossover);
+            }
+        }
+    }
+
+    #[test]
+    fn run_sorts_a_permutation()
+    {
+        // number of elements already in their own position
+        let mut test_run = PermutationRun::new(8, 40, |order:&[usize]| order.iter().enumerate().filter(|(i, x)| i == *x).count() as f64);
+        test_run.crossover = PermutationCrossover::Cycle;
+        test_run.mutation = PermutationMutation::Inversion.into();
+        test_run.Pmut = 0.5;
+        test_run.run(100);
+        let best = test_run.best().unwrap();
+        assert!(best.fitness >= 6.0, "{:?}", best);
+        assert!(is_permutation(&best.order));
+    }
+
+    #[test]
+    fn edge_recombination_keeps_parent_edges()
+    {
+        let mut rng = rand::thread_rng();
+        let a = PermutationChromosome::random(12, &mut rng);
+        let b = PermutationChromosome::random(12, &mut rng);
+        let child = PermutationCrossover::EdgeRecombination.apply(&a.order, &b.order, &mut rng);
+        assert!(is_permutation(&child));
+
+        // with identical parents every step can follow a parent edge
+        let child = PermutationCrossover::EdgeRecombination.apply(&a.order, &a.order, &mut rng);
+        let edges = edge_map(&a.order, &a.order);
+        assert!(child.windows(2).all(|w| edges[w[0]].contains(&w[1])), "{:?} from {:?}", child, a.order);
+    }
+}
@@ -0,0 +1,108 @@
+//! Convergence plots straight from the stats, behind the `plotting` feature: best and mean
+//! fitness on top, population variance below, against the generation. The file extension
+//! picks the format, `.svg` for SVG and anything else for a bitmap such as PNG.
+use crate::error::GaError;
+use crate::stats::GenerationStats;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::Path;
+
+const SIZE:(u32, u32) = (1024, 768);
+
+fn plot_error<E:Display>(e:E) -> GaError
+{
+    GaError::Plot(e.to_string())
+}
+
+// the span of the finite `values`, padded so the curves don't touch the frame
+fn value_range(values:impl Iterator<Item = f64>) -> Range<f64>
+{
+    let (low, high) = values.filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), v| (low.min(v), high.max(v)));
+    if low > high {
+        return 0.0..1.0;
+    }
+    let pad = if high > low { (high - low) * 0.05 } else { low.abs().max(1.0) * 0.05 };
+    low - pad..high + pad
+}
+
+fn draw<DB:DrawingBackend>(root:DrawingArea<DB, Shift>, stats:&[GenerationStats]) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>>
+{
+    root.fill(&WHITE)?;
+    let (upper, lower) = root.split_vertically(SIZE.1 * 3 / 5);
+    let first = stats[0].generation;
+    let generations = first..stats[stats.len() - 1].generation.max(first + 1);
+
+    let fitness = value_range(stats.iter().flat_map(|s| [s.best_fitness, s.mean_fitness]));
+    let mut chart = ChartBuilder::on(&upper)
+        .caption("Fitness", ("sans-serif", 22))
+        .margin(12)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(generations.clone(), fitness)?;
+    chart.configure_mesh().x_desc("generation").y_desc("fitness").draw()?;
+    chart.draw_series(LineSeries::new(stats.iter().map(|s| (s.generation, s.best_fitness)), RED.stroke_width(2)))?
+        .label("best")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED.stroke_width(2)));
+    chart.draw_series(LineSeries::new(stats.iter().map(|s| (s.generation, s.mean_fitness)), BLUE.stroke_width(2)))?
+        .label("mean")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE.stroke_width(2)));
+    chart.configure_series_labels().background_style(WHITE.mix(0.8)).border_style(BLACK).draw()?;
+
+    let variance = value_range(stats.iter().map(|s| s.variance));
+    let mut chart = ChartBuilder::on(&lower)
+        .caption("Population variance", ("sans-serif", 22))
+        .margin(12)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(generations, variance)?;
+    chart.configure_mesh().x_desc("generation").y_desc("variance").draw()?;
+    chart.draw_series(LineSeries::new(stats.iter().map(|s| (s.generation, s.variance)), BLACK.stroke_width(2)))?;
+
+    root.present()
+}
+
+/// Plots `stats` in generation order to `path`, for instance the `stats` of a `RunResult` or
+/// stats loaded with `load_stats_from_csv`.
+pub fn plot_convergence<P:AsRef<Path>>(stats:&[GenerationStats], path:P) -> Result<(), GaError>
+{
+    if stats.is_empty() {
+        return Err(GaError::Plot("no generations to plot".to_string()));
+    }
+    let path = path.as_ref();
+    if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("svg")) {
+        draw(SVGBackend::new(path, SIZE).into_drawing_area(), stats).map_err(plot_error)
+    }else{
+        draw(BitMapBackend::new(path, SIZE).into_drawing_area(), stats).map_err(plot_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{problems, Run};
+    use std::fs;
+
+    #[test]
+    fn plots_svg_and_png()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 12, 20, 4, 1).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        let result = test_run.run(25).unwrap();
+
+        let dir = std::env::temp_dir();
+        let svg = dir.join(format!("convergence_{}.svg", std::process::id()));
+        plot_convergence(&result.stats, &svg).unwrap();
+        assert!(fs::read_to_string(&svg).unwrap().contains("<svg"));
+        let png = dir.join(format!("convergence_{}.png", std::process::id()));
+        plot_convergence(&result.stats, &png).unwrap();
+        assert_eq!(&fs::read(&png).unwrap()[1..4], b"PNG");
+        fs::remove_file(svg).unwrap();
+        fs::remove_file(png).unwrap();
+
+        assert!(matches!(plot_convergence(&[], "empty.svg"), Err(GaError::Plot(_))));
+        assert_eq!(value_range([2.0, f64::NAN].into_iter()), 1.9..2.1);
+    }
+}
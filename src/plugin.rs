@@ -0,0 +1,211 @@
+//! Fitness functions and operators loaded from a cdylib at runtime (`plugins` feature).
+//!
+//! A plugin exports, with the C ABI:
+//! - `ga_fitness(data:u64, data_sum:u64, N:usize) -> f64`, required;
+//! - `ga_mutate(data:u64, L:u8, random:u64) -> u64`, optional, applied to each genome with
+//!   probability `Pmut`, `random` is a fresh random number for the plugin to use;
+//! - `ga_crossover(a:u64, b:u64, L:u8, random:u64) -> GenomePair`, optional, the two children
+//!   of a pair that crosses;
+//! - `ga_select(weights:*const f64, len:usize, random:u64) -> usize`, optional, the index of
+//!   one parent given the `len` selection weights, called once per parent.
+//!
+//! Genomes a plugin returns are cut to their low `L` bits, and selected indices out of range
+//! wrap around.
+use crate::operators::{Crossover, Fitness, Mutation, Selection};
+use crate::stats::PopulationStats;
+use crate::{sampling, Chromosome};
+use libloading::{Library, Symbol};
+use rand::{Rng, RngCore};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+type FitnessSymbol = unsafe extern "C" fn(u64, u64, usize) -> f64;
+type MutateSymbol = unsafe extern "C" fn(u64, u8, u64) -> u64;
+type CrossoverSymbol = unsafe extern "C" fn(u64, u64, u8, u64) -> GenomePair;
+type SelectSymbol = unsafe extern "C" fn(*const f64, usize, u64) -> usize;
+
+/// The two children `ga_crossover` returns.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenomePair
+{
+    pub a:u64,
+    pub b:u64,
+}
+
+fn mask(L:u8) -> u64
+{
+    if L >= 64 { u64::MAX } else { (1u64 << L) - 1 }
+}
+
+/// A loaded plugin library, cheap to clone.
+#[derive(Clone)]
+pub struct Plugin
+{
+    path:PathBuf,
+    fitness:FitnessSymbol,
+    mutate:Option<MutateSymbol>,
+    crossover:Option<CrossoverSymbol>,
+    select:Option<SelectSymbol>,
+    // keeps the symbols above valid
+    _library:Arc<Library>,
+}
+
+impl fmt::Debug for Plugin
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("Plugin").field("path", &self.path).field("mutate", &self.mutate.is_some())
+            .field("crossover", &self.crossover.is_some()).field("select", &self.select.is_some()).finish()
+    }
+}
+
+impl Plugin
+{
+    /// Loads the library at `path`.
+    ///
+    /// # Safety
+    /// Loading runs the library's initialisers, and the exported symbols must have exactly the
+    /// signatures listed in the module docs.
+    pub unsafe fn load(path:&Path) -> Result<Self, libloading::Error>
+    {
+        let library = Library::new(path)?;
+        let fitness:Symbol<FitnessSymbol> = library.get(b"ga_fitness\0")?;
+        let fitness = *fitness;
+        let mutate = library.get::<MutateSymbol>(b"ga_mutate\0").ok().map(|symbol| *symbol);
+        let crossover = library.get::<CrossoverSymbol>(b"ga_crossover\0").ok().map(|symbol| *symbol);
+        let select = library.get::<SelectSymbol>(b"ga_select\0").ok().map(|symbol| *symbol);
+        Ok(Plugin {path:path.to_path_buf(), fitness, mutate, crossover, select, _library:Arc::new(library)})
+    }
+
+    pub fn path(&self) -> &Path
+    {
+        &self.path
+    }
+
+    /// The plugin's mutation with probability `Pmut` per genome, if it exports one.
+    pub fn mutation(&self, Pmut:f32) -> Option<PluginMutation>
+    {
+        self.mutate.map(|_| PluginMutation {plugin:self.clone(), Pmut})
+    }
+
+    /// The plugin's crossover, if it exports one.
+    pub fn crossover(&self) -> Option<PluginCrossover>
+    {
+        self.crossover.map(|_| PluginCrossover {plugin:self.clone()})
+    }
+
+    /// The plugin's selection, if it exports one.
+    pub fn selection(&self) -> Option<PluginSelection>
+    {
+        self.select.map(|_| PluginSelection {plugin:self.clone()})
+    }
+}
+
+impl Fitness for Plugin
+{
+    fn evaluate(&self, ind:&Chromosome, context:&PopulationStats) -> f64
+    {
+        unsafe { (self.fitness)(ind.data, context.data_sum, ind.N) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginMutation
+{
+    plugin:Plugin,
+    Pmut:f32,
+}
+
+impl Mutation for PluginMutation
+{
+    fn mutate(&self, data:&mut [u64], L:u8, rng:&mut dyn RngCore)
+    {
+        let Some(mutate) = self.plugin.mutate else { return };
+        for i in sampling::bernoulli_indices(rng, self.Pmut as f64, data.len()) {
+            data[i] = unsafe { mutate(data[i], L, rng.gen()) } & mask(L);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginCrossover
+{
+    plugin:Plugin,
+}
+
+impl Crossover for PluginCrossover
+{
+    fn crossover(&self, a:u64, b:u64, L:u8, rng:&mut dyn RngCore) -> (u64, u64)
+    {
+        let Some(crossover) = self.plugin.crossover else { return (a, b) };
+        let children = unsafe { crossover(a, b, L, rng.gen()) };
+        (children.a & mask(L), children.b & mask(L))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginSelection
+{
+    plugin:Plugin,
+}
+
+impl Selection for PluginSelection
+{
+    fn select(&self, weights:&[f64], count:usize, rng:&mut dyn RngCore) -> Vec<usize>
+    {
+        let Some(select) = self.plugin.select else { return Vec::new() };
+        (0..count).map(|_| unsafe { select(weights.as_ptr(), weights.len(), rng.gen()) } % weights.len()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_library_is_an_error()
+    {
+        let result = unsafe { Plugin::load(Path::new("/nonexistent/libga_plugin.so")) };
+        assert!(result.is_err());
+    }
+
+    unsafe extern "C" fn fitness(data:u64, _data_sum:u64, _n:usize) -> f64
+    {
+        data as f64
+    }
+
+    // every operator sets bits far beyond any genome length
+    unsafe extern "C" fn mutate(data:u64, _L:u8, _random:u64) -> u64
+    {
+        !data
+    }
+
+    unsafe extern "C" fn crossover(a:u64, b:u64, _L:u8, _random:u64) -> GenomePair
+    {
+        GenomePair {a:!b, b:!a}
+    }
+
+    unsafe extern "C" fn select(weights:*const f64, len:usize, random:u64) -> usize
+    {
+        let weights = std::slice::from_raw_parts(weights, len);
+        if random.is_multiple_of(2) { len + 1 } else { (0..len).fold(0, |best, i| if weights[i] > weights[best] { i } else { best }) }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plugin_operators_are_masked()
+    {
+        let library = Library::from(libloading::os::unix::Library::this());
+        let plugin = Plugin {path:PathBuf::new(), fitness, mutate:Some(mutate), crossover:Some(crossover), select:Some(select),
+            _library:Arc::new(library)};
+        let mut rng = rand::thread_rng();
+        let mut data = vec![0b1010; 4];
+        plugin.mutation(1.0).unwrap().mutate(&mut data, 4, &mut rng);
+        assert_eq!(data, vec![0b0101; 4]);
+        assert_eq!(plugin.crossover().unwrap().crossover(0b0011, 0b1100, 4, &mut rng), (0b0011, 0b1100));
+        let selected = plugin.selection().unwrap().select(&[1.0, 5.0, 2.0], 50, &mut rng);
+        assert!(selected.iter().all(|&i| i < 3) && selected.contains(&1));
+    }
+}
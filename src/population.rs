@@ -0,0 +1,188 @@
+use crate::Chromosome;
+use std::mem::size_of;
+
+/// Population stored as struct-of-arrays: genomes, fitness and violation live in separate
+/// vectors so the fitness and selection loops only walk the column they need. `Chromosome`
+/// values are assembled on the way in and out of `Run`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Population
+{
+    pub data:Vec<u64>,
+    pub fitness:Vec<f64>,
+    pub violation:Vec<f64>,
+    /// `N` of every chromosome in the population.
+    pub N:usize,
+}
+
+impl Population
+{
+    pub fn with_capacity(capacity:usize, N:usize) -> Self
+    {
+        Population {
+            data:Vec::with_capacity(capacity),
+            fitness:Vec::with_capacity(capacity),
+            violation:Vec::with_capacity(capacity),
+            N,
+        }
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.data.len()
+    }
+
+    pub fn push(&mut self, ind:&Chromosome)
+    {
+        self.data.push(ind.data);
+        self.fitness.push(ind.fitness);
+        self.violation.push(ind.violation);
+    }
+
+    // copies individual `i` of `other` to the end of this population
+    pub fn push_from(&mut self, other:&Population, i:usize)
+    {
+        self.data.push(other.data[i]);
+        self.fitness.push(other.fitness[i]);
+        self.violation.push(other.violation[i]);
+    }
+
+    pub fn get(&self, i:usize) -> Chromosome
+    {
+        Chromosome {data:self.data[i], fitness:self.fitness[i], N:self.N, violation:self.violation[i]}
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Chromosome> + '_
+    {
+        (0..self.len()).map(|i| self.get(i))
+    }
+
+    pub fn to_chromosomes(&self) -> Vec<Chromosome>
+    {
+        self.iter().collect()
+    }
+
+    /// New population made of the individuals at `indices`, repeats allowed.
+    pub fn gather(&self, indices:&[usize]) -> Population
+    {
+        let mut gathered = Population::with_capacity(indices.len(), self.N);
+        for &i in indices {
+            gathered.push_from(self, i);
+        }
+        gathered
+    }
+
+    pub fn data_sum(&self) -> u64
+    {
+        crate::sum_data(&self.data)
+    }
+
+    pub fn capacity_bytes(&self) -> usize
+    {
+        self.data.capacity() * size_of::<u64>()
+            + (self.fitness.capacity() + self.violation.capacity()) * size_of::<f64>()
+    }
+}
+
+/// Read-only view of a run's population that borrows its columns instead of copying them.
+/// Fitness and violation are those of the last evaluation; after a generation's reproduction
+/// the genomes are offspring that have not been evaluated yet.
+#[derive(Debug, Clone, Copy)]
+pub struct PopulationView<'a>
+{
+    population:&'a Population,
+}
+
+impl<'a> PopulationView<'a>
+{
+    pub(crate) fn new(population:&'a Population) -> Self
+    {
+        PopulationView {population}
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.population.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.population.len() == 0
+    }
+
+    /// Genomes, one per slot.
+    pub fn data(&self) -> &'a [u64]
+    {
+        &self.population.data
+    }
+
+    pub fn fitness(&self) -> &'a [f64]
+    {
+        &self.population.fitness
+    }
+
+    pub fn violation(&self) -> &'a [f64]
+    {
+        &self.population.violation
+    }
+
+    /// Individual `i` assembled into a `Chromosome`.
+    pub fn get(&self, i:usize) -> Chromosome
+    {
+        self.population.get(i)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Chromosome> + 'a
+    {
+        self.population.iter()
+    }
+}
+
+impl FromIterator<Chromosome> for Population
+{
+    fn from_iter<I:IntoIterator<Item = Chromosome>>(iter:I) -> Self
+    {
+        let mut population = Population::default();
+        for ind in iter {
+            population.N = ind.N;
+            population.push(&ind);
+        }
+        population
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_chromosomes()
+    {
+        let chromosomes:Vec<Chromosome> = (0..5)
+            .map(|i| Chromosome {data:i, fitness:i as f64 * 2.0, N:10, violation:0.5})
+            .collect();
+        let population:Population = chromosomes.iter().cloned().collect();
+        assert_eq!(population.len(), 5);
+        assert_eq!(population.fitness[3], 6.0);
+        assert_eq!(population.to_chromosomes(), chromosomes);
+    }
+
+    #[test]
+    fn gather_repeats_individuals()
+    {
+        let population:Population = (0..3).map(|i| Chromosome {data:i, fitness:0.0, N:3, violation:0.0}).collect();
+        let gathered = population.gather(&[2, 2, 0]);
+        assert_eq!(gathered.data, vec![2, 2, 0]);
+        assert_eq!(gathered.N, 3);
+    }
+
+    #[test]
+    fn views_borrow_the_columns()
+    {
+        let population:Population = (0..4).map(|i| Chromosome {data:i, fitness:i as f64, N:4, violation:0.0}).collect();
+        let view = PopulationView::new(&population);
+        assert_eq!(view.len(), 4);
+        assert_eq!(view.data().as_ptr(), population.data.as_ptr());
+        assert_eq!(view.get(2), population.get(2));
+        assert_eq!(view.iter().map(|c| c.fitness).sum::<f64>(), 6.0);
+    }
+}
@@ -0,0 +1,134 @@
+//! Mean-variance portfolio selection over a real-valued genome of asset weights. Weights are
+//! kept on the simplex (non-negative, summing to one) by `repair`, which turns the budget and
+//! no-short-selling constraints into a projection instead of a penalty.
+use std::error::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portfolio
+{
+    pub expected_returns:Vec<f64>,
+    pub covariance:Vec<Vec<f64>>,
+    /// Weight of the variance against the expected return, larger is more cautious.
+    pub risk_aversion:f64,
+}
+
+fn read_rows(file_name:&str) -> Result<Vec<Vec<f64>>, Box<dyn Error>>
+{
+    let mut reader = csv::ReaderBuilder::new().has_headers(false).trim(csv::Trim::All).flexible(true).from_path(file_name)?;
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        rows.push(record?.iter().map(|value| value.parse::<f64>()).collect::<Result<Vec<f64>, _>>()?);
+    }
+    Ok(rows)
+}
+
+impl Portfolio
+{
+    /// Panics unless `covariance` is square with one row per asset.
+    pub fn new(expected_returns:Vec<f64>, covariance:Vec<Vec<f64>>, risk_aversion:f64) -> Self
+    {
+        let assets = expected_returns.len();
+        assert!(covariance.len() == assets && covariance.iter().all(|row| row.len() == assets),
+            "covariance must be {} x {}", assets, assets);
+        Portfolio {expected_returns, covariance, risk_aversion}
+    }
+
+    /// Reads headerless CSV files: expected returns one per line (or in one row), and the
+    /// covariance as a square matrix in the same asset order.
+    pub fn from_csv(returns_file:&str, covariance_file:&str, risk_aversion:f64) -> Result<Self, Box<dyn Error>>
+    {
+        let expected_returns:Vec<f64> = read_rows(returns_file)?.into_iter().flatten().collect();
+        let covariance = read_rows(covariance_file)?;
+        let assets = expected_returns.len();
+        if covariance.len() != assets || covariance.iter().any(|row| row.len() != assets) {
+            return Err(format!("{}: covariance must be {} x {}", covariance_file, assets, assets).into());
+        }
+        Ok(Self::new(expected_returns, covariance, risk_aversion))
+    }
+
+    pub fn assets(&self) -> usize
+    {
+        self.expected_returns.len()
+    }
+
+    /// Projects `weights` onto the budget: negative weights become zero and the rest are
+    /// rescaled to sum to one, an all-zero vector becomes the equally weighted portfolio.
+    pub fn repair(weights:&mut [f64])
+    {
+        weights.iter_mut().for_each(|w| *w = if w.is_finite() { w.max(0.0) } else { 0.0 });
+        let total:f64 = weights.iter().sum();
+        if total > 0.0 {
+            weights.iter_mut().for_each(|w| *w /= total);
+        }else{
+            let equal = 1.0 / weights.len() as f64;
+            weights.iter_mut().for_each(|w| *w = equal);
+        }
+    }
+
+    pub fn expected_return(&self, weights:&[f64]) -> f64
+    {
+        weights.iter().zip(&self.expected_returns).map(|(w, r)| w * r).sum()
+    }
+
+    pub fn variance(&self, weights:&[f64]) -> f64
+    {
+        self.covariance.iter().zip(weights)
+            .map(|(row, wi)| wi * row.iter().zip(weights).map(|(c, wj)| c * wj).sum::<f64>())
+            .sum()
+    }
+
+    /// `expected return - risk_aversion * variance` of the repaired weights, to be maximized.
+    pub fn fitness(&self, weights:&[f64]) -> f64
+    {
+        let mut repaired = weights.to_vec();
+        Self::repair(&mut repaired);
+        self.expected_return(&repaired) - self.risk_aversion * self.variance(&repaired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_assets(risk_aversion:f64) -> Portfolio
+    {
+        Portfolio::new(vec![0.1, 0.05], vec![vec![0.04, 0.0], vec![0.0, 0.01]], risk_aversion)
+    }
+
+    #[test]
+    fn repair_projects_onto_budget()
+    {
+        let mut weights = vec![2.0, -1.0, 2.0];
+        Portfolio::repair(&mut weights);
+        assert_eq!(weights, vec![0.5, 0.0, 0.5]);
+        let mut zeros = vec![0.0, f64::NAN];
+        Portfolio::repair(&mut zeros);
+        assert_eq!(zeros, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn risk_aversion_shifts_weights()
+    {
+        let greedy = two_assets(0.0);
+        assert!(greedy.fitness(&[1.0, 0.0]) > greedy.fitness(&[0.5, 0.5]));
+        let cautious = two_assets(10.0);
+        assert!(cautious.fitness(&[0.2, 0.8]) > cautious.fitness(&[1.0, 0.0]));
+        assert!((cautious.variance(&[0.5, 0.5]) - 0.0125).abs() < 1e-12);
+    }
+
+    #[test]
+    fn loads_csv()
+    {
+        let dir = std::env::temp_dir();
+        let returns = dir.join(format!("ga_returns_{}.csv", std::process::id()));
+        let covariance = dir.join(format!("ga_covariance_{}.csv", std::process::id()));
+        std::fs::write(&returns, "0.1\n0.05\n").unwrap();
+        std::fs::write(&covariance, "0.04, 0.0\n0.0, 0.01\n").unwrap();
+        let portfolio = Portfolio::from_csv(returns.to_str().unwrap(), covariance.to_str().unwrap(), 1.0).unwrap();
+        assert_eq!(portfolio, two_assets(1.0));
+        std::fs::write(&covariance, "0.04\n").unwrap();
+        assert!(Portfolio::from_csv(returns.to_str().unwrap(), covariance.to_str().unwrap(), 1.0).is_err());
+        std::fs::remove_file(&returns).unwrap();
+        std::fs::remove_file(&covariance).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+use crate::operators::Fitness;
+use crate::stats::PopulationStats;
+use crate::error::GaError;
+use crate::{Chromosome, FitnessFn, Objective, Run};
+use std::collections::BTreeMap;
+
+/// The Cournot profit the crate was written for, `data` is the firm's output.
+pub fn cournot_fitness(ind:&Chromosome, data_sum:u64) -> f64
+{
+    ind.calculate_fitness(data_sum)
+}
+
+pub fn onemax_fitness(ind:&Chromosome, _data_sum:u64) -> f64
+{
+    ind.data.count_ones() as f64
+}
+
+/// Leading ones counted from the most significant bit of an `L`-bit genome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeadingOnes
+{
+    pub L:u8,
+}
+
+impl Fitness for LeadingOnes
+{
+    fn evaluate(&self, ind:&Chromosome, _context:&PopulationStats) -> f64
+    {
+        let L = u32::from(self.L.min(64));
+        if L == 0 {
+            return 0.0;
+        }
+        (ind.data << (64 - L)).leading_ones().min(L) as f64
+    }
+}
+
+/// How a problem scores genomes: the same function whatever the genome length, or one built
+/// for the genome length of the run.
+#[derive(Debug, Clone, Copy)]
+pub enum ProblemFitness
+{
+    Fixed(FitnessFn),
+    PerLength(fn(u8) -> Box<dyn Fitness>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem
+{
+    pub name:String,
+    pub description:String,
+    pub fitness:ProblemFitness,
+    pub objective:Objective,
+    pub default_bits:u8,
+}
+
+impl Problem
+{
+    pub fn new(name:&str, description:&str, fitness:FitnessFn, default_bits:u8) -> Self
+    {
+        Problem {name:name.to_string(), description:description.to_string(), fitness:ProblemFitness::Fixed(fitness),
+            objective:Objective::Maximize, default_bits}
+    }
+
+    /// A problem whose fitness needs the genome length `L`, built for each run.
+    pub fn per_length(name:&str, description:&str, fitness:fn(u8) -> Box<dyn Fitness>, default_bits:u8) -> Self
+    {
+        Problem {fitness:ProblemFitness::PerLength(fitness), ..Problem::new(name, description, onemax_fitness, default_bits)}
+    }
+
+    pub fn with_objective(mut self, objective:Objective) -> Self
+    {
+        self.objective = objective;
+        self
+    }
+
+    /// A run on this problem with the population drawn uniformly from all `L`-bit genomes.
+    pub fn build_run(&self, Pcross:f32, Pmut:f32, L:u8, n:usize, z:u8) -> Result<Run, GaError>
+    {
+        let mut run = Run::new(Pcross, Pmut, L, n, z)?;
+        self.set_up(&mut run, L);
+        run.population = (0..n).map(|_| Chromosome::random(L)).collect();
+        Ok(run)
+    }
+
+    /// Gives `run`, on `L`-bit genomes, this problem's fitness and objective.
+    pub fn set_up(&self, run:&mut Run, L:u8)
+    {
+        match self.fitness {
+            ProblemFitness::Fixed(fitness) => run.set_fitness(fitness),
+            ProblemFitness::PerLength(build) => run.set_fitness_function(build(L)),
+        }
+        run.set_objective(self.objective);
+    }
+}
+
+/// Problems selectable by name, e.g. from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct ProblemRegistry
+{
+    problems:BTreeMap<String, Problem>,
+}
+
+impl ProblemRegistry
+{
+    pub fn new() -> Self
+    {
+        ProblemRegistry::default()
+    }
+
+    pub fn with_builtins() -> Self
+    {
+        let mut registry = ProblemRegistry::new();
+        registry.register(Problem::new("cournot", "profit of a firm in a Cournot market, data is its output", cournot_fitness, 10));
+        registry.register(Problem::new("onemax", "number of set bits", onemax_fitness, 32));
+        registry.register(Problem::per_length("leadingones", "number of leading set bits", |L| Box::new(LeadingOnes {L}), 32));
+        registry
+    }
+
+    /// Adds `problem`, replacing any problem registered under the same name.
+    pub fn register(&mut self, problem:Problem)
+    {
+        self.problems.insert(problem.name.clone(), problem);
+    }
+
+    pub fn get(&self, name:&str) -> Option<&Problem>
+    {
+        self.problems.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str>
+    {
+        self.problems.keys().map(|k| k.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_fitness()
+    {
+        let context = PopulationStats::new(1, 0);
+        let ind = Chromosome {data:0b1110_0000, fitness:0.0, N:100, violation:0.0};
+        assert_eq!(onemax_fitness(&ind, 0), 3.0);
+        assert_eq!(LeadingOnes {L:8}.evaluate(&ind, &context), 3.0);
+        let ind = Chromosome {data:0xFF, fitness:0.0, N:0, violation:0.0};
+        assert_eq!(LeadingOnes {L:8}.evaluate(&ind, &context), 8.0);
+        assert_eq!(LeadingOnes {L:10}.evaluate(&ind, &context), 0.0);
+    }
+
+    #[test]
+    fn leading_ones_run()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 10, 30, 5, 2).unwrap();
+        test_run.set_fitness_function(Box::new(LeadingOnes {L:10}));
+        test_run.run(40).unwrap();
+        assert!(test_run.best().unwrap().fitness >= 3.0);
+
+        // more individuals than genome bits used to overflow the shift
+        let registry = ProblemRegistry::with_builtins();
+        let mut test_run = registry.get("leadingones").unwrap().build_run(0.6, 0.05, 10, 100, 5).unwrap();
+        test_run.run(5).unwrap();
+        assert!(test_run.best().unwrap().fitness > 0.0);
+    }
+
+    #[test]
+    fn registry_lookup()
+    {
+        let mut registry = ProblemRegistry::with_builtins();
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["cournot", "leadingones", "onemax"]);
+
+        fn zeros(ind:&Chromosome, _data_sum:u64) -> f64
+        {
+            ind.data.count_ones() as f64
+        }
+        registry.register(Problem::new("zeromax", "number of set bits, minimized", zeros, 16).with_objective(Objective::Minimize));
+        assert!(registry.get("zeromax").is_some());
+        assert!(registry.get("missing").is_none());
+    }
+
+    #[test]
+    fn onemax_run_improves()
+    {
+        let problem = ProblemRegistry::with_builtins().get("onemax").cloned().unwrap();
+        let mut test_run = problem.build_run(0.6, 0.3, 32, 40, 16).unwrap();
+        assert!(test_run.population.data.iter().all(|data| *data < 1 << 32));
+        test_run.run(60).unwrap();
+        assert!(test_run.best().unwrap().fitness >= 24.0);
+    }
+}
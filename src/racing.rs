@@ -0,0 +1,195 @@
+//! F-race (Birattari et al. 2002) for picking among algorithm configurations without running
+//! every one of them to the full budget. Each round runs all surviving candidates once on the
+//! same seed or instance and ranks them. Once `min_rounds` have been run, a Friedman test on
+//! the ranks decides whether the candidates differ, and if they do, every candidate whose rank
+//! sum is significantly worse than the best one's is dropped.
+use crate::objective::Objective;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Race
+{
+    pub objective:Objective,
+    /// Rounds before the first test.
+    pub min_rounds:usize,
+    pub max_rounds:usize,
+    /// Confidence of the Friedman and post-hoc tests.
+    pub confidence:f64,
+}
+
+impl Default for Race
+{
+    fn default() -> Self
+    {
+        Race {objective:Objective::Maximize, min_rounds:5, max_rounds:50, confidence:0.95}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaceResult
+{
+    /// Results of each candidate, one per round it took part in.
+    pub results:Vec<Vec<f64>>,
+    /// Round after which each candidate was dropped, `None` for survivors.
+    pub eliminated:Vec<Option<usize>>,
+}
+
+impl RaceResult
+{
+    pub fn survivors(&self) -> Vec<usize>
+    {
+        (0..self.eliminated.len()).filter(|&c| self.eliminated[c].is_none()).collect()
+    }
+
+    /// Trials run in total, comparable to `candidates * max_rounds` without racing.
+    pub fn trials(&self) -> usize
+    {
+        self.results.iter().map(Vec::len).sum()
+    }
+
+    /// The survivor with the best mean result.
+    pub fn best(&self, objective:Objective) -> usize
+    {
+        let mean = |c:usize| self.results[c].iter().sum::<f64>() / self.results[c].len() as f64;
+        self.survivors().into_iter().min_by(|&a, &b| objective.best_first(mean(a), mean(b))).unwrap()
+    }
+}
+
+impl Race
+{
+    /// Races `candidates` configurations, `trial(candidate, round)` being one run's final
+    /// fitness. All candidates get the same `round` in a round, use it as the seed so they are
+    /// compared on equal terms. Stops at `max_rounds` or when one candidate is left.
+    pub fn run<F:FnMut(usize, usize) -> f64>(&self, candidates:usize, mut trial:F) -> RaceResult
+    {
+        let mut result = RaceResult {results:vec![Vec::new(); candidates], eliminated:vec![None; candidates]};
+        let mut alive:Vec<usize> = (0..candidates).collect();
+        let mut blocks:Vec<Vec<f64>> = Vec::new();
+        for round in 0..self.max_rounds {
+            if alive.len() < 2 {
+                break;
+            }
+            for &c in &alive {
+                let fitness = trial(c, round);
+                result.results[c].push(fitness);
+            }
+            blocks.push(alive.iter().map(|&c| *result.results[c].last().unwrap()).collect());
+            if round + 1 < self.min_rounds.max(2) {
+                continue;
+            }
+            let dropped = self.worse_than_best(&blocks);
+            if dropped.is_empty() {
+                continue;
+            }
+            for &column in &dropped {
+                result.eliminated[alive[column]] = Some(round + 1);
+            }
+            let keep:Vec<usize> = (0..alive.len()).filter(|column| !dropped.contains(column)).collect();
+            alive = keep.iter().map(|&column| alive[column]).collect();
+            blocks = blocks.iter().map(|block| keep.iter().map(|&column| block[column]).collect()).collect();
+        }
+        result
+    }
+
+    // columns of `blocks` significantly worse than the best column, empty unless the Friedman
+    // test rejects that all columns are alike
+    fn worse_than_best(&self, blocks:&[Vec<f64>]) -> Vec<usize>
+    {
+        let columns = blocks[0].len();
+        let (b, k) = (blocks.len() as f64, columns as f64);
+        let ranks:Vec<Vec<f64>> = blocks.iter().map(|block| rank(block, self.objective)).collect();
+        let sums:Vec<f64> = (0..columns).map(|j| ranks.iter().map(|r| r[j]).sum()).collect();
+        let a:f64 = ranks.iter().flatten().map(|r| r * r).sum();
+        let c = b * k * (k + 1.0).powi(2) / 4.0;
+        if a <= c {
+            // every block tied throughout
+            return Vec::new();
+        }
+        let t = (k - 1.0) * sums.iter().map(|s| (s - b * (k + 1.0) / 2.0).powi(2)).sum::<f64>() / (a - c);
+        if t <= chi_square_quantile(self.confidence, k - 1.0) {
+            return Vec::new();
+        }
+        // Conover's post-hoc comparison against the best rank sum
+        let best = sums.iter().copied().fold(f64::INFINITY, f64::min);
+        let spread = (2.0 * b * (1.0 - t / (b * (k - 1.0))) * (a - c) / ((b - 1.0) * (k - 1.0))).max(0.0).sqrt();
+        let critical = t_quantile(1.0 - (1.0 - self.confidence) / 2.0, (b - 1.0) * (k - 1.0));
+        (0..columns).filter(|&j| sums[j] - best > critical * spread).collect()
+    }
+}
+
+// ranks 1..=k of `block`, best first, ties sharing their mean rank
+fn rank(block:&[f64], objective:Objective) -> Vec<f64>
+{
+    let mut order:Vec<usize> = (0..block.len()).collect();
+    order.sort_by(|&a, &b| objective.best_first(block[a], block[b]));
+    let mut ranks = vec![0.0; block.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && block[order[end]] == block[order[start]] {
+            end += 1;
+        }
+        let mean = (start + end + 1) as f64 / 2.0;
+        order[start..end].iter().for_each(|&i| ranks[i] = mean);
+        start = end;
+    }
+    ranks
+}
+
+// standard normal quantile for p in (0, 1), Abramowitz and Stegun 26.2.23, error below 5e-4
+fn normal_quantile(p:f64) -> f64
+{
+    if p < 0.5 {
+        return -normal_quantile(1.0 - p);
+    }
+    let t = (-2.0 * (1.0 - p).ln()).sqrt();
+    t - (2.515517 + 0.802853 * t + 0.010328 * t * t) / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t)
+}
+
+// Wilson-Hilferty approximation
+fn chi_square_quantile(p:f64, degrees:f64) -> f64
+{
+    let h = 2.0 / (9.0 * degrees);
+    degrees * (1.0 - h + normal_quantile(p) * h.sqrt()).powi(3)
+}
+
+// Cornish-Fisher expansion around the normal quantile
+fn t_quantile(p:f64, degrees:f64) -> f64
+{
+    let z = normal_quantile(p);
+    z + (z.powi(3) + z) / (4.0 * degrees) + (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / (96.0 * degrees * degrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn quantiles_match_tables()
+    {
+        assert!((normal_quantile(0.975) - 1.960).abs() < 1e-3);
+        assert!((chi_square_quantile(0.95, 2.0) - 5.991).abs() < 0.1);
+        assert!((chi_square_quantile(0.95, 9.0) - 16.919).abs() < 0.05);
+        assert!((t_quantile(0.975, 10.0) - 2.228).abs() < 0.01);
+        assert_eq!(rank(&[3.0, 1.0, 3.0, 2.0], Objective::Minimize), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+
+    #[test]
+    fn drops_inferior_configurations_early()
+    {
+        // candidate c scores around c when minimizing, give or take 0.6
+        let race = Race {objective:Objective::Minimize, max_rounds:30, ..Race::default()};
+        let result = race.run(4, |c, round| {
+            let mut rng = StdRng::seed_from_u64(round as u64 * 7 + c as u64);
+            c as f64 + rng.gen_range(-0.6..0.6)
+        });
+        assert_eq!(result.survivors(), vec![0]);
+        assert_eq!(result.best(Objective::Minimize), 0);
+        assert!(result.eliminated[3].unwrap() <= result.eliminated[1].unwrap());
+        assert!(result.trials() < 4 * 30);
+
+        let tied = Race::default().run(3, |_, _| 1.0);
+        assert_eq!(tied.survivors(), vec![0, 1, 2]);
+        assert_eq!(tied.trials(), 3 * 50);
+    }
+}
@@ -0,0 +1,351 @@
+//! Real-valued genomes for continuous problems. `RealRun` evolves them inside per-gene bounds,
+//! by default with arithmetic crossover and Gaussian mutation. Genes are clamped back into
+//! their bounds after every crossover and mutation.
+use crate::evolution::{Genome, GenomeRun};
+use crate::sampling;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub type RealFitnessFn = Arc<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// `GenomeRun` over real vectors, its context is the `(low, high)` bounds of every gene.
+pub type RealRun = GenomeRun<RealChromosome>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RealChromosome
+{
+    pub genes:Vec<f64>,
+    pub fitness:f64,
+}
+
+impl RealChromosome
+{
+    pub fn new(genes:Vec<f64>) -> Self
+    {
+        RealChromosome {genes, fitness:0.0}
+    }
+
+    /// Genes drawn uniformly within `bounds`.
+    pub fn random<R:Rng + ?Sized>(bounds:&[(f64, f64)], rng:&mut R) -> Self
+    {
+        Self::new(bounds.iter().map(|&(low, high)| if low < high { rng.gen_range(low..high) } else { low }).collect())
+    }
+
+    pub fn clamp(&mut self, bounds:&[(f64, f64)])
+    {
+        for (gene, &(low, high)) in self.genes.iter_mut().zip(bounds) {
+            *gene = gene.clamp(low, high);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RealCrossover
+{
+    /// `alpha * a + (1 - alpha) * b` with one uniform `alpha` for the whole genome.
+    #[default]
+    Arithmetic,
+    /// Each gene uniform in the parents' interval widened by `alpha` times its length on both
+    /// sides, 0.5 is the usual choice.
+    Blx{alpha:f64},
+    /// Simulated binary crossover, larger `eta` keeps the child closer to the parents.
+    Sbx{eta:f64},
+}
+
+impl RealCrossover
+{
+    pub fn apply<R:Rng + ?Sized>(self, a:&[f64], b:&[f64], rng:&mut R) -> Vec<f64>
+    {
+        match self {
+            RealCrossover::Arithmetic => {
+                let alpha:f64 = rng.gen();
+                a.iter().zip(b).map(|(x, y)| alpha * x + (1.0 - alpha) * y).collect()
+            }
+            RealCrossover::Blx{alpha} => a.iter().zip(b).map(|(&x, &y)| {
+                let spread = alpha * (x - y).abs();
+                let (low, high) = (x.min(y) - spread, x.max(y) + spread);
+                if low < high { rng.gen_range(low..high) } else { low }
+            }).collect(),
+            RealCrossover::Sbx{eta} => a.iter().zip(b).map(|(&x, &y)| {
+                let u:f64 = rng.gen();
+                let beta = if u <= 0.5 { (2.0 * u).powf(1.0 / (eta + 1.0)) } else { (0.5 / (1.0 - u)).powf(1.0 / (eta + 1.0)) };
+                // either of the two SBX children, so the gene is equally likely to stay near x or y
+                if rng.gen::<bool>() { 0.5 * ((1.0 + beta) * x + (1.0 - beta) * y) } else { 0.5 * ((1.0 - beta) * x + (1.0 + beta) * y) }
+            }).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RealMutation
+{
+    /// Adds normal noise with standard deviation `sigma` times the gene's range.
+    Gaussian{sigma:f64},
+    /// Deb's polynomial mutation, larger `eta` makes smaller steps.
+    Polynomial{eta:f64},
+}
+
+impl Default for RealMutation
+{
+    fn default() -> Self
+    {
+        RealMutation::Gaussian{sigma:0.1}
+    }
+}
+
+impl RealMutation
+{
+    /// Mutates each gene with probability `rate` and clamps it into its bounds.
+    pub fn apply<R:Rng + ?Sized>(self, genes:&mut [f64], rate:f64, bounds:&[(f64, f64)], rng:&mut R)
+    {
+        for (gene, &(low, high)) in genes.iter_mut().zip(bounds) {
+            if rng.gen::<f64>() >= rate {
+                continue;
+            }
+            let step = match self {
+                RealMutation::Gaussian{sigma} => sigma * sampling::gaussian(rng),
+                RealMutation::Polynomial{eta} => {
+                    let u:f64 = rng.gen();
+                    if u < 0.5 { (2.0 * u).powf(1.0 / (eta + 1.0)) - 1.0 } else { 1.0 - (2.0 * (1.0 - u)).powf(1.0 / (eta + 1.0)) }
+                }
+            };
+            *gene = (*gene + step * (high - low)).clamp(low, high);
+        }
+    }
+}
+
+impl Genome for RealChromosome
+{
+    type Context = Vec<(f64, f64)>;
+    type Crossover = RealCrossover;
+    type Mutation = RealMutation;
+
+    fn fitness(&self) -> f64
+    {
+        self.fitness
+    }
+
+    fn set_fitness(&mut self, fitness:f64)
+    {
+        self.fitness = fitness;
+    }
+
+    fn crossover(a:&Self, b:&Self, operator:RealCrossover, bounds:&Vec<(f64, f64)>, rng:&mut dyn RngCore) -> Self
+    {
+        let mut child = RealChromosome::new(operator.apply(&a.genes, &b.genes, rng));
+        child.clamp(bounds);
+        child
+    }
+
+    /// `rate` is the per-gene mutation probability.
+    fn mutate(&mut self, operator:&RealMutation, rate:f64, bounds:&Vec<(f64, f64)>, rng:&mut dyn RngCore)
+    {
+        operator.apply(&mut self.genes, rate, bounds, rng);
+    }
+}
+
+/// How the initial population of a `RealRun` is spread over the bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Initialization
+{
+    /// Independent uniform draws.
+    #[default]
+    Uniform,
+    /// Each gene's range is cut into `n` strata and every stratum gets exactly one individual.
+    LatinHypercube,
+    /// A randomly shifted Sobol sequence, even in every projection for `n` a power of two.
+    /// Genomes longer than 21 genes fall back to `LatinHypercube`.
+    Sobol,
+}
+
+impl Initialization
+{
+    /// `n` genomes within `bounds`.
+    pub fn sample<R:Rng + ?Sized>(self, bounds:&[(f64, f64)], n:usize, rng:&mut R) -> Vec<RealChromosome>
+    {
+        let unit = match self {
+            Initialization::Uniform => return (0..n).map(|_| RealChromosome::random(bounds, rng)).collect(),
+            Initialization::Sobol if bounds.len() <= sampling::SOBOL_DIMENSIONS => {
+                let shift:Vec<u32> = bounds.iter().map(|_| rng.gen()).collect();
+                sampling::sobol(bounds.len(), n, &shift)
+            }
+            _ => sampling::latin_hypercube(rng, bounds.len(), n),
+        };
+        unit.into_iter().map(|point| {
+            RealChromosome::new(point.iter().zip(bounds).map(|(u, &(low, high))| low + u * (high - low)).collect())
+        }).collect()
+    }
+}
+
+impl RealRun
+{
+    /// `n` random individuals within `bounds`, maximizing `fitness` by default. Crossover
+    /// probability starts at 0.9 and mutation at one gene per genome.
+    pub fn new<F>(bounds:Vec<(f64, f64)>, n:usize, fitness:F) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        Self::with_initialization(bounds, n, Initialization::Uniform, fitness)
+    }
+
+    /// Like `new` but reproducible: the initial population and every later draw come from an
+    /// RNG seeded with `seed`.
+    pub fn with_seed<F>(bounds:Vec<(f64, f64)>, n:usize, seed:u64, fitness:F) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        Self::from_rng(bounds, n, Initialization::Uniform, fitness, ChaCha12Rng::seed_from_u64(seed))
+    }
+
+    /// Like `new` with the initial population spread by `initialization`.
+    pub fn with_initialization<F>(bounds:Vec<(f64, f64)>, n:usize, initialization:Initialization, fitness:F) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        Self::from_rng(bounds, n, initialization, fitness, ChaCha12Rng::from_entropy())
+    }
+
+    fn from_rng<F>(bounds:Vec<(f64, f64)>, n:usize, initialization:Initialization, fitness:F, mut rng:ChaCha12Rng) -> Self
+        where F:Fn(&[f64]) -> f64 + Send + Sync + 'static
+    {
+        let population = initialization.sample(&bounds, n, &mut rng);
+        let Pmut = 1.0 / bounds.len().max(1) as f32;
+        let mut run = GenomeRun::from_population(population, bounds, move |ind:&RealChromosome| fitness(&ind.genes));
+        run.Pmut = Pmut;
+        run.rng = rng;
+        run
+    }
+
+    pub fn bounds(&self) -> &[(f64, f64)]
+    {
+        &self.context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::EvaluationBudget;
+    use crate::evolution::GenomeStats;
+    use crate::objective::Objective;
+    use crate::observer::GenomeObserver;
+    use crate::operators::Tournament;
+
+    #[test]
+    fn minimizes_sphere()
+    {
+        let mut test_run = RealRun::new(vec![(-5.0, 5.0); 3], 40, |x:&[f64]| x.iter().map(|v| v * v).sum());
+        test_run.set_objective(Objective::Minimize);
+        test_run.run(100);
+        let best = test_run.best().unwrap();
+        assert!(best.fitness < 0.05, "best {:?}", best);
+        assert_eq!(test_run.evaluations(), 4000);
+        assert!(test_run.history().windows(2).all(|w| w[1].best_fitness <= w[0].best_fitness));
+    }
+
+    #[test]
+    fn seeded_runs_repeat()
+    {
+        let run = |seed| {
+            let mut test_run = RealRun::with_seed(vec![(-5.0, 5.0); 3], 10, seed, |x:&[f64]| x.iter().sum());
+            test_run.run(10);
+            (test_run.history().to_vec(), test_run.population)
+        };
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1).1, run(2).1);
+    }
+
+    #[derive(Debug)]
+    struct StopAt(u32);
+
+    impl GenomeObserver<RealChromosome> for StopAt
+    {
+        fn on_evaluated(&self, _generation:u32, population:&[RealChromosome])
+        {
+            assert_eq!(population.len(), 20);
+        }
+
+        fn on_generation_end(&self, stats:&GenomeStats) -> bool
+        {
+            stats.generation == self.0
+        }
+    }
+
+    #[test]
+    fn constraints_selection_and_budget()
+    {
+        let mut test_run = RealRun::with_seed(vec![(-5.0, 5.0); 2], 20, 3, |x:&[f64]| x.iter().map(|v| v * v).sum());
+        test_run.set_objective(Objective::Minimize);
+        test_run.add_constraint(|ind:&RealChromosome| 1.0 - ind.genes[0]);
+        test_run.set_penalty(100.0);
+        test_run.set_elitism(2);
+        test_run.set_selection(Box::new(Tournament {size:3}));
+        test_run.set_evaluation_budget(EvaluationBudget::new(1000));
+        test_run.run(100);
+        assert_eq!((test_run.generation(), test_run.evaluations()), (50, 1000));
+        let best = test_run.best().unwrap();
+        assert!(best.genes[0] >= 1.0 && best.fitness < 1.1, "{:?}", best);
+        assert!(test_run.history().last().unwrap().feasible_fraction > 0.5);
+        assert!(test_run.history()[0].feasible_fraction < 0.5);
+
+        let mut stopped = RealRun::with_seed(vec![(-5.0, 5.0); 2], 20, 3, |x:&[f64]| x[0]);
+        stopped.add_observer(Box::new(StopAt(4)));
+        stopped.run(10);
+        assert_eq!(stopped.generation(), 4);
+    }
+
+    #[test]
+    fn initializations_cover_the_bounds()
+    {
+        let bounds = [(-5.0, 5.0), (10.0, 20.0)];
+        let mut rng = rand::thread_rng();
+        for initialization in [Initialization::Uniform, Initialization::LatinHypercube, Initialization::Sobol] {
+            let population = initialization.sample(&bounds, 8, &mut rng);
+            assert_eq!(population.len(), 8);
+            assert!(population.iter().all(|ind| (-5.0..5.0).contains(&ind.genes[0]) && (10.0..20.0).contains(&ind.genes[1])));
+        }
+        // one individual per eighth of the second gene's range
+        let mut strata:Vec<usize> = Initialization::Sobol.sample(&bounds, 8, &mut rng).iter()
+            .map(|ind| ((ind.genes[1] - 10.0) / 1.25) as usize).collect();
+        strata.sort();
+        assert_eq!(strata, (0..8).collect::<Vec<usize>>());
+        let test_run = RealRun::with_initialization(vec![(0.0, 1.0); 30], 10, Initialization::Sobol, |x:&[f64]| x[0]);
+        assert_eq!(test_run.population.len(), 10);
+    }
+
+    #[test]
+    fn genes_stay_in_bounds()
+    {
+        let mut test_run = RealRun::new(vec![(0.0, 1.0), (10.0, 20.0)], 10, |x:&[f64]| x[0] + x[1]);
+        test_run.mutation = RealMutation::Gaussian{sigma:5.0};
+        test_run.crossover = RealCrossover::Blx{alpha:2.0};
+        test_run.Pmut = 1.0;
+        test_run.run(10);
+        assert!(test_run.population.iter().all(|ind| (0.0..=1.0).contains(&ind.genes[0]) && (10.0..=20.0).contains(&ind.genes[1])));
+        assert_eq!(test_run.best().unwrap().genes.len(), 2);
+    }
+
+    #[test]
+    fn operators_solve_sphere()
+    {
+        let crossovers = [RealCrossover::Arithmetic, RealCrossover::Blx{alpha:0.5}, RealCrossover::Sbx{eta:15.0}];
+        let mutations = [RealMutation::Gaussian{sigma:0.05}, RealMutation::Polynomial{eta:20.0}];
+        for crossover in crossovers {
+            for mutation in mutations {
+                let mut test_run = RealRun::new(vec![(-5.0, 5.0); 2], 30, |x:&[f64]| x.iter().map(|v| v * v).sum());
+                test_run.set_objective(Objective::Minimize);
+                (test_run.crossover, test_run.mutation) = (crossover, mutation);
+                test_run.run(80);
+                assert!(test_run.best().unwrap().fitness < 0.1, "{:?} {:?}: {:?}", crossover, mutation, test_run.best());
+            }
+        }
+    }
+
+    #[test]
+    fn sbx_children_are_centred_on_parents()
+    {
+        let mut rng = rand::thread_rng();
+        let children:Vec<f64> = (0..2000).map(|_| RealCrossover::Sbx{eta:2.0}.apply(&[1.0], &[3.0], &mut rng)[0]).collect();
+        let mean = children.iter().sum::<f64>() / children.len() as f64;
+        assert!((mean - 2.0).abs() < 0.2, "mean {}", mean);
+    }
+}
@@ -0,0 +1,115 @@
+//! Discrete replicator dynamics for comparing GA learning with evolutionary game dynamics. Every
+//! distinct genotype of the starting population is a strategy, and each step its share grows
+//! in proportion to its fitness against the current mix:
+//! `x_s <- x_s * f_s(x) / mean fitness`.
+use crate::error::GaError;
+use crate::operators::Fitness;
+use crate::stats::{GenerationStats, PopulationStats};
+use crate::{Chromosome, Run};
+use std::collections::BTreeMap;
+use std::fs::File;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicatorPoint
+{
+    pub generation:u32,
+    /// Share of each strategy, keyed by its quantity.
+    pub shares:BTreeMap<u64, f64>,
+    /// Expected industry output of a population of the original size.
+    pub ind_out:f64,
+    pub mean_fitness:f64,
+}
+
+/// Trajectory of `generations` replicator steps from the distribution of `quantities`. Fitness
+/// must be non-negative; the dynamics stop moving once mean fitness is zero.
+pub fn replicator_dynamics(quantities:&[u64], fitness:&dyn Fitness, generations:u32) -> Vec<ReplicatorPoint>
+{
+    let n = quantities.len() as f64;
+    let mut shares:BTreeMap<u64, f64> = BTreeMap::new();
+    for q in quantities {
+        *shares.entry(*q).or_insert(0.0) += 1.0 / n;
+    }
+
+    let mut trajectory = Vec::with_capacity(generations as usize + 1);
+    for generation in 0..=generations {
+        let mean_quantity:f64 = shares.iter().map(|(q, x)| *q as f64 * x).sum();
+        let data_sum = (n * mean_quantity).round() as u64;
+        let context = PopulationStats {generation, size:quantities.len(), data_sum};
+        let payoffs:BTreeMap<u64, f64> = shares.keys()
+            .map(|&q| (q, fitness.evaluate(&Chromosome {data:q, fitness:0.0, N:quantities.len(), violation:0.0}, &context).max(0.0)))
+            .collect();
+        let mean_fitness:f64 = shares.iter().map(|(q, x)| x * payoffs[q]).sum();
+        trajectory.push(ReplicatorPoint {generation, shares:shares.clone(), ind_out:n * mean_quantity, mean_fitness});
+
+        if mean_fitness > 0.0 {
+            for (q, x) in shares.iter_mut() {
+                *x *= payoffs[q] / mean_fitness;
+            }
+        }
+    }
+    trajectory
+}
+
+impl Run
+{
+    /// Replicator trajectory from the current population with this run's fitness and decoding,
+    /// call it before `run` to start from the same initial distribution as the GA.
+    pub fn replicator_trajectory(&self, generations:u32) -> Vec<ReplicatorPoint>
+    {
+        replicator_dynamics(&self.phenotypes().data, self.fitness.as_ref(), generations)
+    }
+}
+
+/// Industry output of the GA next to the replicator trajectory, matched by generation. The GA's
+/// generation `g` is compared with replicator step `g - 1`, both having seen `g - 1` updates.
+pub fn save_replicator_comparison(ga:&[GenerationStats], replicator:&[ReplicatorPoint], file_name:&str) -> Result<(), GaError>
+{
+    let file = File::create(file_name)?;
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(["generation", "ga_ind_out", "replicator_ind_out", "replicator_mean_fitness"])?;
+    for stats in ga {
+        let point = replicator.iter().find(|p| p.generation + 1 == stats.generation);
+        writer.write_record(&[
+            stats.generation.to_string(),
+            stats.ind_out.to_string(),
+            point.map_or(String::new(), |p| p.ind_out.to_string()),
+            point.map_or(String::new(), |p| p.mean_fitness.to_string()),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::onemax_fitness;
+    use crate::FitnessFn;
+
+    #[test]
+    fn fitter_strategy_takes_over()
+    {
+        let trajectory = replicator_dynamics(&[1, 3, 3, 7], &(onemax_fitness as FitnessFn), 30);
+        assert_eq!(trajectory.len(), 31);
+        assert_eq!(trajectory[0].shares[&3], 0.5);
+        let shares:Vec<f64> = trajectory.iter().map(|p| p.shares[&7]).collect();
+        assert!(shares.windows(2).all(|w| w[1] >= w[0]));
+        assert!(shares[30] > 0.99);
+        assert!(trajectory.iter().all(|p| (p.shares.values().sum::<f64>() - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn cournot_comparison()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2).unwrap();
+        let replicator = test_run.replicator_trajectory(20);
+        test_run.run(20).unwrap();
+        assert!(replicator.iter().all(|p| p.ind_out.is_finite() && p.mean_fitness >= 0.0));
+
+        let path = std::env::temp_dir().join(format!("ga_replicator_{}.csv", std::process::id()));
+        save_replicator_comparison(test_run.history(), &replicator, path.to_str().unwrap()).unwrap();
+        let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, 21);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+//! What `Run::run` hands back: the final population with everything needed to judge the run,
+//! under names instead of tuple positions.
+use crate::error::GaError;
+use crate::stats::{save_stats_to_csv, GenerationStats};
+use crate::Chromosome;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Why a call to `Run::run` returned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason
+{
+    /// All the requested generations ran.
+    Iterations,
+    /// `MaxGenerations` fired.
+    MaxGenerations,
+    /// `Stagnation` fired.
+    Stagnation,
+    /// `TargetFitness` fired.
+    TargetFitness,
+    /// `TimeLimit` fired.
+    TimeLimit,
+    /// A `Cancellation` was cancelled.
+    Cancelled,
+    /// A user-defined `Termination`, described by its `Debug` output unless it overrides
+    /// `Termination::reason`.
+    Criterion(String),
+    /// An `Observer` asked to stop.
+    Observer,
+    /// The shared `EvaluationBudget` is spent.
+    BudgetExhausted,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult
+{
+    pub population:Vec<Chromosome>,
+    /// Best individual of the whole run so far, see `Run::best`.
+    pub best:Option<Chromosome>,
+    /// Members of the run's `HallOfFame`, best first, empty without one.
+    pub hall_of_fame:Vec<Chromosome>,
+    /// Stats of the generations of this call kept in memory: none in low-memory mode and only
+    /// the retained ones under a `RetentionPolicy`.
+    pub stats:Vec<GenerationStats>,
+    pub stop_reason:StopReason,
+    pub elapsed:Duration,
+    /// Fitness evaluations of the whole run so far, see `Run::evaluations`.
+    pub evaluations:usize,
+}
+
+impl RunResult
+{
+    pub fn best(&self) -> Option<&Chromosome>
+    {
+        self.best.as_ref()
+    }
+
+    pub fn best_fitness(&self) -> Option<f64>
+    {
+        self.best.as_ref().map(|best| best.fitness)
+    }
+
+    /// Writes `stats` like `save_stats_to_csv`.
+    pub fn to_csv(&self, file_name:&str) -> Result<(), GaError>
+    {
+        save_stats_to_csv(&self.stats, file_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::{Cancellation, MaxGenerations, Stagnation, Termination, TimeLimit};
+    use crate::{problems, Run};
+
+    #[test]
+    fn results_say_why_the_run_stopped()
+    {
+        let mut test_run = Run::with_seed(0.6, 0.05, 8, 10, 4, 3).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        let result = test_run.run(5).unwrap();
+        assert_eq!(result.stop_reason, StopReason::Iterations);
+        assert_eq!((result.stats.len(), result.population.len(), result.evaluations), (5, 10, 50));
+        assert_eq!(result.best(), test_run.best());
+        assert_eq!(result.best_fitness(), Some(test_run.best().unwrap().fitness));
+
+        test_run.add_termination(Box::new(MaxGenerations(8)));
+        let result = test_run.run(10).unwrap();
+        assert_eq!(result.stop_reason, StopReason::MaxGenerations);
+        assert_eq!(result.stats.last().unwrap().generation, 8);
+
+        let path = std::env::temp_dir().join(format!("run_result_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        result.to_csv(path).unwrap();
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        let generations:Vec<u32> = reader.records().map(|r| r.unwrap()[0].parse().unwrap()).collect();
+        assert_eq!(generations, vec![6, 7, 8]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[derive(Debug)]
+    struct ThirdGeneration;
+
+    impl Termination for ThirdGeneration
+    {
+        fn should_stop(&self, stats:&GenerationStats) -> bool
+        {
+            stats.generation == 3
+        }
+    }
+
+    #[test]
+    fn each_criterion_reports_itself()
+    {
+        let new_run = || {
+            let mut run = Run::with_seed(0.6, 0.05, 6, 10, 4, 5).unwrap();
+            run.set_fitness(problems::onemax_fitness);
+            run
+        };
+        let mut test_run = new_run();
+        test_run.add_termination(Box::new(Stagnation(3)));
+        let result = test_run.run(500).unwrap();
+        assert_eq!(result.stop_reason, StopReason::Stagnation);
+        let last = result.stats.last().unwrap();
+        assert_eq!(last.stagnant_generations, 3);
+        assert!(result.stats.iter().any(|s| s.stagnant_generations == 0));
+
+        let cancellation = Cancellation::new();
+        let mut test_run = new_run();
+        test_run.add_termination(Box::new(cancellation.clone()));
+        cancellation.cancel();
+        let result = test_run.run(10).unwrap();
+        assert_eq!((result.stop_reason, result.stats.len()), (StopReason::Cancelled, 1));
+
+        let mut test_run = new_run();
+        test_run.add_termination(Box::new(TimeLimit::from_now(std::time::Duration::ZERO)));
+        assert_eq!(test_run.run(10).unwrap().stop_reason, StopReason::TimeLimit);
+
+        let mut test_run = new_run();
+        test_run.add_termination(Box::new(ThirdGeneration));
+        assert_eq!(test_run.run(10).unwrap().stop_reason, StopReason::Criterion("ThirdGeneration".to_string()));
+    }
+}
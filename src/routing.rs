@@ -0,0 +1,255 @@
+//! Route-improving local search for permutation genomes, read as closed tours over a symmetric
+//! distance matrix. Meant as a memetic step: a few improving moves per individual per
+//! generation, bounded by a move budget so the GA keeps most of the search.
+use crate::permutation::PermutationChromosome;
+use std::error::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceMatrix
+{
+    len:usize,
+    data:Vec<f64>,
+}
+
+impl DistanceMatrix
+{
+    /// Panics unless `rows` is square.
+    pub fn new(rows:Vec<Vec<f64>>) -> Self
+    {
+        let len = rows.len();
+        assert!(rows.iter().all(|row| row.len() == len), "distance matrix must be square");
+        DistanceMatrix {len, data:rows.into_iter().flatten().collect()}
+    }
+
+    /// Euclidean distances between points.
+    pub fn from_points(points:&[(f64, f64)]) -> Self
+    {
+        Self::new(points.iter()
+            .map(|a| points.iter().map(|b| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()).collect())
+            .collect())
+    }
+
+    /// Reads a square matrix from a CSV file without a header row.
+    pub fn from_csv(file_name:&str) -> Result<Self, Box<dyn Error>>
+    {
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).trim(csv::Trim::All).from_path(file_name)?;
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            rows.push(record?.iter().map(|value| value.parse::<f64>()).collect::<Result<Vec<f64>, _>>()?);
+        }
+        if rows.iter().any(|row| row.len() != rows.len()) {
+            return Err(format!("{}: distance matrix must be square", file_name).into());
+        }
+        Ok(Self::new(rows))
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    pub fn get(&self, from:usize, to:usize) -> f64
+    {
+        self.data[from * self.len + to]
+    }
+
+    /// Length of the closed tour visiting `order`.
+    pub fn tour_length(&self, order:&[usize]) -> f64
+    {
+        (0..order.len()).map(|i| self.get(order[i], order[(i + 1) % order.len()])).sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteMove
+{
+    /// Replaces two edges by reversing the segment between them.
+    TwoOpt,
+    /// Moves a segment of one to three elements elsewhere, keeping its direction.
+    OrOpt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RouteImprovement
+{
+    pub moves_evaluated:usize,
+    pub moves_applied:usize,
+    /// Reduction in tour length.
+    pub gain:f64,
+}
+
+/// First-improvement local search with the given moves. Every candidate move examined counts
+/// against `move_budget`, so one call costs at most that many distance look-up rounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteSearch
+{
+    pub moves:Vec<RouteMove>,
+    pub move_budget:usize,
+}
+
+// improvements smaller than this are rounding noise and would make the search cycle
+const EPSILON:f64 = 1e-9;
+
+impl RouteSearch
+{
+    pub fn new(moves:Vec<RouteMove>, move_budget:usize) -> Self
+    {
+        RouteSearch {moves, move_budget}
+    }
+
+    pub fn two_opt(move_budget:usize) -> Self
+    {
+        Self::new(vec![RouteMove::TwoOpt], move_budget)
+    }
+
+    pub fn or_opt(move_budget:usize) -> Self
+    {
+        Self::new(vec![RouteMove::OrOpt], move_budget)
+    }
+
+    /// Improves `order` in place until no move helps or the budget runs out.
+    pub fn improve(&self, order:&mut Vec<usize>, distances:&DistanceMatrix) -> RouteImprovement
+    {
+        let mut result = RouteImprovement::default();
+        let mut improved = true;
+        while improved && result.moves_evaluated < self.move_budget {
+            improved = false;
+            for route_move in &self.moves {
+                let gain = match route_move {
+                    RouteMove::TwoOpt => self.two_opt_pass(order, distances, &mut result.moves_evaluated),
+                    RouteMove::OrOpt => self.or_opt_pass(order, distances, &mut result.moves_evaluated),
+                };
+                if let Some(gain) = gain {
+                    result.gain += gain;
+                    result.moves_applied += 1;
+                    improved = true;
+                }
+            }
+        }
+        result
+    }
+
+    /// One memetic step over a population. Fitness is left as it was, re-evaluate the improved
+    /// individuals before selection.
+    pub fn improve_all(&self, population:&mut [PermutationChromosome], distances:&DistanceMatrix) -> RouteImprovement
+    {
+        let mut total = RouteImprovement::default();
+        for ind in population {
+            let result = self.improve(&mut ind.order, distances);
+            total.moves_evaluated += result.moves_evaluated;
+            total.moves_applied += result.moves_applied;
+            total.gain += result.gain;
+        }
+        total
+    }
+
+    // applies the first improving 2-opt move, returning its gain
+    fn two_opt_pass(&self, order:&mut [usize], d:&DistanceMatrix, evaluated:&mut usize) -> Option<f64>
+    {
+        let len = order.len();
+        for i in 0..len.saturating_sub(2) {
+            for j in i + 2..len {
+                if i == 0 && j == len - 1 {
+                    continue;
+                }
+                if *evaluated >= self.move_budget {
+                    return None;
+                }
+                *evaluated += 1;
+                let (a, b, c, e) = (order[i], order[i + 1], order[j], order[(j + 1) % len]);
+                let gain = d.get(a, b) + d.get(c, e) - d.get(a, c) - d.get(b, e);
+                if gain > EPSILON {
+                    order[i + 1..=j].reverse();
+                    return Some(gain);
+                }
+            }
+        }
+        None
+    }
+
+    // applies the first improving Or-opt move, returning its gain
+    fn or_opt_pass(&self, order:&mut Vec<usize>, d:&DistanceMatrix, evaluated:&mut usize) -> Option<f64>
+    {
+        let len = order.len();
+        for segment_len in 1..=3 {
+            if segment_len + 2 > len {
+                break;
+            }
+            for start in 0..=len - segment_len {
+                let end = start + segment_len;
+                let (first, last) = (order[start], order[end - 1]);
+                let (prev, next) = (order[(start + len - 1) % len], order[end % len]);
+                let removal = d.get(prev, first) + d.get(last, next) - d.get(prev, next);
+                let rest:Vec<usize> = order[..start].iter().chain(&order[end..]).copied().collect();
+                for q in 0..rest.len() {
+                    let (u, v) = (rest[q], rest[(q + 1) % rest.len()]);
+                    if u == prev {
+                        continue;
+                    }
+                    if *evaluated >= self.move_budget {
+                        return None;
+                    }
+                    *evaluated += 1;
+                    let gain = removal - (d.get(u, first) + d.get(last, v) - d.get(u, v));
+                    if gain > EPSILON {
+                        let mut moved = rest;
+                        moved.splice(q + 1..q + 1, order[start..end].iter().copied());
+                        *order = moved;
+                        return Some(gain);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permutation::is_permutation;
+
+    #[test]
+    fn two_opt_uncrosses_a_square()
+    {
+        let distances = DistanceMatrix::from_points(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]);
+        let mut order = vec![0, 1, 2, 3];
+        let before = distances.tour_length(&order);
+        let result = RouteSearch::two_opt(100).improve(&mut order, &distances);
+        assert!(is_permutation(&order));
+        assert!((distances.tour_length(&order) - 4.0).abs() < 1e-9);
+        assert!((before - 4.0 - result.gain).abs() < 1e-9);
+    }
+
+    #[test]
+    fn or_opt_moves_a_stray_city()
+    {
+        let points:Vec<(f64, f64)> = (0..8).map(|i| (i as f64, 0.0)).chain([(0.0, 1.0)]).collect();
+        let distances = DistanceMatrix::from_points(&points);
+        // city 8 sits next to city 0 but is visited between 4 and 5
+        let mut order = vec![0, 1, 2, 3, 4, 8, 5, 6, 7];
+        let before = distances.tour_length(&order);
+        let result = RouteSearch::or_opt(1000).improve(&mut order, &distances);
+        assert!(is_permutation(&order));
+        assert!(result.moves_applied > 0);
+        assert!((before - distances.tour_length(&order) - result.gain).abs() < 1e-9);
+    }
+
+    #[test]
+    fn budget_is_respected()
+    {
+        let mut rng = rand::thread_rng();
+        let points:Vec<(f64, f64)> = (0..30).map(|i| ((i * 7 % 30) as f64, (i * 11 % 30) as f64)).collect();
+        let distances = DistanceMatrix::from_points(&points);
+        let mut population:Vec<PermutationChromosome> = (0..4).map(|_| PermutationChromosome::random(30, &mut rng)).collect();
+        let search = RouteSearch::new(vec![RouteMove::TwoOpt, RouteMove::OrOpt], 50);
+        let result = search.improve_all(&mut population, &distances);
+        assert!(result.moves_evaluated <= 200);
+        assert!(population.iter().all(|ind| is_permutation(&ind.order)));
+    }
+}
@@ -0,0 +1,167 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Number of failures before the next success of independent trials with success probability `p`,
+/// so a loop over `n` trials only needs one draw per success instead of one per trial.
+/// Returns `usize::MAX` when `p` is zero or not a number.
+pub(crate) fn geometric_skip<R:Rng + ?Sized>(rng:&mut R, p:f64) -> usize
+{
+    if p >= 1.0 {
+        return 0;
+    }
+    if p.is_nan() || p <= 0.0 {
+        return usize::MAX;
+    }
+    // 1 - u is in (0, 1], keeping ln away from -inf
+    let u:f64 = 1.0 - rng.gen::<f64>();
+    let skip = (u.ln() / (-p).ln_1p()).floor();
+    if skip >= usize::MAX as f64 { usize::MAX } else { skip as usize }
+}
+
+/// Indices in `0..len` hit by independent trials with success probability `p`, in increasing order.
+pub(crate) fn bernoulli_indices<R:Rng + ?Sized>(rng:&mut R, p:f64, len:usize) -> Vec<usize>
+{
+    let mut hits = Vec::new();
+    let mut i = geometric_skip(rng, p);
+    while i < len {
+        hits.push(i);
+        i = i.saturating_add(1).saturating_add(geometric_skip(rng, p));
+    }
+    hits
+}
+
+/// Standard normal draw (Box-Muller).
+pub(crate) fn gaussian<R:Rng + ?Sized>(rng:&mut R) -> f64
+{
+    let u:f64 = 1.0 - rng.gen::<f64>();
+    let v:f64 = rng.gen();
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+/// `count` points of a Latin hypercube in `[0, 1)^dimensions`: along every axis each of the
+/// `count` equal strata holds exactly one point.
+pub(crate) fn latin_hypercube<R:Rng + ?Sized>(rng:&mut R, dimensions:usize, count:usize) -> Vec<Vec<f64>>
+{
+    let mut points = vec![Vec::with_capacity(dimensions); count];
+    for _ in 0..dimensions {
+        let mut strata:Vec<usize> = (0..count).collect();
+        strata.shuffle(rng);
+        for (point, stratum) in points.iter_mut().zip(strata) {
+            point.push((stratum as f64 + rng.gen::<f64>()) / count as f64);
+        }
+    }
+    points
+}
+
+/// Dimensions `sobol` has direction numbers for.
+pub(crate) const SOBOL_DIMENSIONS:usize = 21;
+
+// degree, coefficients and initial direction numbers of dimensions 2 to 21, from Joe and Kuo's
+// new-joe-kuo-6.21201 table
+const SOBOL_TABLE:[(u32, u32, &[u32]); SOBOL_DIMENSIONS - 1] = [
+    (1, 0, &[1]), (2, 1, &[1, 3]), (3, 1, &[1, 3, 1]), (3, 2, &[1, 1, 1]), (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]), (5, 2, &[1, 1, 5, 5, 17]), (5, 4, &[1, 1, 5, 5, 5]), (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]), (5, 13, &[1, 1, 1, 3, 11]), (5, 14, &[1, 3, 5, 5, 31]), (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]), (6, 16, &[1, 3, 1, 13, 27, 49]), (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]), (6, 25, &[1, 1, 5, 5, 19, 61]), (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+fn sobol_directions(dimension:usize) -> [u32; 32]
+{
+    let mut v = [0u32; 32];
+    if dimension == 0 {
+        (0..32).for_each(|k| v[k] = 1 << (31 - k));
+        return v;
+    }
+    let (s, a, m) = SOBOL_TABLE[dimension - 1];
+    let s = s as usize;
+    for k in 0..32 {
+        v[k] = if k < s {
+            m[k] << (31 - k)
+        }else{
+            let mut value = v[k - s] ^ (v[k - s] >> s);
+            for i in 1..s {
+                if (a >> (s - 1 - i)) & 1 == 1 {
+                    value ^= v[k - i];
+                }
+            }
+            value
+        };
+    }
+    v
+}
+
+/// The first `count` points of the Sobol sequence in `[0, 1)^dimensions`, each coordinate XORed
+/// with `shift[dimension]`. A random digital shift keeps the stratification of the sequence,
+/// shift by zero for the plain one. Panics beyond `SOBOL_DIMENSIONS`.
+pub(crate) fn sobol(dimensions:usize, count:usize, shift:&[u32]) -> Vec<Vec<f64>>
+{
+    assert!(dimensions <= SOBOL_DIMENSIONS, "Sobol points are available up to {} dimensions", SOBOL_DIMENSIONS);
+    let directions:Vec<[u32; 32]> = (0..dimensions).map(sobol_directions).collect();
+    let mut x = vec![0u32; dimensions];
+    let mut points = Vec::with_capacity(count);
+    for i in 0..count {
+        points.push(x.iter().zip(shift).map(|(x, shift)| (x ^ shift) as f64 / 4294967296.0).collect());
+        // Gray-code order: the next point flips the direction of the lowest zero bit of `i`
+        let bit = (!i).trailing_zeros() as usize;
+        if bit < 32 {
+            x.iter_mut().zip(&directions).for_each(|(x, v)| *x ^= v[bit]);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_probabilities()
+    {
+        let mut rng = rand::thread_rng();
+        assert_eq!(geometric_skip(&mut rng, 1.0), 0);
+        assert_eq!(geometric_skip(&mut rng, 0.0), usize::MAX);
+        assert_eq!(bernoulli_indices(&mut rng, 1.0, 5), vec![0, 1, 2, 3, 4]);
+        assert!(bernoulli_indices(&mut rng, 0.0, 5).is_empty());
+    }
+
+    #[test]
+    fn hit_rate_matches_probability()
+    {
+        let mut rng = rand::thread_rng();
+        let hits = bernoulli_indices(&mut rng, 0.01, 1_000_000);
+        let rate = hits.len() as f64 / 1_000_000.0;
+        assert!((rate - 0.01).abs() < 0.001, "rate {}", rate);
+        assert!(hits.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn gaussian_moments()
+    {
+        let mut rng = rand::thread_rng();
+        let draws:Vec<f64> = (0..100_000).map(|_| gaussian(&mut rng)).collect();
+        let mean = draws.iter().sum::<f64>() / draws.len() as f64;
+        let variance = draws.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / draws.len() as f64;
+        assert!(mean.abs() < 0.02 && (variance - 1.0).abs() < 0.03, "mean {} variance {}", mean, variance);
+    }
+
+    #[test]
+    fn quasi_random_points_are_stratified()
+    {
+        let mut rng = rand::thread_rng();
+        // one point per sixteenth along each axis
+        let stratified = |points:&[Vec<f64>], dimension:usize| {
+            let mut strata:Vec<usize> = points.iter().map(|p| (p[dimension] * 16.0) as usize).collect();
+            strata.sort();
+            strata == (0..16).collect::<Vec<usize>>()
+        };
+        let plain = sobol(SOBOL_DIMENSIONS, 16, &[0; SOBOL_DIMENSIONS]);
+        assert_eq!(plain[..4].iter().map(|p| p[1]).collect::<Vec<f64>>(), vec![0.0, 0.5, 0.25, 0.75]);
+        let shift:Vec<u32> = (0..SOBOL_DIMENSIONS).map(|_| rng.gen()).collect();
+        let shifted = sobol(SOBOL_DIMENSIONS, 16, &shift);
+        assert!((0..SOBOL_DIMENSIONS).all(|d| stratified(&plain, d) && stratified(&shifted, d)));
+        let latin = latin_hypercube(&mut rng, 3, 16);
+        assert!((0..3).all(|d| stratified(&latin, d)));
+    }
+}
@@ -0,0 +1,87 @@
+//! Fitness functions written in rhai (`scripting` feature).
+//!
+//! The script defines `fn fitness(data, data_sum, n)`, all three integers, and returns a
+//! number. A script error counts as a NaN fitness, handled by the run's `NonFinitePolicy`.
+use crate::operators::Fitness;
+use crate::stats::PopulationStats;
+use crate::Chromosome;
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct ScriptFitness
+{
+    source:String,
+    engine:Arc<Engine>,
+    ast:Arc<AST>,
+}
+
+impl fmt::Debug for ScriptFitness
+{
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result
+    {
+        f.debug_struct("ScriptFitness").field("source", &self.source).finish()
+    }
+}
+
+impl ScriptFitness
+{
+    pub fn from_source(source:&str) -> Result<Self, Box<dyn Error>>
+    {
+        let engine = Engine::new();
+        let ast = engine.compile(source)?;
+        if !ast.iter_functions().any(|f| f.name == "fitness" && f.params.len() == 3) {
+            return Err("script must define fn fitness(data, data_sum, n)".into());
+        }
+        Ok(ScriptFitness {source:source.to_string(), engine:Arc::new(engine), ast:Arc::new(ast)})
+    }
+
+    pub fn from_file(path:&Path) -> Result<Self, Box<dyn Error>>
+    {
+        Self::from_source(&std::fs::read_to_string(path)?)
+    }
+}
+
+impl Fitness for ScriptFitness
+{
+    fn evaluate(&self, ind:&Chromosome, context:&PopulationStats) -> f64
+    {
+        let args = (ind.data as i64, context.data_sum as i64, ind.N as i64);
+        let result = self.engine.call_fn::<Dynamic>(&mut Scope::new(), &self.ast, "fitness", args);
+        match result {
+            Ok(value) => value.as_float().or_else(|_| value.as_int().map(|i| i as f64)).unwrap_or(f64::NAN),
+            Err(_) => f64::NAN,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ind(data:u64) -> Chromosome
+    {
+        Chromosome {data, fitness:0.0, N:8, violation:0.0}
+    }
+
+    #[test]
+    fn evaluates_script()
+    {
+        let script = ScriptFitness::from_source("fn fitness(data, data_sum, n) { (data * 2).to_float() + 0.5 }").unwrap();
+        assert_eq!(script.evaluate(&ind(3), &PopulationStats::new(2, 10)), 6.5);
+        let ints = ScriptFitness::from_source("fn fitness(data, data_sum, n) { data_sum - data }").unwrap();
+        assert_eq!(ints.evaluate(&ind(3), &PopulationStats::new(2, 10)), 7.0);
+    }
+
+    #[test]
+    fn rejects_scripts_without_fitness()
+    {
+        assert!(ScriptFitness::from_source("fn other(x) { x }").is_err());
+        assert!(ScriptFitness::from_source("fn fitness(").is_err());
+        let failing = ScriptFitness::from_source("fn fitness(data, data_sum, n) { throw \"no\" }").unwrap();
+        assert!(failing.evaluate(&ind(1), &PopulationStats::new(2, 1)).is_nan());
+    }
+}
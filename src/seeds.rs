@@ -0,0 +1,111 @@
+//! Every seed of a multi-run experiment from one master seed. Replicates, restarts and islands
+//! each get a seed derived with SplitMix64 from the master seed, a label and an index, so the
+//! whole experiment reproduces from a single number. The sequence records each derivation,
+//! for saving next to the results.
+use crate::events::fnv1a;
+use serde::{Deserialize, Serialize};
+
+const GOLDEN_GAMMA:u64 = 0x9e3779b97f4a7c15;
+
+// the SplitMix64 output function
+fn splitmix64(state:u64) -> u64
+{
+    let mut z = state.wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedDerivation
+{
+    pub label:String,
+    pub index:u64,
+    pub seed:u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedSequence
+{
+    master:u64,
+    derivations:Vec<SeedDerivation>,
+}
+
+impl SeedSequence
+{
+    pub fn new(master:u64) -> Self
+    {
+        SeedSequence {master, derivations:Vec::new()}
+    }
+
+    pub fn master(&self) -> u64
+    {
+        self.master
+    }
+
+    /// The seed for run `index` of the kind `label`, e.g. `("replicate", 3)`. It only depends on
+    /// the master seed, the label and the index, not on what was derived before.
+    pub fn derive(&mut self, label:&str, index:u64) -> u64
+    {
+        let stream = splitmix64(self.master ^ fnv1a(label.as_bytes()));
+        let seed = splitmix64(stream.wrapping_add(index.wrapping_mul(GOLDEN_GAMMA)));
+        self.derivations.push(SeedDerivation {label:label.to_string(), index, seed});
+        seed
+    }
+
+    /// Seeds for `count` replicates, labelled `"replicate"`.
+    pub fn replicates(&mut self, count:u64) -> Vec<u64>
+    {
+        (0..count).map(|index| self.derive("replicate", index)).collect()
+    }
+
+    /// Every seed handed out so far, in order.
+    pub fn derivations(&self) -> &[SeedDerivation]
+    {
+        &self.derivations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::island::{IslandRun, Topology};
+    use crate::{problems, Run};
+
+    #[test]
+    fn seeds_depend_only_on_master_label_and_index()
+    {
+        let mut a = SeedSequence::new(42);
+        let mut b = SeedSequence::new(42);
+        let replicates = a.replicates(4);
+        b.derive("island", 0);
+        assert_eq!(b.derive("replicate", 2), replicates[2]);
+        assert_ne!(b.derive("restart", 2), replicates[2]);
+        assert_ne!(SeedSequence::new(43).derive("replicate", 2), replicates[2]);
+        let mut distinct = replicates.clone();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 4);
+        assert_eq!(a.derivations()[3], SeedDerivation {label:"replicate".to_string(), index:3, seed:replicates[3]});
+    }
+
+    #[test]
+    fn island_models_reproduce_from_the_master_seed()
+    {
+        let build = |seed| {
+            let mut run = Run::with_seed(0.6, 0.05, 16, 10, 8, seed).unwrap();
+            run.set_fitness(problems::onemax_fitness);
+            run
+        };
+        let model = |master| {
+            let mut seeds = SeedSequence::new(master);
+            let mut model = IslandRun::seeded(&mut seeds, 3, build, Topology::Random, 4, 1);
+            model.run(12);
+            (model.islands().iter().map(Run::population_snapshot).collect::<Vec<_>>(), seeds)
+        };
+        let (populations, seeds) = model(7);
+        assert_eq!(model(7).0, populations);
+        assert_ne!(model(8).0, populations);
+        let labels:Vec<&str> = seeds.derivations().iter().map(|d| d.label.as_str()).collect();
+        assert_eq!(labels, vec!["island", "island", "island", "topology"]);
+    }
+}
@@ -0,0 +1,156 @@
+//! Dynamic markets: demand and cost shocks applied at the start of chosen generations, either on
+//! a fixed schedule or drawn at random. A shocked generation has `shock` set in its stats.
+use crate::cournot::Market;
+use crate::Run;
+use rand::Rng;
+use std::sync::Arc;
+
+/// A change to the market, deltas are added to the current value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketChange
+{
+    Intercept(f64),
+    OwnSlope(f64),
+    CrossSlope(f64),
+    /// Added to the marginal cost of every firm.
+    Cost(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shock
+{
+    pub generation:u32,
+    pub change:MarketChange,
+}
+
+/// Every generation the intercept is shocked with `probability` by a delta drawn uniformly from
+/// `-magnitude..=magnitude`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomShocks
+{
+    pub probability:f64,
+    pub magnitude:f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShockSchedule
+{
+    pub scheduled:Vec<Shock>,
+    pub random:Option<RandomShocks>,
+}
+
+impl ShockSchedule
+{
+    pub fn at(mut self, generation:u32, change:MarketChange) -> Self
+    {
+        self.scheduled.push(Shock {generation, change});
+        self
+    }
+
+    pub fn with_random(mut self, random:RandomShocks) -> Self
+    {
+        self.random = Some(random);
+        self
+    }
+}
+
+impl Run
+{
+    /// Shocks change the run's `Market`, which also becomes the run's fitness unless one was
+    /// set with `set_fitness` or `set_fitness_function`. A custom fitness is kept as it is and
+    /// only sees the cost shocks.
+    pub fn set_shocks(&mut self, shocks:ShockSchedule)
+    {
+        self.shocks = Some(shocks);
+    }
+
+    // applies this generation's shocks, returns true if there were any
+    pub(crate) fn apply_shocks(&mut self) -> bool
+    {
+        let Some(shocks) = &self.shocks else { return false };
+        let mut changes:Vec<MarketChange> = shocks.scheduled.iter()
+            .filter(|shock| shock.generation == self.period)
+            .map(|shock| shock.change)
+            .collect();
+        if let Some(random) = shocks.random {
+            let rng = &mut self.rng;
+            if rng.gen::<f64>() < random.probability {
+                changes.push(MarketChange::Intercept(rng.gen_range(-random.magnitude..=random.magnitude)));
+            }
+        }
+        if changes.is_empty() {
+            return false;
+        }
+
+        let mut market = self.market;
+        for change in changes {
+            if let MarketChange::Cost(delta) = change {
+                let n = self.n;
+                let costs = self.firm_costs.get_or_insert_with(|| vec![0.0; n]);
+                costs.iter_mut().for_each(|c| *c += delta);
+            }
+            market = market.shocked(change);
+        }
+        self.market = market;
+        if !self.custom_fitness {
+            self.fitness = Arc::new(market);
+        }
+        true
+    }
+}
+
+impl Market
+{
+    /// `self` with `change` applied, costs are not part of the market and are left out.
+    pub fn shocked(mut self, change:MarketChange) -> Self
+    {
+        match change {
+            MarketChange::Intercept(delta) => self.intercept += delta,
+            MarketChange::OwnSlope(delta) => self.own_slope += delta,
+            MarketChange::CrossSlope(delta) => self.cross_slope += delta,
+            MarketChange::Cost(_) => {}
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheduled_shocks_are_marked()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2).unwrap();
+        test_run.set_shocks(ShockSchedule::default()
+            .at(3, MarketChange::Intercept(10000.0))
+            .at(5, MarketChange::Cost(50.0)));
+        test_run.run(6).unwrap();
+
+        let shocked:Vec<u32> = test_run.history().iter().filter(|s| s.shock).map(|s| s.generation).collect();
+        assert_eq!(shocked, vec![3, 5]);
+        assert_eq!(test_run.market().intercept, 30000.0);
+        assert_eq!(test_run.firm_costs().unwrap()[0], 50.0);
+    }
+
+    #[test]
+    fn random_shocks()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2).unwrap();
+        test_run.set_shocks(ShockSchedule::default().with_random(RandomShocks {probability:1.0, magnitude:100.0}));
+        test_run.run(5).unwrap();
+        assert!(test_run.history().iter().all(|s| s.shock));
+        assert!((test_run.market().intercept - 20000.0).abs() <= 500.0);
+    }
+
+    #[test]
+    fn shocks_keep_a_custom_fitness()
+    {
+        let mut test_run = Run::new(0.322, 0.00522, 10, 30, 2).unwrap();
+        test_run.set_fitness(crate::problems::onemax_fitness);
+        test_run.set_shocks(ShockSchedule::default().at(2, MarketChange::Intercept(10000.0)));
+        test_run.run(5).unwrap();
+        assert_eq!(test_run.market().intercept, 30000.0);
+        assert!(test_run.history().iter().all(|s| s.best_fitness <= 10.0));
+    }
+}
@@ -0,0 +1,214 @@
+//! An SQLite database of experiments, behind the `sqlite` feature. Every recorded run gets a
+//! row in `runs` with its parameters and outcome, its stats go to `generations` and its best
+//! individuals to `best_individuals`, so runs can be compared with plain SQL:
+//!
+//! ```sql
+//! SELECT r.Pmut, AVG(r.best_fitness) FROM runs r GROUP BY r.Pmut;
+//! SELECT g.generation, g.mean_fitness FROM generations g WHERE g.run_id = ?;
+//! ```
+//!
+//! SQLite integers are signed, so genotypes are stored as the `i64` with the same bits.
+use crate::error::GaError;
+use crate::events::fnv1a;
+use crate::result::RunResult;
+use crate::stats::GenerationStats;
+use crate::Run;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA:&str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id TEXT PRIMARY KEY,
+    created_at INTEGER NOT NULL,
+    Pcross REAL NOT NULL,
+    Pmut REAL NOT NULL,
+    L INTEGER NOT NULL,
+    n INTEGER NOT NULL,
+    z INTEGER NOT NULL,
+    objective TEXT NOT NULL,
+    elitism INTEGER NOT NULL,
+    seed INTEGER,
+    config_hash TEXT NOT NULL,
+    stop_reason TEXT NOT NULL,
+    generations INTEGER NOT NULL,
+    evaluations INTEGER NOT NULL,
+    elapsed_s REAL NOT NULL,
+    best_data INTEGER,
+    best_fitness REAL
+);
+CREATE TABLE IF NOT EXISTS generations (
+    run_id TEXT NOT NULL REFERENCES runs(id),
+    generation INTEGER NOT NULL,
+    ind_out INTEGER, price REAL, consumer_surplus REAL, producer_surplus REAL, welfare REAL,
+    var REAL, mean_phenotype REAL, best_fitness REAL, worst_fitness REAL, mean_fitness REAL,
+    median_fitness REAL, std_fitness REAL, best_data INTEGER, best_phenotype INTEGER, diversity REAL,
+    feasible_fraction REAL, mean_violation REAL, best_feasible_fitness REAL, penalty REAL,
+    degenerate INTEGER, shock INTEGER, firms INTEGER, entrants INTEGER, exits INTEGER,
+    non_finite INTEGER, panics INTEGER, timeouts INTEGER, effective_size REAL,
+    stagnant_generations INTEGER, evaluation_s REAL, selection_s REAL, crossover_s REAL,
+    mutation_s REAL,
+    PRIMARY KEY (run_id, generation)
+);
+CREATE TABLE IF NOT EXISTS best_individuals (
+    run_id TEXT NOT NULL REFERENCES runs(id),
+    rank INTEGER NOT NULL,
+    data INTEGER NOT NULL,
+    fitness REAL NOT NULL,
+    violation REAL NOT NULL,
+    PRIMARY KEY (run_id, rank)
+);";
+
+const GENERATION_COLUMNS:usize = 35;
+
+fn generation_row(run_id:&str, stats:&GenerationStats) -> Vec<Value>
+{
+    let real = |value:f64| Value::Real(value);
+    let integer = |value:i64| Value::Integer(value);
+    let optional = |value:Option<f64>| value.map_or(Value::Null, Value::Real);
+    vec![
+        Value::Text(run_id.to_string()),
+        integer(stats.generation as i64),
+        integer(stats.ind_out as i64),
+        real(stats.price),
+        real(stats.welfare.consumer_surplus),
+        real(stats.welfare.producer_surplus),
+        real(stats.welfare.total),
+        real(stats.variance),
+        real(stats.mean_phenotype),
+        real(stats.best_fitness),
+        real(stats.worst_fitness),
+        real(stats.mean_fitness),
+        real(stats.median_fitness),
+        real(stats.std_fitness),
+        stats.best.as_ref().map_or(Value::Null, |best| integer(best.data as i64)),
+        stats.best_phenotype.map_or(Value::Null, |phenotype| integer(phenotype as i64)),
+        real(stats.diversity),
+        real(stats.feasible_fraction),
+        real(stats.mean_violation),
+        optional(stats.best_feasible_fitness),
+        real(stats.penalty),
+        integer(stats.degenerate as i64),
+        integer(stats.shock as i64),
+        integer(stats.firms as i64),
+        integer(stats.entrants as i64),
+        integer(stats.exits as i64),
+        integer(stats.non_finite as i64),
+        integer(stats.panics as i64),
+        integer(stats.timeouts as i64),
+        optional(stats.effective_size),
+        integer(stats.stagnant_generations as i64),
+        real(stats.timings.evaluation.as_secs_f64()),
+        real(stats.timings.selection.as_secs_f64()),
+        real(stats.timings.crossover.as_secs_f64()),
+        real(stats.timings.mutation.as_secs_f64()),
+    ]
+}
+
+pub struct ExperimentStore
+{
+    connection:Connection,
+}
+
+impl ExperimentStore
+{
+    /// Opens or creates the database at `path`.
+    pub fn open<P:AsRef<Path>>(path:P) -> Result<Self, GaError>
+    {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self, GaError>
+    {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection:Connection) -> Result<Self, GaError>
+    {
+        connection.execute_batch(SCHEMA)?;
+        Ok(ExperimentStore {connection})
+    }
+
+    /// For queries of your own.
+    pub fn connection(&self) -> &Connection
+    {
+        &self.connection
+    }
+
+    /// Records `run` and the `result` of its last call to `run`, returning the new run id: 16
+    /// hex digits hashed from the parameters, seed, outcome and time of recording, like a
+    /// commit id. The best individuals are the hall of fame if the run keeps one, else its
+    /// best individual.
+    pub fn record(&mut self, run:&Run, result:&RunResult) -> Result<String, GaError>
+    {
+        let manifest = run.manifest();
+        let created = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let run_id = format!("{:016x}", fnv1a(format!("{:?}|{:?}|{:?}|{}", manifest, result.stop_reason,
+            result.best_fitness(), created.as_nanos()).as_bytes()));
+
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO runs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+            params![run_id, created.as_secs() as i64, manifest.Pcross as f64, manifest.Pmut as f64, manifest.L,
+                manifest.n as i64, manifest.z, format!("{:?}", manifest.objective), manifest.elitism as i64,
+                manifest.seed.map(|seed| seed as i64), format!("{:016x}", manifest.config_hash),
+                format!("{:?}", result.stop_reason), run.generation(), result.evaluations as i64,
+                result.elapsed.as_secs_f64(), result.best.as_ref().map(|best| best.data as i64), result.best_fitness()],
+        )?;
+        {
+            let placeholders = (1..=GENERATION_COLUMNS).map(|i| format!("?{}", i)).collect::<Vec<String>>().join(", ");
+            let mut insert = transaction.prepare(&format!("INSERT INTO generations VALUES ({})", placeholders))?;
+            for stats in &result.stats {
+                insert.execute(params_from_iter(generation_row(&run_id, stats)))?;
+            }
+            let best = if result.hall_of_fame.is_empty() { result.best.iter().cloned().collect() } else { result.hall_of_fame.clone() };
+            let mut insert = transaction.prepare("INSERT INTO best_individuals VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            for (rank, ind) in best.iter().enumerate() {
+                insert.execute(params![run_id, rank as i64, ind.data as i64, ind.fitness, ind.violation])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(run_id)
+    }
+
+    /// Ids of the recorded runs, oldest first.
+    pub fn run_ids(&self) -> Result<Vec<String>, GaError>
+    {
+        let mut query = self.connection.prepare("SELECT id FROM runs ORDER BY created_at, rowid")?;
+        let ids = query.query_map([], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?;
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    #[test]
+    fn runs_can_be_compared_with_sql()
+    {
+        let mut store = ExperimentStore::in_memory().unwrap();
+        let mut ids = Vec::new();
+        for (seed, Pmut) in [(1, 0.01), (2, 0.1)] {
+            let mut test_run = Run::with_seed(0.6, Pmut, 10, 20, 4, seed).unwrap();
+            test_run.set_fitness(problems::onemax_fitness);
+            test_run.set_hall_of_fame(3);
+            let result = test_run.run(12).unwrap();
+            ids.push(store.record(&test_run, &result).unwrap());
+        }
+        assert_eq!(store.run_ids().unwrap(), ids);
+        assert!(ids.iter().all(|id| id.len() == 16) && ids[0] != ids[1]);
+
+        let connection = store.connection();
+        let generations:i64 = connection.query_row("SELECT COUNT(*) FROM generations WHERE run_id = ?1", [&ids[1]], |row| row.get(0)).unwrap();
+        assert_eq!(generations, 12);
+        let (seed, Pmut):(i64, f64) = connection.query_row("SELECT seed, Pmut FROM runs WHERE id = ?1", [&ids[1]], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        assert_eq!((seed, Pmut as f32), (2, 0.1));
+        let best:Vec<f64> = connection.prepare("SELECT fitness FROM best_individuals WHERE run_id = ?1 ORDER BY rank").unwrap()
+            .query_map([&ids[0]], |row| row.get(0)).unwrap().map(|f| f.unwrap()).collect();
+        assert_eq!(best.len(), 3);
+        assert!(best.windows(2).all(|w| w[0] >= w[1]));
+    }
+}
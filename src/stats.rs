@@ -0,0 +1,380 @@
+use crate::cournot::Welfare;
+use crate::error::GaError;
+use crate::objective::Objective;
+use crate::population::Population;
+use crate::Chromosome;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// What a fitness function may know about the population it is evaluated in.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PopulationStats
+{
+    pub generation:u32,
+    pub size:usize,
+    /// Sum of the decoded genomes, the industry output in the Cournot model.
+    pub data_sum:u64,
+}
+
+impl PopulationStats
+{
+    pub fn new(size:usize, data_sum:u64) -> Self
+    {
+        PopulationStats {generation:0, size, data_sum}
+    }
+
+    pub fn mean(&self) -> f64
+    {
+        if self.size == 0 { 0.0 } else { self.data_sum as f64 / self.size as f64 }
+    }
+}
+
+/// Wall-clock time spent in each phase of a generation.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct PhaseTimings
+{
+    pub evaluation:Duration,
+    pub selection:Duration,
+    pub crossover:Duration,
+    pub mutation:Duration,
+}
+
+impl PhaseTimings
+{
+    pub fn total(&self) -> Duration
+    {
+        self.evaluation + self.selection + self.crossover + self.mutation
+    }
+}
+
+impl AddAssign for PhaseTimings
+{
+    fn add_assign(&mut self, other:PhaseTimings)
+    {
+        self.evaluation += other.evaluation;
+        self.selection += other.selection;
+        self.crossover += other.crossover;
+        self.mutation += other.mutation;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats
+{
+    pub generation:u32,
+    pub ind_out:u64,
+    /// Output-weighted market price under the run's `Market`.
+    pub price:f64,
+    pub welfare:Welfare,
+    pub variance:f64,
+    /// Mean decoded quantity, `ind_out / firms`.
+    pub mean_phenotype:f64,
+    pub best_fitness:f64,
+    pub worst_fitness:f64,
+    pub mean_fitness:f64,
+    pub median_fitness:f64,
+    /// Population standard deviation of the fitness.
+    pub std_fitness:f64,
+    /// Individual with the best fitness, `None` for an empty population.
+    pub best:Option<Chromosome>,
+    /// `best` decoded by the run's `Decoding`, `None` without one.
+    #[serde(default)]
+    pub best_phenotype:Option<u64>,
+    /// Mean Hamming distance between two members, see `mean_hamming_distance`.
+    pub diversity:f64,
+    pub feasible_fraction:f64,
+    pub mean_violation:f64,
+    pub best_feasible_fitness:Option<f64>,
+    pub penalty:f64,
+    /// Selection fell back to uniform random because total fitness was zero.
+    pub degenerate:bool,
+    /// The market was shocked at the start of this generation.
+    pub shock:bool,
+    /// Population size after entry and exit, see `EntryExit`.
+    pub firms:usize,
+    pub entrants:usize,
+    pub exits:usize,
+    /// Number of NaN or infinite evaluations repaired by the `NonFinitePolicy`.
+    pub non_finite:usize,
+    /// Fitness evaluations that panicked and got the worst fitness under `PanicPolicy::Worst`.
+    pub panics:usize,
+    /// Evaluations that overran `Run::set_evaluation_timeout` and got the worst fitness.
+    pub timeouts:usize,
+    /// Crow-Denniston effective population size from the variance of the offspring counts
+    /// selection gave each slot, `None` in steady-state runs. See `effective_size`.
+    pub effective_size:Option<f64>,
+    /// Generations since the run's best last improved, zero if it improved in this one.
+    pub stagnant_generations:u32,
+    pub timings:PhaseTimings,
+}
+
+impl GenerationStats
+{
+    pub fn from_population(generation:u32, population:&[Chromosome], objective:Objective) -> Self
+    {
+        Self::from_columns(generation, &population.iter().cloned().collect(), objective)
+    }
+
+    pub(crate) fn from_columns(generation:u32, population:&Population, objective:Objective) -> Self
+    {
+        let n = population.len() as f64;
+        let ind_out = population.data_sum();
+        let mean = ind_out as f64 / n;
+        let variance = population.data.iter()
+            .map(|data| (*data as f64 - mean).powi(2))
+            .sum::<f64>() / n;
+
+        let feasible:Vec<usize> = (0..population.len()).filter(|&i| population.violation[i] <= 0.0).collect();
+        let best_feasible_fitness = objective.best(feasible.iter().map(|&i| population.fitness[i]));
+
+        let mut sorted = population.fitness.clone();
+        sorted.sort_by(|a, b| objective.best_first(*a, *b));
+        let mean_fitness = sorted.iter().sum::<f64>() / n;
+        let median_fitness = match sorted.len() {
+            0 => f64::NAN,
+            len if len % 2 == 1 => sorted[len / 2],
+            len => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+        };
+
+        GenerationStats {
+            generation,
+            ind_out,
+            price:0.0,
+            welfare:Welfare::default(),
+            variance,
+            mean_phenotype:mean,
+            best_fitness:sorted.first().copied().unwrap_or(f64::NAN),
+            worst_fitness:sorted.last().copied().unwrap_or(f64::NAN),
+            mean_fitness,
+            median_fitness,
+            std_fitness:(sorted.iter().map(|f| (f - mean_fitness).powi(2)).sum::<f64>() / n).sqrt(),
+            best:best_slot(population, objective).map(|i| population.get(i)),
+            best_phenotype:None,
+            diversity:mean_hamming_distance(&population.data),
+            feasible_fraction:feasible.len() as f64 / n,
+            mean_violation:population.violation.iter().sum::<f64>() / n,
+            best_feasible_fitness,
+            penalty:0.0,
+            degenerate:false,
+            shock:false,
+            firms:population.len(),
+            entrants:0,
+            exits:0,
+            non_finite:0,
+            panics:0,
+            timeouts:0,
+            effective_size:None,
+            stagnant_generations:0,
+            timings:PhaseTimings::default(),
+        }
+    }
+}
+
+// slot of the best fitness, the first one on ties
+pub(crate) fn best_slot(population:&Population, objective:Objective) -> Option<usize>
+{
+    let fitness = &population.fitness;
+    (0..fitness.len()).min_by(|&a, &b| objective.best_first(fitness[a], fitness[b]))
+}
+
+/// Mean Hamming distance over all pairs of `genomes`, zero for fewer than two. Computed from
+/// the count of ones at each bit, so it costs `O(64 n)` rather than `O(n^2)`.
+pub fn mean_hamming_distance(genomes:&[u64]) -> f64
+{
+    let n = genomes.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let differing:u64 = (0..64).map(|bit| {
+        let ones = genomes.iter().filter(|g| *g >> bit & 1 == 1).count() as u64;
+        ones * (n as u64 - ones)
+    }).sum();
+    differing as f64 / (n * (n - 1) / 2) as f64
+}
+
+/// Effective population size `(N k - 1) / (k - 1 + V / k)` (Crow and Denniston) of `N` parents
+/// whose offspring counts have mean `k` and variance `V`. With one offspring per parent on
+/// average, Poisson-distributed counts give `N - 1` and equal counts give infinity.
+pub fn effective_size(offspring_counts:&[usize]) -> f64
+{
+    let n = offspring_counts.len() as f64;
+    let mean = offspring_counts.iter().sum::<usize>() as f64 / n;
+    let variance = offspring_counts.iter().map(|&k| (k as f64 - mean).powi(2)).sum::<f64>() / n;
+    (n * mean - 1.0) / (mean - 1.0 + variance / mean)
+}
+
+/// Which generations to keep in memory: all of the first `keep_first`, then every `every`-th,
+/// plus (with `keep_improvements`) any generation where the best individual improved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy
+{
+    pub keep_first:u32,
+    pub every:u32,
+    pub keep_improvements:bool,
+}
+
+impl RetentionPolicy
+{
+    pub fn retains(&self, generation:u32, improved:bool) -> bool
+    {
+        generation <= self.keep_first
+            || (self.every > 0 && generation.is_multiple_of(self.every))
+            || (self.keep_improvements && improved)
+    }
+}
+
+const STATS_HEADER:[&str; 34] = ["generation", "ind_out", "price", "consumer_surplus", "producer_surplus",
+    "welfare", "var", "mean_phenotype", "best_fitness", "worst_fitness", "mean_fitness", "median_fitness", "std_fitness",
+    "best_data", "best_phenotype", "diversity", "feasible_fraction", "mean_violation",
+    "best_feasible_fitness", "penalty", "degenerate", "shock", "firms", "entrants", "exits", "non_finite", "panics", "timeouts", "effective_size", "stagnant_generations",
+    "evaluation_s", "selection_s", "crossover_s", "mutation_s"];
+
+fn stats_record(stats:&GenerationStats) -> Vec<String>
+{
+    vec![
+        stats.generation.to_string(),
+        stats.ind_out.to_string(),
+        stats.price.to_string(),
+        stats.welfare.consumer_surplus.to_string(),
+        stats.welfare.producer_surplus.to_string(),
+        stats.welfare.total.to_string(),
+        stats.variance.to_string(),
+        stats.mean_phenotype.to_string(),
+        stats.best_fitness.to_string(),
+        stats.worst_fitness.to_string(),
+        stats.mean_fitness.to_string(),
+        stats.median_fitness.to_string(),
+        stats.std_fitness.to_string(),
+        stats.best.as_ref().map_or(String::new(), |best| best.data.to_string()),
+        stats.best_phenotype.map_or(String::new(), |phenotype| phenotype.to_string()),
+        stats.diversity.to_string(),
+        stats.feasible_fraction.to_string(),
+        stats.mean_violation.to_string(),
+        stats.best_feasible_fitness.map_or(String::new(), |f| f.to_string()),
+        stats.penalty.to_string(),
+        stats.degenerate.to_string(),
+        stats.shock.to_string(),
+        stats.firms.to_string(),
+        stats.entrants.to_string(),
+        stats.exits.to_string(),
+        stats.non_finite.to_string(),
+        stats.panics.to_string(),
+        stats.timeouts.to_string(),
+        stats.effective_size.map_or(String::new(), |ne| ne.to_string()),
+        stats.stagnant_generations.to_string(),
+        stats.timings.evaluation.as_secs_f64().to_string(),
+        stats.timings.selection.as_secs_f64().to_string(),
+        stats.timings.crossover.as_secs_f64().to_string(),
+        stats.timings.mutation.as_secs_f64().to_string(),
+    ]
+}
+
+pub fn save_stats_to_csv(data:&[GenerationStats], file_name:&str) -> Result<(), GaError>
+{
+    let file = File::create(file_name)?;
+    let mut writer = csv::Writer::from_writer(file);
+
+    writer.write_record(STATS_HEADER)?;
+
+    for stats in data {
+        writer.write_record(stats_record(stats))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Appends stats rows to a CSV file as they are produced, in the `save_stats_to_csv` format.
+pub struct StatsWriter
+{
+    writer:csv::Writer<File>,
+}
+
+impl StatsWriter
+{
+    /// Opens `file_name` for appending, writing the header only if the file is new or empty.
+    pub fn append(file_name:&str) -> Result<Self, csv::Error>
+    {
+        let file = OpenOptions::new().create(true).append(true).open(file_name)?;
+        let empty = file.metadata()?.len() == 0;
+        let mut writer = csv::Writer::from_writer(file);
+        if empty {
+            writer.write_record(STATS_HEADER)?;
+        }
+        Ok(StatsWriter {writer})
+    }
+
+    pub fn write(&mut self, stats:&GenerationStats) -> Result<(), csv::Error>
+    {
+        self.writer.write_record(stats_record(stats))
+    }
+
+    pub fn flush(&mut self) -> Result<(), csv::Error>
+    {
+        Ok(self.writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_size_from_offspring_counts()
+    {
+        assert_eq!(effective_size(&[1, 1, 1, 1]), f64::INFINITY);
+        // one parent leaves all offspring: mean 1, variance 3
+        assert_eq!(effective_size(&[4, 0, 0, 0]), 1.0);
+        assert_eq!(effective_size(&[2, 0, 2, 0]), 3.0);
+    }
+
+    #[test]
+    fn retention_keeps_improvements()
+    {
+        let policy = RetentionPolicy {keep_first:3, every:10, keep_improvements:true};
+        assert!(policy.retains(3, false));
+        assert!(!policy.retains(4, false));
+        assert!(policy.retains(4, true));
+        assert!(policy.retains(20, false));
+
+        let policy = RetentionPolicy {keep_first:0, every:0, keep_improvements:false};
+        assert!(!policy.retains(1, true));
+    }
+
+    #[test]
+    fn feasibility_summary()
+    {
+        let population = vec![
+            Chromosome {data:1, fitness:5.0, N:3, violation:0.0},
+            Chromosome {data:2, fitness:9.0, N:3, violation:3.0},
+            Chromosome {data:3, fitness:4.0, N:3, violation:0.0},
+        ];
+        let stats = GenerationStats::from_population(7, &population, Objective::Maximize);
+        assert_eq!(stats.generation, 7);
+        assert_eq!(stats.ind_out, 6);
+        assert!((stats.feasible_fraction - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(stats.mean_violation, 1.0);
+        assert_eq!(stats.best_feasible_fitness, Some(5.0));
+
+        let stats = GenerationStats::from_population(7, &population, Objective::Minimize);
+        assert_eq!(stats.best_feasible_fitness, Some(4.0));
+    }
+
+    #[test]
+    fn fitness_and_diversity_summary()
+    {
+        let population:Vec<Chromosome> = [(0b000, 2.0), (0b011, 8.0), (0b101, 4.0), (0b110, 6.0)].iter()
+            .map(|&(data, fitness)| Chromosome {data, fitness, N:4, violation:0.0}).collect();
+        let stats = GenerationStats::from_population(1, &population, Objective::Minimize);
+        assert_eq!((stats.best_fitness, stats.worst_fitness), (2.0, 8.0));
+        assert_eq!((stats.mean_fitness, stats.median_fitness), (5.0, 5.0));
+        assert_eq!(stats.std_fitness, 5.0f64.sqrt());
+        assert_eq!(stats.best.unwrap().data, 0);
+        assert_eq!(stats.mean_phenotype, 3.5);
+        // every pair differs in exactly two bits
+        assert_eq!(stats.diversity, 2.0);
+        assert_eq!(mean_hamming_distance(&[7]), 0.0);
+    }
+}
@@ -0,0 +1,119 @@
+//! Steady-state reproduction. Instead of replacing the whole population every generation, each
+//! iteration selects two parents, crosses and mutates them, and the two children take the place
+//! of members chosen by the `ReplacementPolicy`. Children are evaluated with the next iteration,
+//! and they are the only ones: the other members keep the fitness they were evaluated with,
+//! unless a market shock has the whole population evaluated again.
+use crate::{ops, Run};
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplacementPolicy
+{
+    /// Children replace the two worst members, infeasible ones first.
+    Worst,
+    /// Children replace two members drawn uniformly at random.
+    Random,
+}
+
+impl Run
+{
+    /// Switches the run to steady-state reproduction, see `ReplacementPolicy`. Selection,
+    /// crossover and mutation operators are used as in generational runs.
+    pub fn set_steady_state(&mut self, policy:ReplacementPolicy)
+    {
+        self.steady_state = Some(policy);
+    }
+
+    pub fn steady_state(&self) -> Option<ReplacementPolicy>
+    {
+        self.steady_state
+    }
+
+    pub(crate) fn replace_with_offspring(&mut self, parents:&[usize], policy:ReplacementPolicy)
+    {
+        let rng = &mut self.rng;
+        let (a, b) = (self.population.data[parents[0]], self.population.data[parents[1]]);
+        let mut children = match &self.crossover {
+            Some(crossover) if rng.gen::<f32>() < self.Pcross => {
+                let (a, b) = crossover.crossover(a, b, self.L, rng);
+                vec![a, b]
+            }
+            Some(_) => vec![a, b],
+            None => ops::crossover(&[a, b], &[(0, 1)], self.Pcross, self.L, self.z, rng),
+        };
+        match &self.mutation {
+            Some(mutation) => mutation.mutate(&mut children, self.L, rng),
+            None => children = self.mutation_scheme.apply(&children, self.Pmut, self.L, rng),
+        }
+
+        let slots = match policy {
+            ReplacementPolicy::Worst => self.worst(2),
+            ReplacementPolicy::Random => rand::seq::index::sample(rng, self.population.len(), 2).into_vec(),
+        };
+        for (&slot, child) in slots.iter().zip(children) {
+            self.population.data[slot] = child;
+        }
+        self.stale = Some(slots);
+    }
+
+    // slots of the `count` worst members, infeasible before feasible
+    pub(crate) fn worst(&self, count:usize) -> Vec<usize>
+    {
+        let (fitness, violation) = (&self.population.fitness, &self.population.violation);
+        let mut order:Vec<usize> = (0..fitness.len()).collect();
+        order.sort_by(|&a, &b| (violation[a] <= 0.0).cmp(&(violation[b] <= 0.0))
+            .then(self.objective.best_first(fitness[b], fitness[a])));
+        order.truncate(count);
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    #[test]
+    fn replaces_the_worst()
+    {
+        let mut test_run = Run::new(1.0, 0.0, 8, 6, 4).unwrap();
+        test_run.population.fitness = vec![5.0, 1.0, 4.0, 0.5, 3.0, 2.0];
+        test_run.population.violation = vec![0.0; 6];
+        let kept = [0, 2, 4, 5].map(|i| test_run.population.data[i]);
+        test_run.replace_with_offspring(&[0, 2], ReplacementPolicy::Worst);
+        assert_eq!([0, 2, 4, 5].map(|i| test_run.population.data[i]), kept);
+        assert_eq!(test_run.worst(1), vec![3]);
+    }
+
+    #[test]
+    fn steady_state_run_improves()
+    {
+        let mut test_run = Run::new(0.9, 0.2, 16, 20, 8).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.set_steady_state(ReplacementPolicy::Worst);
+        test_run.run(300).unwrap();
+        let best:Vec<f64> = test_run.history().iter().map(|s| s.best_feasible_fitness.unwrap()).collect();
+        assert!(best.windows(2).all(|w| w[1] >= w[0]), "{:?}", best);
+        assert!(test_run.best().unwrap().fitness >= 13.0, "{:?}", test_run.best());
+        assert_eq!(test_run.population.len(), 20);
+    }
+
+    #[test]
+    fn steady_state_steps_evaluate_the_children()
+    {
+        let mut test_run = Run::new(0.9, 0.2, 16, 20, 8).unwrap();
+        test_run.set_fitness(problems::onemax_fitness);
+        test_run.set_steady_state(ReplacementPolicy::Worst);
+        test_run.run(1).unwrap();
+        assert_eq!(test_run.evaluations(), 20);
+        test_run.run(10).unwrap();
+        assert_eq!(test_run.evaluations(), 40);
+        // the fitness of the children the last iteration left is still to come
+        test_run.calculate_data_sum();
+        test_run.calculate_iteration_fitness().unwrap();
+        assert_eq!(test_run.evaluations(), 42);
+        for ind in test_run.population.iter() {
+            assert_eq!(ind.fitness, problems::onemax_fitness(&ind, 0));
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Parameter sweeps: every combination of the listed `Pcross`, `Pmut`, `n` and `L` values, each
+//! run a number of times with seeds derived from one master seed, on as many threads as asked
+//! for. The seeds are handed out in grid order before anything runs, so the results do not
+//! depend on the thread count. `summarize` aggregates the replicates of each configuration.
+use crate::config::RunConfig;
+use crate::error::GaError;
+use crate::result::StopReason;
+use crate::seeds::SeedSequence;
+use crate::Run;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep
+{
+    /// Settings shared by every run, and the value of any parameter whose list is empty.
+    pub base:RunConfig,
+    pub Pcross:Vec<f32>,
+    pub Pmut:Vec<f32>,
+    pub n:Vec<usize>,
+    pub L:Vec<u8>,
+    pub replicates:u64,
+    /// Worker threads; with one, everything runs in order on the calling thread.
+    pub threads:usize,
+}
+
+impl Default for Sweep
+{
+    fn default() -> Self
+    {
+        Sweep {base:RunConfig::default(), Pcross:Vec::new(), Pmut:Vec::new(), n:Vec::new(), L:Vec::new(), replicates:1, threads:1}
+    }
+}
+
+/// One replicate of one configuration; `config.seed` is the seed it ran with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepRun
+{
+    pub config:RunConfig,
+    pub replicate:u64,
+    pub best_fitness:Option<f64>,
+    pub evaluations:usize,
+    pub stop_reason:StopReason,
+}
+
+/// The replicates of one configuration aggregated, `config.seed` being `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepSummary
+{
+    pub config:RunConfig,
+    /// Replicates that found a best individual, the ones the statistics are over.
+    pub runs:usize,
+    pub mean_best_fitness:f64,
+    /// Sample standard deviation, zero for a single run.
+    pub std_best_fitness:f64,
+    pub mean_evaluations:f64,
+}
+
+impl Sweep
+{
+    /// Every combination of the parameter lists, `Pcross` varying slowest and `L` fastest.
+    pub fn configurations(&self) -> Vec<RunConfig>
+    {
+        let or_base = |values:&[f32], base:f32| if values.is_empty() { vec![base] } else { values.to_vec() };
+        let sizes = if self.n.is_empty() { vec![self.base.n] } else { self.n.clone() };
+        let lengths = if self.L.is_empty() { vec![self.base.L] } else { self.L.clone() };
+        let mut configurations = Vec::new();
+        for &Pcross in &or_base(&self.Pcross, self.base.Pcross) {
+            for &Pmut in &or_base(&self.Pmut, self.base.Pmut) {
+                for &n in &sizes {
+                    for &L in &lengths {
+                        configurations.push(RunConfig {Pcross, Pmut, n, L, seed:None, ..self.base.clone()});
+                    }
+                }
+            }
+        }
+        configurations
+    }
+
+    /// Runs `replicates` runs of every configuration for `base.iterations` generations, `build`
+    /// making each run from its configuration and seed, labelled `"sweep"` in `seeds`. The
+    /// results come back in grid order, the replicates of a configuration next to each other,
+    /// or the first error of a run in that order.
+    pub fn run<F:Fn(&RunConfig) -> Run + Sync>(&self, seeds:&mut SeedSequence, build:F) -> Result<Vec<SweepRun>, GaError>
+    {
+        let mut jobs = Vec::new();
+        for config in self.configurations() {
+            for replicate in 0..self.replicates {
+                let seed = seeds.derive("sweep", jobs.len() as u64);
+                jobs.push((RunConfig {seed:Some(seed), ..config.clone()}, replicate));
+            }
+        }
+        let execute = |(config, replicate):&(RunConfig, u64)| {
+            let result = build(config).run(config.iterations)?;
+            Ok(SweepRun {config:config.clone(), replicate:*replicate, best_fitness:result.best_fitness(),
+                evaluations:result.evaluations, stop_reason:result.stop_reason})
+        };
+        if self.threads <= 1 {
+            return jobs.iter().map(execute).collect();
+        }
+        // workers take the next job until none are left
+        let next = AtomicUsize::new(0);
+        let mut finished:Vec<(usize, Result<SweepRun, GaError>)> = thread::scope(|scope| {
+            let workers:Vec<_> = (0..self.threads.min(jobs.len())).map(|_| scope.spawn(|| {
+                let mut done = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else { break };
+                    done.push((index, execute(job)));
+                }
+                done
+            })).collect();
+            workers.into_iter().flat_map(|worker| worker.join().expect("sweep run panicked")).collect()
+        });
+        finished.sort_by_key(|(index, _)| *index);
+        finished.into_iter().map(|(_, run)| run).collect()
+    }
+}
+
+/// Mean and spread of the final best fitness of each configuration, in the order of `runs`.
+pub fn summarize(runs:&[SweepRun]) -> Vec<SweepSummary>
+{
+    let mut summaries:Vec<(RunConfig, Vec<f64>, Vec<usize>)> = Vec::new();
+    for run in runs {
+        let config = RunConfig {seed:None, ..run.config.clone()};
+        if summaries.last().is_none_or(|(last, _, _)| last != &config) {
+            summaries.push((config, Vec::new(), Vec::new()));
+        }
+        let (_, best, evaluations) = summaries.last_mut().unwrap();
+        best.extend(run.best_fitness);
+        evaluations.push(run.evaluations);
+    }
+    summaries.into_iter().map(|(config, best, evaluations)| {
+        let mean = best.iter().sum::<f64>() / best.len() as f64;
+        let std = if best.len() > 1 {
+            (best.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / (best.len() - 1) as f64).sqrt()
+        }else{
+            0.0
+        };
+        SweepSummary {config, runs:best.len(), mean_best_fitness:mean, std_best_fitness:std,
+            mean_evaluations:evaluations.iter().sum::<usize>() as f64 / evaluations.len() as f64}
+    }).collect()
+}
+
+/// Writes one row per configuration: its parameters, replicate count and statistics.
+pub fn save_sweep_summary_to_csv(summaries:&[SweepSummary], file_name:&str) -> Result<(), GaError>
+{
+    let mut writer = csv::Writer::from_path(file_name)?;
+    writer.write_record(["Pcross", "Pmut", "n", "L", "runs", "mean_best_fitness", "std_best_fitness", "mean_evaluations"])?;
+    for summary in summaries {
+        let config = &summary.config;
+        writer.write_record([config.Pcross.to_string(), config.Pmut.to_string(), config.n.to_string(), config.L.to_string(),
+            summary.runs.to_string(), summary.mean_best_fitness.to_string(), summary.std_best_fitness.to_string(),
+            summary.mean_evaluations.to_string()])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems;
+
+    fn onemax(config:&RunConfig) -> Run
+    {
+        let mut run = Run::with_seed(config.Pcross, config.Pmut, config.L, config.n, config.z, config.seed.unwrap()).unwrap();
+        run.set_fitness(problems::onemax_fitness);
+        run
+    }
+
+    fn sweep(threads:usize) -> Sweep
+    {
+        let base = RunConfig {L:12, n:10, z:6, iterations:15, ..RunConfig::default()};
+        Sweep {base, Pcross:vec![0.6, 0.9], Pmut:vec![0.01, 0.1], replicates:3, threads, ..Sweep::default()}
+    }
+
+    #[test]
+    fn threads_do_not_change_the_results()
+    {
+        let sequential = sweep(1).run(&mut SeedSequence::new(5), onemax).unwrap();
+        let parallel = sweep(4).run(&mut SeedSequence::new(5), onemax).unwrap();
+        assert_eq!(sequential.len(), 12);
+        assert_eq!(parallel, sequential);
+        assert_eq!((sequential[4].config.Pcross, sequential[4].config.Pmut, sequential[4].replicate), (0.6, 0.1, 1));
+    }
+
+    #[test]
+    fn replicates_are_aggregated_per_configuration()
+    {
+        let runs = sweep(2).run(&mut SeedSequence::new(9), onemax).unwrap();
+        let summaries = summarize(&runs);
+        assert_eq!(summaries.len(), 4);
+        let best:Vec<f64> = runs[3..6].iter().map(|run| run.best_fitness.unwrap()).collect();
+        let mean = best.iter().sum::<f64>() / 3.0;
+        assert_eq!((summaries[1].config.Pcross, summaries[1].config.Pmut, summaries[1].runs), (0.6, 0.1, 3));
+        assert!((summaries[1].mean_best_fitness - mean).abs() < 1e-12);
+        let variance = best.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / 2.0;
+        assert!((summaries[1].std_best_fitness - variance.sqrt()).abs() < 1e-12);
+
+        let path = std::env::temp_dir().join(format!("sweep_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_sweep_summary_to_csv(&summaries, path).unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(content.lines().count(), 5);
+        assert!(content.starts_with("Pcross,Pmut,n,L,runs,mean_best_fitness"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+//! Capacitated vehicle routing. A genome is a giant tour over all customers without depot
+//! visits; it is decoded by splitting the tour into capacity-feasible routes with the shortest
+//! total distance (Prins' split), so every permutation decodes to a valid set of routes.
+//! Customers whose demand alone exceeds the capacity, and fleets larger than `max_vehicles`,
+//! are reported as constraint violation. `build_run` sets up a `PermutationRun` minimizing the
+//! distance with that violation as a penalized constraint.
+use crate::objective::Objective;
+use crate::permutation::{PermutationChromosome, PermutationCrossover, PermutationRun};
+use crate::routing::DistanceMatrix;
+use rand::Rng;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cvrp
+{
+    /// Node 0 is the depot, node `i + 1` is customer `i`.
+    pub distances:DistanceMatrix,
+    /// Demand of every node, the depot's is ignored.
+    pub demands:Vec<f64>,
+    pub capacity:f64,
+    pub max_vehicles:Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VrpSolution
+{
+    /// Node indices of every route, depot excluded.
+    pub routes:Vec<Vec<usize>>,
+    pub distance:f64,
+    /// Excess load summed over routes plus the number of vehicles beyond `max_vehicles`.
+    pub violation:f64,
+}
+
+impl Cvrp
+{
+    /// Panics unless there is one demand per node of `distances`.
+    pub fn new(distances:DistanceMatrix, demands:Vec<f64>, capacity:f64) -> Self
+    {
+        assert_eq!(distances.len(), demands.len(), "need one demand per node");
+        assert!(!distances.is_empty(), "the matrix must contain the depot");
+        Cvrp {distances, demands, capacity, max_vehicles:None}
+    }
+
+    /// Euclidean instance with the depot at `points[0]`.
+    pub fn from_points(points:&[(f64, f64)], demands:Vec<f64>, capacity:f64) -> Self
+    {
+        Self::new(DistanceMatrix::from_points(points), demands, capacity)
+    }
+
+    pub fn with_max_vehicles(mut self, max_vehicles:usize) -> Self
+    {
+        self.max_vehicles = Some(max_vehicles);
+        self
+    }
+
+    /// Number of customers, the genome length.
+    pub fn customers(&self) -> usize
+    {
+        self.distances.len() - 1
+    }
+
+    pub fn random_population<R:Rng + ?Sized>(&self, size:usize, rng:&mut R) -> Vec<PermutationChromosome>
+    {
+        (0..size).map(|_| PermutationChromosome::random(self.customers(), rng)).collect()
+    }
+
+    /// Optimal split of the giant tour `tour` (customer indices) into routes.
+    pub fn split(&self, tour:&[usize]) -> VrpSolution
+    {
+        let d = &self.distances;
+        let nodes:Vec<usize> = tour.iter().map(|c| c + 1).collect();
+        let len = nodes.len();
+        // cost[j] is the shortest distance serving the first j customers, pred[j] where its last route starts
+        let mut cost = vec![f64::INFINITY; len + 1];
+        let mut pred = vec![0; len + 1];
+        cost[0] = 0.0;
+        for i in 0..len {
+            let mut load = 0.0;
+            let mut route = 0.0;
+            for j in i..len {
+                load += self.demands[nodes[j]];
+                if j > i && load > self.capacity {
+                    break;
+                }
+                route = if j == i {
+                    d.get(0, nodes[j]) + d.get(nodes[j], 0)
+                }else{
+                    route - d.get(nodes[j - 1], 0) + d.get(nodes[j - 1], nodes[j]) + d.get(nodes[j], 0)
+                };
+                if cost[i] + route < cost[j + 1] {
+                    cost[j + 1] = cost[i] + route;
+                    pred[j + 1] = i;
+                }
+            }
+        }
+
+        let mut routes = Vec::new();
+        let mut end = len;
+        while end > 0 {
+            routes.push(nodes[pred[end]..end].to_vec());
+            end = pred[end];
+        }
+        routes.reverse();
+        let excess:f64 = routes.iter()
+            .map(|route| (route.iter().map(|&node| self.demands[node]).sum::<f64>() - self.capacity).max(0.0))
+            .sum();
+        let extra_vehicles = self.max_vehicles.map_or(0, |max| routes.len().saturating_sub(max));
+        VrpSolution {routes, distance:cost[len], violation:excess + extra_vehicles as f64}
+    }
+
+    /// Fitness to minimize: the total distance of the routes the giant tour splits into.
+    pub fn fitness(&self) -> impl Fn(&[usize]) -> f64 + Send + Sync + 'static
+    {
+        let cvrp = self.clone();
+        move |tour:&[usize]| cvrp.split(tour).distance
+    }
+
+    /// Constraint for `GenomeRun::add_constraint`: the excess load and extra vehicles of the
+    /// routes, see `VrpSolution::violation`.
+    pub fn constraint(&self) -> impl Fn(&PermutationChromosome) -> f64 + Send + Sync + 'static
+    {
+        let cvrp = self.clone();
+        move |ind:&PermutationChromosome| cvrp.split(&ind.order).violation
+    }
+
+    /// `n` random giant tours minimizing `fitness` with edge recombination, each unit of
+    /// violation adding `penalty` to the distance.
+    pub fn build_run(&self, n:usize, penalty:f64) -> PermutationRun
+    {
+        let mut run = PermutationRun::new(self.customers(), n, self.fitness());
+        run.set_objective(Objective::Minimize);
+        run.crossover = PermutationCrossover::EdgeRecombination;
+        run.add_constraint(self.constraint());
+        run.set_penalty(penalty);
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_instance() -> Cvrp
+    {
+        // depot at the origin, customers at 1..=4 on a line with demand 1 each
+        let points:Vec<(f64, f64)> = (0..5).map(|i| (i as f64, 0.0)).collect();
+        Cvrp::from_points(&points, vec![0.0, 1.0, 1.0, 1.0, 1.0], 2.0)
+    }
+
+    #[test]
+    fn split_respects_capacity()
+    {
+        let cvrp = line_instance();
+        let solution = cvrp.split(&[0, 1, 2, 3]);
+        assert_eq!(solution.routes, vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(solution.distance, 4.0 + 8.0);
+        assert_eq!(solution.violation, 0.0);
+        assert!(solution.routes.iter().all(|r| r.len() <= 2));
+    }
+
+    #[test]
+    fn violations_are_reported()
+    {
+        let mut cvrp = line_instance().with_max_vehicles(1);
+        let ind = PermutationChromosome::new(vec![0, 1, 2, 3]);
+        assert_eq!(cvrp.constraint()(&ind), 1.0);
+        assert_eq!(cvrp.fitness()(&ind.order), 12.0);
+
+        cvrp.demands[2] = 5.0;
+        cvrp.max_vehicles = None;
+        assert_eq!(cvrp.split(&[1]).violation, 3.0);
+    }
+
+    #[test]
+    fn run_finds_feasible_routes()
+    {
+        let cvrp = line_instance().with_max_vehicles(2);
+        let mut test_run = cvrp.build_run(10, 100.0);
+        test_run.set_seed(3);
+        test_run.run(20);
+        let best = test_run.best().unwrap();
+        assert_eq!(cvrp.split(&best.order).violation, 0.0);
+        assert_eq!(best.fitness, 12.0);
+    }
+}